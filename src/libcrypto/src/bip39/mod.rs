@@ -0,0 +1,349 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! BIP39 mnemonic sentences, the word lists wallets show users to back up their seed.
+//!
+//! The `Seed` a mnemonic gives is the input to `veil::bip32::ExtendedPrivKey::new_master`.
+
+mod english;
+
+use rand::{RngCore, rngs::OsRng};
+use std::{error, fmt, str};
+use unicode_normalization::UnicodeNormalization;
+
+use self::english::WORDS;
+use crate::hash::{pbkdf2_hmac_sha512, sha256};
+use crate::util::zero;
+
+/// Iterations of PBKDF2 used to stretch a mnemonic into a seed.
+const PBKDF2_ROUNDS: u32 = 2048;
+/// Prefix of the PBKDF2 salt, the passphrase follows it.
+const SALT_PREFIX: &str = "mnemonic";
+/// Each word encodes 11 bits, an index into the wordlist.
+const BITS_PER_WORD: usize = 11;
+const MAX_ENTROPY_SIZE: usize = 32;
+
+#[derive(Debug, PartialEq)]
+pub enum Bip39Error {
+    /// Entropy must be 16 to 32 bytes in steps of 4.
+    InvalidEntropyLength(usize),
+    /// Mnemonics have 12, 15, 18, 21 or 24 words.
+    InvalidWordCount(usize),
+    /// The word at this position is not in the wordlist. The word itself is left out, as it is
+    /// part of a secret.
+    UnknownWord(usize),
+    InvalidChecksum,
+}
+
+impl fmt::Display for Bip39Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bip39Error::InvalidEntropyLength(len) => write!(f, "invalid entropy length {}", len),
+            Bip39Error::InvalidWordCount(count) => write!(f, "invalid word count {}", count),
+            Bip39Error::UnknownWord(position) => {
+                write!(f, "word {} is not in the wordlist", position + 1)
+            }
+            Bip39Error::InvalidChecksum => f.write_str("invalid mnemonic checksum"),
+        }
+    }
+}
+
+impl error::Error for Bip39Error {}
+
+/// A validated mnemonic sentence, its words separated by single spaces.
+#[derive(PartialEq)]
+pub struct Mnemonic(String);
+
+impl Mnemonic {
+    /// A new mnemonic of `word_count` words from the OS random number generator. Veil's wallet
+    /// uses 24 words.
+    pub fn generate(word_count: usize) -> Result<Self, Bip39Error> {
+        if !is_valid_word_count(word_count) {
+            return Err(Bip39Error::InvalidWordCount(word_count));
+        }
+        let mut entropy = [0u8; MAX_ENTROPY_SIZE];
+        let len = word_count * BITS_PER_WORD * 32 / 33 / 8;
+        OsRng.fill_bytes(&mut entropy[..len]);
+        let mnemonic = Self::from_entropy(&entropy[..len]);
+        zero(&mut entropy);
+        mnemonic
+    }
+
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, Bip39Error> {
+        let len = entropy.len();
+        if !matches!(len, 16 | 20 | 24 | 28 | 32) {
+            return Err(Bip39Error::InvalidEntropyLength(len));
+        }
+        // The checksum is the first `len / 4` bits of the hash, appended to the entropy.
+        let mut bits = [0u8; MAX_ENTROPY_SIZE + 1];
+        bits[..len].copy_from_slice(entropy);
+        bits[len] = sha256(entropy)[0];
+
+        let word_count = (len * 8 + len / 4) / BITS_PER_WORD;
+        let mut phrase = String::with_capacity(word_count * 9);
+        for i in 0..word_count {
+            if i > 0 {
+                phrase.push(' ');
+            }
+            phrase.push_str(WORDS[read_index(&bits, i)]);
+        }
+        zero(&mut bits);
+        Ok(Mnemonic(phrase))
+    }
+
+    /// Checks each word is in the wordlist and the checksum matches. Words may be separated by
+    /// any whitespace.
+    pub fn parse(phrase: &str) -> Result<Self, Bip39Error> {
+        let mut entropy = [0u8; MAX_ENTROPY_SIZE];
+        let len = decode(phrase, &mut entropy)?;
+        zero(&mut entropy[..len]);
+        Ok(Mnemonic(
+            phrase.split_whitespace().collect::<Vec<_>>().join(" "),
+        ))
+    }
+
+    pub fn phrase(&self) -> &str {
+        &self.0
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.0.split(' ').count()
+    }
+
+    /// The entropy the mnemonic encodes, without its checksum.
+    pub fn to_entropy(&self) -> Vec<u8> {
+        let mut entropy = [0u8; MAX_ENTROPY_SIZE];
+        let len = decode(&self.0, &mut entropy).expect("mnemonic was validated");
+        let vec = entropy[..len].to_vec();
+        zero(&mut entropy);
+        vec
+    }
+
+    /// Stretches the mnemonic and an optional passphrase, which may be empty, into a seed with
+    /// PBKDF2-HMAC-SHA512. Any passphrase gives a valid seed, a wrong one just gives a different
+    /// wallet.
+    pub fn to_seed(&self, passphrase: &str) -> Seed {
+        let mut salt = String::with_capacity(SALT_PREFIX.len() + passphrase.len());
+        salt.push_str(SALT_PREFIX);
+        salt.extend(passphrase.nfkd());
+
+        let mut seed = [0u8; 64];
+        pbkdf2_hmac_sha512(self.0.as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS, &mut seed);
+        zero(unsafe { salt.as_bytes_mut() });
+        Seed(seed)
+    }
+}
+
+impl str::FromStr for Mnemonic {
+    type Err = Bip39Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip39Error> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Mnemonic(..)")
+    }
+}
+
+impl Drop for Mnemonic {
+    fn drop(&mut self) {
+        // Zero bytes are valid UTF-8, so the string stays valid.
+        zero(unsafe { self.0.as_bytes_mut() });
+    }
+}
+
+/// The 64 byte seed a mnemonic and passphrase stretch to, the root of a BIP32 key tree.
+pub struct Seed([u8; 64]);
+
+impl Seed {
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Seed(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Seed {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Seed(..)")
+    }
+}
+
+impl Drop for Seed {
+    fn drop(&mut self) {
+        zero(&mut self.0);
+    }
+}
+
+fn is_valid_word_count(word_count: usize) -> bool {
+    matches!(word_count, 12 | 15 | 18 | 21 | 24)
+}
+
+/// Reads the wordlist index of the `word`th word out of `bits`.
+fn read_index(bits: &[u8], word: usize) -> usize {
+    (0..BITS_PER_WORD).fold(0, |index, i| {
+        let bit = word * BITS_PER_WORD + i;
+        (index << 1) | usize::from(bits[bit / 8] >> (7 - bit % 8) & 1)
+    })
+}
+
+/// Writes `index` as the `word`th word into `bits`, which must start zeroed.
+fn write_index(bits: &mut [u8], word: usize, index: usize) {
+    for i in 0..BITS_PER_WORD {
+        if index >> (BITS_PER_WORD - 1 - i) & 1 == 1 {
+            let bit = word * BITS_PER_WORD + i;
+            bits[bit / 8] |= 1 << (7 - bit % 8);
+        }
+    }
+}
+
+/// Decodes `phrase` into `entropy`, checking its checksum, and returns the entropy's length.
+fn decode(phrase: &str, entropy: &mut [u8; MAX_ENTROPY_SIZE]) -> Result<usize, Bip39Error> {
+    let word_count = phrase.split_whitespace().count();
+    if !is_valid_word_count(word_count) {
+        return Err(Bip39Error::InvalidWordCount(word_count));
+    }
+
+    let mut bits = [0u8; MAX_ENTROPY_SIZE + 1];
+    for (i, word) in phrase.split_whitespace().enumerate() {
+        match WORDS.binary_search(&word) {
+            Ok(index) => write_index(&mut bits, i, index),
+            Err(_) => {
+                zero(&mut bits);
+                return Err(Bip39Error::UnknownWord(i));
+            }
+        }
+    }
+
+    let len = word_count * BITS_PER_WORD * 32 / 33 / 8;
+    let checksum_bits = len / 4;
+    let checksum = sha256(&bits[..len])[0] >> (8 - checksum_bits);
+    let valid = bits[len] >> (8 - checksum_bits) == checksum;
+    entropy[..len].copy_from_slice(&bits[..len]);
+    zero(&mut bits);
+
+    if valid {
+        Ok(len)
+    } else {
+        zero(&mut entropy[..len]);
+        Err(Bip39Error::InvalidChecksum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // From the reference implementation's vectors, all with the passphrase "TREZOR".
+    const VECTORS: &[(&str, &str, &str)] = &[
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+            "274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+        ),
+    ];
+
+    #[test]
+    fn vectors() {
+        for &(entropy, phrase, seed) in VECTORS {
+            let mnemonic = Mnemonic::from_entropy(&hex(entropy)).unwrap();
+            assert_eq!(mnemonic.phrase(), phrase);
+            assert_eq!(mnemonic.to_entropy(), hex(entropy));
+            assert_eq!(Mnemonic::parse(phrase).unwrap(), mnemonic);
+            assert_eq!(&mnemonic.to_seed("TREZOR").as_bytes()[..], &hex(seed)[..]);
+        }
+    }
+
+    #[test]
+    fn generate() {
+        let mnemonic = Mnemonic::generate(24).unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(mnemonic.to_entropy().len(), 32);
+        assert_eq!(Mnemonic::parse(mnemonic.phrase()).unwrap(), mnemonic);
+        assert_eq!(
+            Mnemonic::generate(13).unwrap_err(),
+            Bip39Error::InvalidWordCount(13)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Mnemonic::from_entropy(&[0; 15]).unwrap_err(),
+            Bip39Error::InvalidEntropyLength(15)
+        );
+        assert_eq!(
+            Mnemonic::parse("abandon abandon about").unwrap_err(),
+            Bip39Error::InvalidWordCount(3)
+        );
+        assert_eq!(
+            Mnemonic::parse(&"abandon ".repeat(12)).unwrap_err(),
+            Bip39Error::InvalidChecksum
+        );
+        assert_eq!(
+            Mnemonic::parse(&VECTORS[0].1.replace("about", "veil")).unwrap_err(),
+            Bip39Error::UnknownWord(11)
+        );
+
+        let spaced = VECTORS[1].1.replace(' ', " \n ");
+        assert_eq!(Mnemonic::parse(&spaced).unwrap().phrase(), VECTORS[1].1);
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Hash functions used across the workspace, so crates share one implementation instead of each
+//! pulling in their own.
+//!
+//! Each function comes both as a one shot `fn` and as a `Hasher` for input that arrives in
+//! pieces. Hashes with a meaning of their own, such as transaction ids, get their own types so
+//! they print the way veild prints them.
+
+use bitcoin_hashes::{Hash, HashEngine, hash160, hmac, ripemd160, sha256, sha256d, sha512};
+use core::{fmt, str};
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::util::zero;
+
+/// A hash function fed its input in pieces.
+pub trait Hasher {
+    type Output;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Self::Output;
+}
+
+macro_rules! hasher {
+    ($(#[$doc:meta])* $name:ident, $hash:ty, $len:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(<$hash as Hash>::Engine);
+
+        impl $name {
+            pub fn new() -> Self {
+                $name(<$hash>::engine())
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Hasher for $name {
+            type Output = [u8; $len];
+
+            fn update(&mut self, data: &[u8]) {
+                self.0.input(data);
+            }
+
+            fn finalize(self) -> [u8; $len] {
+                <$hash>::from_engine(self.0).into_inner()
+            }
+        }
+    };
+}
+
+hasher!(Sha256, sha256::Hash, 32);
+hasher!(
+    /// SHA256 applied twice, used for transaction and block hashes.
+    Sha256d,
+    sha256d::Hash,
+    32
+);
+hasher!(Sha512, sha512::Hash, 64);
+hasher!(Ripemd160, ripemd160::Hash, 20);
+hasher!(
+    /// RIPEMD160 of SHA256, used to hash public keys into addresses.
+    Hash160,
+    hash160::Hash,
+    20
+);
+
+macro_rules! hmac_hasher {
+    ($name:ident, $hash:ty, $len:expr) => {
+        #[derive(Clone)]
+        pub struct $name(hmac::HmacEngine<$hash>);
+
+        impl $name {
+            pub fn new(key: &[u8]) -> Self {
+                $name(hmac::HmacEngine::new(key))
+            }
+        }
+
+        impl Hasher for $name {
+            type Output = [u8; $len];
+
+            fn update(&mut self, data: &[u8]) {
+                self.0.input(data);
+            }
+
+            fn finalize(self) -> [u8; $len] {
+                hmac::Hmac::<$hash>::from_engine(self.0).into_inner()
+            }
+        }
+    };
+}
+
+hmac_hasher!(HmacSha256, sha256::Hash, 32);
+hmac_hasher!(HmacSha512, sha512::Hash, 64);
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    sha256::Hash::hash(data).into_inner()
+}
+
+/// SHA256 of `data` prefixed with the SHA256 of `tag` twice, as in BIP340, so hashes made for
+/// one purpose can never collide with those made for another.
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag = sha256(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(&tag);
+    hasher.update(&tag);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    sha256d::Hash::hash(data).into_inner()
+}
+
+pub fn sha512(data: &[u8]) -> [u8; 64] {
+    sha512::Hash::hash(data).into_inner()
+}
+
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    ripemd160::Hash::hash(data).into_inner()
+}
+
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    hash160::Hash::hash(data).into_inner()
+}
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut hasher = HmacSha256::new(key);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut hasher = HmacSha512::new(key);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Fills `output` with PBKDF2-HMAC-SHA256 of `password` and `salt`.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    pbkdf2::<sha256::Hash>(password, salt, rounds, output)
+}
+
+/// Fills `output` with PBKDF2-HMAC-SHA512 of `password` and `salt`, as BIP39 uses to stretch a
+/// mnemonic into a seed.
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    pbkdf2::<sha512::Hash>(password, salt, rounds, output)
+}
+
+fn pbkdf2<T: Hash>(password: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    let keyed = hmac::HmacEngine::<T>::new(password);
+    let len = T::LEN;
+    // Large enough for the output of any of the hashes above.
+    let mut block = [0u8; 64];
+    let mut sum = [0u8; 64];
+
+    for (i, chunk) in output.chunks_mut(len).enumerate() {
+        let mut engine = keyed.clone();
+        engine.input(salt);
+        engine.input(&(i as u32 + 1).to_be_bytes());
+        block[..len].copy_from_slice(&hmac::Hmac::<T>::from_engine(engine)[..]);
+        sum[..len].copy_from_slice(&block[..len]);
+
+        for _ in 1..rounds {
+            let mut engine = keyed.clone();
+            engine.input(&block[..len]);
+            block[..len].copy_from_slice(&hmac::Hmac::<T>::from_engine(engine)[..]);
+            for (sum, byte) in sum.iter_mut().zip(block[..len].iter()) {
+                *sum ^= byte;
+            }
+        }
+        chunk.copy_from_slice(&sum[..chunk.len()]);
+    }
+    zero(&mut block);
+    zero(&mut sum);
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HashParseError {
+    InvalidHex,
+    /// The input had this many characters, rather than two per byte of the hash.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for HashParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashParseError::InvalidHex => f.write_str("invalid hex"),
+            HashParseError::InvalidLength(len) => write!(f, "invalid hash hex length {}", len),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for HashParseError {}
+
+macro_rules! hash_type {
+    ($(#[$doc:meta])* $name:ident, $hash:ident, $len:expr, $reversed:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            pub fn hash(data: &[u8]) -> Self {
+                $name($hash(data))
+            }
+
+            pub fn from_bytes(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+
+            /// The bytes in the order they are hashed and serialized, which is not necessarily
+            /// the order they are printed in.
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_hex(f, &self.0, $reversed)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, concat!(stringify!($name), "({})"), self)
+            }
+        }
+
+        impl str::FromStr for $name {
+            type Err = HashParseError;
+
+            fn from_str(s: &str) -> Result<Self, HashParseError> {
+                let mut bytes = [0u8; $len];
+                read_hex(s, &mut bytes, $reversed)?;
+                Ok($name(bytes))
+            }
+        }
+    };
+}
+
+hash_type!(
+    /// The double SHA256 of a transaction, printed byte reversed like veild does.
+    Txid,
+    sha256d,
+    32,
+    true
+);
+hash_type!(
+    /// The double SHA256 of a transaction including its witness data, printed byte reversed
+    /// like veild does.
+    Wtxid,
+    sha256d,
+    32,
+    true
+);
+hash_type!(
+    /// The double SHA256 of a block header, printed byte reversed like veild does.
+    BlockHash,
+    sha256d,
+    32,
+    true
+);
+hash_type!(
+    /// The hash160 of a serialized public key, as paid to by an address.
+    PubkeyHash,
+    hash160,
+    20,
+    false
+);
+
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8], reversed: bool) -> fmt::Result {
+    if reversed {
+        bytes
+            .iter()
+            .rev()
+            .try_for_each(|byte| write!(f, "{:02x}", byte))
+    } else {
+        bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+fn read_hex(s: &str, bytes: &mut [u8], reversed: bool) -> Result<(), HashParseError> {
+    if s.len() != bytes.len() * 2 {
+        return Err(HashParseError::InvalidLength(s.len()));
+    }
+    let len = bytes.len();
+    for (i, pair) in s.as_bytes().chunks(2).enumerate() {
+        let byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+        bytes[if reversed { len - 1 - i } else { i }] = byte;
+    }
+    Ok(())
+}
+
+fn hex_digit(c: u8) -> Result<u8, HashParseError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(HashParseError::InvalidHex),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256d(b"abc")),
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+        );
+        assert_eq!(
+            hex(&tagged_hash("BIP0340/challenge", b"abc")),
+            "770a5b7e7c304bbcc3ea107343ff951dd404312ef418db0c3b94e2ebfbb50087"
+        );
+        assert_eq!(
+            hex(&sha512(b"abc")),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            hex(&ripemd160(b"abc")),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(
+            hex(&hash160(b"abc")),
+            "bb1be98c142444d7a56aa3981c3942a978e4dc33"
+        );
+        // RFC 4231 test case 2.
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(&hmac_sha512(b"Jefe", b"what do ya want for nothing?")),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut hasher = Sha256d::new();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), sha256d(b"abc"));
+
+        let mut hasher = HmacSha512::new(b"Jefe");
+        hasher.update(b"what do ya want ");
+        hasher.update(b"for nothing?");
+        assert_eq!(
+            hasher.finalize()[..],
+            hmac_sha512(b"Jefe", b"what do ya want for nothing?")[..]
+        );
+    }
+
+    #[test]
+    fn pbkdf2() {
+        // RFC 7914 section 11, which spans two blocks of output.
+        let mut output = [0u8; 64];
+        pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut output);
+        assert_eq!(
+            hex(&output),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+
+        let mut output = [0u8; 64];
+        pbkdf2_hmac_sha512(b"password", b"salt", 2, &mut output);
+        assert_eq!(
+            hex(&output),
+            "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53cf76cab2868a39b9f7840edce4fef5a82be67335c77a6068e04112754f27ccf4e"
+        );
+    }
+
+    #[test]
+    fn hash_types() {
+        // The genesis block of Bitcoin, whose hash is well known in its printed order.
+        let genesis = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        let hash: BlockHash = genesis.parse().unwrap();
+        assert_eq!(hash.as_bytes()[0], 0x6f);
+        assert_eq!(hash.to_string(), genesis);
+        assert_eq!(format!("{:?}", hash), format!("BlockHash({})", genesis));
+
+        let txid = Txid::hash(b"abc");
+        assert_eq!(txid.as_bytes(), &sha256d(b"abc"));
+        assert_eq!(
+            txid.to_string(),
+            "58636c3ec08c12d55aedda056d602d5bcca72d8df6a69b519b72d32dc2428b4f"
+        );
+        assert_eq!(txid.to_string().parse(), Ok(txid));
+
+        let pubkey_hash = PubkeyHash::hash(b"abc");
+        assert_eq!(
+            pubkey_hash.to_string(),
+            "bb1be98c142444d7a56aa3981c3942a978e4dc33"
+        );
+        assert_eq!(
+            "bb1b".parse::<PubkeyHash>(),
+            Err(HashParseError::InvalidLength(4))
+        );
+        assert_eq!(
+            "bb1be98c142444d7a56aa3981c3942a978e4dc3".parse::<PubkeyHash>(),
+            Err(HashParseError::InvalidLength(39))
+        );
+        // Digits only, no sign that integer parsing would allow.
+        assert_eq!(
+            "+b1be98c142444d7a56aa3981c3942a978e4dc33".parse::<PubkeyHash>(),
+            Err(HashParseError::InvalidHex)
+        );
+        assert_eq!(
+            "zz1be98c142444d7a56aa3981c3942a978e4dc33".parse::<PubkeyHash>(),
+            Err(HashParseError::InvalidHex)
+        );
+    }
+}
@@ -0,0 +1,735 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Password protected storage for wallet secrets.
+//!
+//! Secrets are encrypted with a random data key, and only that key is encrypted with the key
+//! derived from the password. Changing the password re-encrypts the data key alone.
+//!
+//! The on-disk format, with integers little endian:
+//!
+//! ```text
+//! magic        8 bytes  "VEILKEYS"
+//! version      1 byte
+//! scrypt       log_n: 1 byte, r: 4 bytes, p: 4 bytes
+//! salt         16 bytes
+//! data key     24 byte nonce, 48 byte ciphertext, authenticated with everything above
+//! entry count  4 bytes
+//! entries      kind: 1 byte, label length: 2 bytes, label, 24 byte nonce,
+//!              ciphertext length: 4 bytes, ciphertext, authenticated with the kind and label
+//! index        24 byte nonce, 16 byte tag, authenticating the entry count and each entry's
+//!              kind, label and nonce, so entries cannot be dropped or swapped for old ones
+//! ```
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use libsecp256k1_veil::{Context, PrivateKey};
+use rand::{RngCore, rngs::OsRng};
+use scrypt::ScryptParams;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::{error, fmt, fs, io};
+
+use crate::bip39::Seed;
+use crate::util::zero;
+
+const MAGIC: &[u8; 8] = b"VEILKEYS";
+const VERSION: u8 = 1;
+const SALT_SIZE: usize = 16;
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 24;
+const TAG_SIZE: usize = 16;
+const WRAPPED_KEY_SIZE: usize = NONCE_SIZE + KEY_SIZE + TAG_SIZE;
+const INDEX_SIZE: usize = NONCE_SIZE + TAG_SIZE;
+/// Length of everything before the wrapped data key, which is authenticated along with it.
+const HEADER_SIZE: usize = MAGIC.len() + 1 + 9 + SALT_SIZE;
+/// Upper bounds on the KDF parameters, so a crafted file cannot make opening it take unbounded
+/// memory or time. The memory scrypt needs is `128 * r * 2^log_n` bytes.
+const MAX_KDF_MEMORY: u64 = 1 << 30;
+const MAX_KDF_P: u32 = 16;
+
+const KIND_PRIVATE_KEY: u8 = 0;
+const KIND_SEED: u8 = 1;
+const KIND_STEALTH_KEYS: u8 = 2;
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    Io(io::Error),
+    /// The data is not a keystore at all.
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    InvalidKdfParams,
+    /// The password does not decrypt the data key.
+    WrongPassword,
+    /// The keystore is truncated, or an entry fails to decrypt even though the password is
+    /// right.
+    Corrupt,
+    LabelTooLong(usize),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeystoreError::Io(e) => e.fmt(f),
+            KeystoreError::InvalidMagic => f.write_str("not a keystore"),
+            KeystoreError::UnsupportedVersion(version) => {
+                write!(f, "unsupported keystore version {}", version)
+            }
+            KeystoreError::InvalidKdfParams => f.write_str("invalid key derivation parameters"),
+            KeystoreError::WrongPassword => f.write_str("wrong password"),
+            KeystoreError::Corrupt => f.write_str("keystore is corrupt"),
+            KeystoreError::LabelTooLong(len) => write!(f, "label of {} bytes is too long", len),
+        }
+    }
+}
+
+impl error::Error for KeystoreError {}
+
+impl From<io::Error> for KeystoreError {
+    fn from(e: io::Error) -> Self {
+        KeystoreError::Io(e)
+    }
+}
+
+/// Cost of deriving a key from the password with scrypt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl KdfParams {
+    fn derive_key(self, password: &str, salt: &[u8]) -> Result<[u8; KEY_SIZE], KeystoreError> {
+        if self.log_n >= 32
+            || self.p > MAX_KDF_P
+            || (128 * u64::from(self.r)) << self.log_n > MAX_KDF_MEMORY
+        {
+            return Err(KeystoreError::InvalidKdfParams);
+        }
+        let params = ScryptParams::new(self.log_n, self.r, self.p)
+            .map_err(|_| KeystoreError::InvalidKdfParams)?;
+        let mut key = [0u8; KEY_SIZE];
+        scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+            .map_err(|_| KeystoreError::InvalidKdfParams)?;
+        Ok(key)
+    }
+}
+
+impl Default for KdfParams {
+    /// The parameters scrypt's authors recommend for interactive logins, about 32 MiB of memory.
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// A secret held in a keystore.
+#[derive(Debug)]
+pub enum Secret {
+    PrivateKey(PrivateKey),
+    /// The seed of a BIP32 key tree.
+    Seed(Seed),
+    /// The scan and spend keys of a stealth address.
+    StealthKeys {
+        scan: PrivateKey,
+        spend: PrivateKey,
+    },
+}
+
+impl Secret {
+    fn kind(&self) -> u8 {
+        match self {
+            Secret::PrivateKey(_) => KIND_PRIVATE_KEY,
+            Secret::Seed(_) => KIND_SEED,
+            Secret::StealthKeys { .. } => KIND_STEALTH_KEYS,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Secret::PrivateKey(key) => key.to_bytes().to_vec(),
+            Secret::Seed(seed) => seed.as_bytes().to_vec(),
+            Secret::StealthKeys { scan, spend } => {
+                let mut bytes = Vec::with_capacity(64);
+                bytes.extend_from_slice(&scan.to_bytes());
+                bytes.extend_from_slice(&spend.to_bytes());
+                bytes
+            }
+        }
+    }
+
+    fn from_bytes(ctx: &impl Context, kind: u8, bytes: &[u8]) -> Result<Self, KeystoreError> {
+        let private_key =
+            |bytes: &[u8]| PrivateKey::from_slice(ctx, bytes).map_err(|_| KeystoreError::Corrupt);
+        match (kind, bytes.len()) {
+            (KIND_PRIVATE_KEY, 32) => Ok(Secret::PrivateKey(private_key(bytes)?)),
+            (KIND_SEED, 64) => {
+                let mut seed = [0u8; 64];
+                seed.copy_from_slice(bytes);
+                Ok(Secret::Seed(Seed::from_bytes(seed)))
+            }
+            (KIND_STEALTH_KEYS, 64) => Ok(Secret::StealthKeys {
+                scan: private_key(&bytes[..32])?,
+                spend: private_key(&bytes[32..])?,
+            }),
+            _ => Err(KeystoreError::Corrupt),
+        }
+    }
+}
+
+struct Entry {
+    kind: u8,
+    label: String,
+    nonce: [u8; NONCE_SIZE],
+    ciphertext: Vec<u8>,
+}
+
+impl Entry {
+    /// The kind and label, which are stored in the clear but authenticated.
+    fn associated_data(kind: u8, label: &str) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(1 + label.len());
+        aad.push(kind);
+        aad.extend_from_slice(label.as_bytes());
+        aad
+    }
+}
+
+/// An unlocked keystore. Entries stay encrypted in memory and are only decrypted by `get`.
+pub struct Keystore {
+    kdf: KdfParams,
+    salt: [u8; SALT_SIZE],
+    wrapped_key: Vec<u8>,
+    data_key: [u8; KEY_SIZE],
+    entries: Vec<Entry>,
+}
+
+impl Keystore {
+    pub fn new(password: &str) -> Result<Self, KeystoreError> {
+        Self::with_params(password, KdfParams::default())
+    }
+
+    pub fn with_params(password: &str, kdf: KdfParams) -> Result<Self, KeystoreError> {
+        let mut data_key = [0u8; KEY_SIZE];
+        OsRng.fill_bytes(&mut data_key);
+        let mut keystore = Self {
+            kdf,
+            salt: [0; SALT_SIZE],
+            wrapped_key: Vec::new(),
+            data_key,
+            entries: Vec::new(),
+        };
+        keystore.change_password(password)?;
+        Ok(keystore)
+    }
+
+    /// Re-encrypts the data key under `new_password`, with a new salt. The entries are left as
+    /// they are.
+    pub fn change_password(&mut self, new_password: &str) -> Result<(), KeystoreError> {
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let mut password_key = self.kdf.derive_key(new_password, &salt)?;
+
+        self.salt = salt;
+        let header = self.header();
+        self.wrapped_key = seal(&password_key, &self.data_key, &header);
+        zero(&mut password_key);
+        Ok(())
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf
+    }
+
+    /// Encrypts `secret` under `label`, replacing any secret already there.
+    pub fn insert(&mut self, label: &str, secret: &Secret) -> Result<(), KeystoreError> {
+        if label.len() > usize::from(u16::MAX) {
+            return Err(KeystoreError::LabelTooLong(label.len()));
+        }
+        let kind = secret.kind();
+        let mut plaintext = secret.to_bytes();
+        let sealed = seal(
+            &self.data_key,
+            &plaintext,
+            &Entry::associated_data(kind, label),
+        );
+        zero(&mut plaintext);
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(&sealed[..NONCE_SIZE]);
+        let entry = Entry {
+            kind,
+            label: label.to_string(),
+            nonce,
+            ciphertext: sealed[NONCE_SIZE..].to_vec(),
+        };
+        self.remove(label);
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Decrypts the secret under `label`, if there is one.
+    pub fn get(&self, ctx: &impl Context, label: &str) -> Result<Option<Secret>, KeystoreError> {
+        let entry = match self.entries.iter().find(|entry| entry.label == label) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let mut plaintext = open(
+            &self.data_key,
+            &entry.nonce,
+            &entry.ciphertext,
+            &Entry::associated_data(entry.kind, &entry.label),
+        )
+        .ok_or(KeystoreError::Corrupt)?;
+        let secret = Secret::from_bytes(ctx, entry.kind, &plaintext);
+        zero(&mut plaintext);
+        secret.map(Some)
+    }
+
+    /// Removes the secret under `label`, returning whether there was one.
+    pub fn remove(&mut self, label: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.label != label);
+        self.entries.len() != len
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.label.as_str())
+    }
+
+    pub fn from_bytes(data: &[u8], password: &str) -> Result<Self, KeystoreError> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(KeystoreError::InvalidMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(KeystoreError::UnsupportedVersion(version));
+        }
+        let kdf = KdfParams {
+            log_n: reader.u8()?,
+            r: reader.u32()?,
+            p: reader.u32()?,
+        };
+        let mut salt = [0u8; SALT_SIZE];
+        salt.copy_from_slice(reader.take(SALT_SIZE)?);
+        let wrapped_key = reader.take(WRAPPED_KEY_SIZE)?.to_vec();
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(&wrapped_key[..NONCE_SIZE]);
+
+        let mut password_key = kdf.derive_key(password, &salt)?;
+        let data_key = open(
+            &password_key,
+            &nonce,
+            &wrapped_key[NONCE_SIZE..],
+            &data[..HEADER_SIZE],
+        );
+        zero(&mut password_key);
+        let mut data_key = data_key.ok_or(KeystoreError::WrongPassword)?;
+        let mut keystore = Self {
+            kdf,
+            salt,
+            wrapped_key,
+            data_key: [0; KEY_SIZE],
+            entries: Vec::new(),
+        };
+        keystore.data_key.copy_from_slice(&data_key);
+        zero(&mut data_key);
+
+        let count = reader.u32()?;
+        for _ in 0..count {
+            let kind = reader.u8()?;
+            let label_len = usize::from(reader.u16()?);
+            let label = String::from_utf8(reader.take(label_len)?.to_vec())
+                .map_err(|_| KeystoreError::Corrupt)?;
+            let mut nonce = [0u8; NONCE_SIZE];
+            nonce.copy_from_slice(reader.take(NONCE_SIZE)?);
+            let ciphertext_len = reader.u32()? as usize;
+            let ciphertext = reader.take(ciphertext_len)?.to_vec();
+            keystore.entries.push(Entry {
+                kind,
+                label,
+                nonce,
+                ciphertext,
+            });
+        }
+        let index = reader.take(INDEX_SIZE)?;
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(&index[..NONCE_SIZE]);
+        let tag = &index[NONCE_SIZE..];
+        open(&keystore.data_key, &nonce, tag, &keystore.index()).ok_or(KeystoreError::Corrupt)?;
+        if reader.pos != data.len() {
+            return Err(KeystoreError::Corrupt);
+        }
+        Ok(keystore)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = self.header();
+        data.extend_from_slice(&self.wrapped_key);
+        data.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            data.push(entry.kind);
+            data.extend_from_slice(&(entry.label.len() as u16).to_le_bytes());
+            data.extend_from_slice(entry.label.as_bytes());
+            data.extend_from_slice(&entry.nonce);
+            data.extend_from_slice(&(entry.ciphertext.len() as u32).to_le_bytes());
+            data.extend_from_slice(&entry.ciphertext);
+        }
+        data.extend_from_slice(&seal(&self.data_key, &[], &self.index()));
+        data
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, KeystoreError> {
+        Self::from_bytes(&fs::read(path)?, password)
+    }
+
+    /// Writes the keystore next to `path` and renames it into place, so a crash part way
+    /// through leaves the old file intact. On unix the file is only readable by its owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeystoreError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+
+        // A file left by an earlier crash may have other permissions, start from a new one.
+        match fs::remove_file(&tmp) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(&self.to_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        // The rename is only durable once the directory holding both names is synced.
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        sync_dir(dir)?;
+        fs::rename(&tmp, path)?;
+        sync_dir(dir)?;
+        Ok(())
+    }
+
+    /// The entry count and each entry's kind, label and nonce. The nonce stands in for the
+    /// ciphertext, which is already authenticated by it.
+    fn index(&self) -> Vec<u8> {
+        let mut index = (self.entries.len() as u32).to_le_bytes().to_vec();
+        for entry in &self.entries {
+            index.push(entry.kind);
+            index.extend_from_slice(&(entry.label.len() as u16).to_le_bytes());
+            index.extend_from_slice(entry.label.as_bytes());
+            index.extend_from_slice(&entry.nonce);
+        }
+        index
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(self.kdf.log_n);
+        header.extend_from_slice(&self.kdf.r.to_le_bytes());
+        header.extend_from_slice(&self.kdf.p.to_le_bytes());
+        header.extend_from_slice(&self.salt);
+        header
+    }
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keystore")
+            .field("kdf", &self.kdf)
+            .field("labels", &self.labels().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Drop for Keystore {
+    fn drop(&mut self) {
+        zero(&mut self.data_key);
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing elsewhere, renames are left to the file system.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Encrypts `plaintext` under a random nonce, returning the nonce followed by the ciphertext.
+fn seal(key: &[u8; KEY_SIZE], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(&Key::from(*key))
+        .encrypt(
+            &XNonce::from(nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("plaintext is far below the cipher's limit");
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    sealed
+}
+
+fn open(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Option<Vec<u8>> {
+    XChaCha20Poly1305::new(&Key::from(*key))
+        .decrypt(
+            &XNonce::from(*nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
+
+/// Reads fields off the front of a serialized keystore, any short read meaning it is corrupt.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], KeystoreError> {
+        if self.data.len() - self.pos < len {
+            return Err(KeystoreError::Corrupt);
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, KeystoreError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, KeystoreError> {
+        let mut bytes = [0u8; 2];
+        bytes.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    fn u32(&mut self) -> Result<u32, KeystoreError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libsecp256k1_veil::ContextNone;
+
+    // Cheap enough for tests, far too cheap for real use.
+    const TEST_KDF: KdfParams = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn keystore(ctx: &ContextNone) -> Keystore {
+        let mut keystore = Keystore::with_params("hunter2", TEST_KDF).unwrap();
+        keystore
+            .insert("key", &Secret::PrivateKey(PrivateKey::new(ctx)))
+            .unwrap();
+        keystore
+            .insert("seed", &Secret::Seed(Seed::from_bytes([7; 64])))
+            .unwrap();
+        keystore
+            .insert(
+                "stealth",
+                &Secret::StealthKeys {
+                    scan: PrivateKey::new(ctx),
+                    spend: PrivateKey::new(ctx),
+                },
+            )
+            .unwrap();
+        keystore
+    }
+
+    #[test]
+    fn round_trip() {
+        let ctx = ContextNone::new();
+        let keystore = keystore(&ctx);
+        let reopened = Keystore::from_bytes(&keystore.to_bytes(), "hunter2").unwrap();
+        assert_eq!(reopened.kdf_params(), TEST_KDF);
+        assert_eq!(
+            reopened.labels().collect::<Vec<_>>(),
+            ["key", "seed", "stealth"]
+        );
+
+        for label in &["key", "seed", "stealth"] {
+            let before = keystore.get(&ctx, label).unwrap().unwrap();
+            let after = reopened.get(&ctx, label).unwrap().unwrap();
+            assert_eq!(before.to_bytes(), after.to_bytes());
+            assert_eq!(before.kind(), after.kind());
+        }
+        assert!(reopened.get(&ctx, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn kdf_limits() {
+        let derive = |log_n, r, p| KdfParams { log_n, r, p }.derive_key("hunter2", &[0; 16]);
+        assert!(derive(4, 8, 16).is_ok());
+        assert!(matches!(
+            derive(4, 8, 17),
+            Err(KeystoreError::InvalidKdfParams)
+        ));
+        assert!(matches!(
+            derive(21, 8, 1),
+            Err(KeystoreError::InvalidKdfParams)
+        ));
+        assert!(matches!(
+            derive(40, 1, 1),
+            Err(KeystoreError::InvalidKdfParams)
+        ));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let ctx = ContextNone::new();
+        let keystore = keystore(&ctx);
+        let path = std::env::temp_dir().join(format!("veil-keystore-{}", std::process::id()));
+        keystore.save(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let loaded = Keystore::load(&path, "hunter2");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().to_bytes().len(), keystore.to_bytes().len());
+    }
+
+    #[test]
+    fn change_password() {
+        let ctx = ContextNone::new();
+        let mut keystore = keystore(&ctx);
+        let key = keystore.get(&ctx, "key").unwrap().unwrap().to_bytes();
+        let data = keystore.to_bytes();
+        let entries = data[HEADER_SIZE + WRAPPED_KEY_SIZE..data.len() - INDEX_SIZE].to_vec();
+
+        keystore.change_password("correct horse").unwrap();
+        let data = keystore.to_bytes();
+        // Only the header and the data key changed, the index is sealed under a new nonce.
+        assert_eq!(
+            data[HEADER_SIZE + WRAPPED_KEY_SIZE..data.len() - INDEX_SIZE],
+            entries[..]
+        );
+        assert!(matches!(
+            Keystore::from_bytes(&data, "hunter2"),
+            Err(KeystoreError::WrongPassword)
+        ));
+        let reopened = Keystore::from_bytes(&data, "correct horse").unwrap();
+        assert_eq!(reopened.get(&ctx, "key").unwrap().unwrap().to_bytes(), key);
+    }
+
+    #[test]
+    fn corrupt() {
+        let ctx = ContextNone::new();
+        let mut keystore = keystore(&ctx);
+        assert!(keystore.remove("seed"));
+        assert!(!keystore.remove("seed"));
+        let data = keystore.to_bytes();
+
+        let mut bad = data.clone();
+        bad[0] = b'X';
+        assert!(matches!(
+            Keystore::from_bytes(&bad, "hunter2"),
+            Err(KeystoreError::InvalidMagic)
+        ));
+        bad = data.clone();
+        bad[8] = 2;
+        assert!(matches!(
+            Keystore::from_bytes(&bad, "hunter2"),
+            Err(KeystoreError::UnsupportedVersion(2))
+        ));
+        // Tampering with the KDF parameters invalidates the data key like a wrong password.
+        bad = data.clone();
+        bad[9] = 5;
+        assert!(matches!(
+            Keystore::from_bytes(&bad, "hunter2"),
+            Err(KeystoreError::WrongPassword)
+        ));
+        assert!(matches!(
+            Keystore::from_bytes(&data[..data.len() - 1], "hunter2"),
+            Err(KeystoreError::Corrupt)
+        ));
+        bad = data.clone();
+        let last = bad.len() - 1;
+        bad[last] ^= 1;
+        assert!(matches!(
+            Keystore::from_bytes(&bad, "hunter2"),
+            Err(KeystoreError::Corrupt)
+        ));
+
+        // Dropping an entry needs a new index, which only the data key can seal.
+        keystore.remove("stealth");
+        let mut dropped = keystore.to_bytes();
+        dropped.truncate(dropped.len() - INDEX_SIZE);
+        dropped.extend_from_slice(&data[data.len() - INDEX_SIZE..]);
+        assert!(matches!(
+            Keystore::from_bytes(&dropped, "hunter2"),
+            Err(KeystoreError::Corrupt)
+        ));
+
+        // A flipped bit in an entry's ciphertext is only noticed when it is decrypted.
+        bad = data.clone();
+        let last = bad.len() - INDEX_SIZE - 1;
+        bad[last] ^= 1;
+        let reopened = Keystore::from_bytes(&bad, "hunter2").unwrap();
+        assert!(reopened.get(&ctx, "key").unwrap().is_some());
+        assert!(matches!(
+            reopened.get(&ctx, "stealth"),
+            Err(KeystoreError::Corrupt)
+        ));
+    }
+}
@@ -21,3 +21,339 @@
 // CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
+//! Encrypted, mutually authenticated streams over the Noise Protocol Framework.
+//!
+//! Each side is identified by a static Curve25519 key rather than a certificate, and either
+//! side may pin the key it expects the other to have. Messages are framed with a two byte big
+//! endian length, as the Noise specification suggests for stream transports.
+
+use snow::{Builder, HandshakeState, Keypair, TransportState};
+use std::io::{self, Read, Write};
+use std::{cmp, error, fmt};
+
+/// Noise limits every message, handshake or transport, to this many bytes.
+const MAX_MESSAGE_SIZE: usize = 65535;
+/// Length of the authentication tag ChaChaPoly adds to every transport message.
+const TAG_SIZE: usize = 16;
+/// Every transport message starts with one of these, so a rekey can be signalled in band.
+const FRAME_DATA: u8 = 0;
+const FRAME_REKEY: u8 = 1;
+/// Most application bytes a single transport message carries.
+const MAX_CHUNK_SIZE: usize = MAX_MESSAGE_SIZE - TAG_SIZE - 1;
+/// Outgoing messages after which the sending key is replaced, limiting how much traffic a
+/// leaked key exposes.
+const REKEY_INTERVAL: u64 = 1 << 16;
+
+/// The handshake used to set up a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandshakePattern {
+    /// Both static keys are sent during the handshake, neither side needs to know the other's
+    /// key beforehand.
+    XX,
+    /// The initiator already knows the responder's static key, saving a round trip.
+    IK,
+}
+
+impl HandshakePattern {
+    fn params(self) -> &'static str {
+        match self {
+            HandshakePattern::XX => "Noise_XX_25519_ChaChaPoly_BLAKE2s",
+            HandshakePattern::IK => "Noise_IK_25519_ChaChaPoly_BLAKE2s",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NoiseError {
+    Io(io::Error),
+    Noise(snow::Error),
+    /// IK needs the responder's static key to start the handshake.
+    MissingRemoteKey,
+    /// The other side's static key is not the pinned one.
+    UnexpectedRemoteKey,
+}
+
+impl fmt::Display for NoiseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoiseError::Io(e) => e.fmt(f),
+            NoiseError::Noise(e) => e.fmt(f),
+            NoiseError::MissingRemoteKey => f.write_str("remote static key required for IK"),
+            NoiseError::UnexpectedRemoteKey => f.write_str("remote static key does not match"),
+        }
+    }
+}
+
+impl error::Error for NoiseError {}
+
+impl From<io::Error> for NoiseError {
+    fn from(e: io::Error) -> Self {
+        NoiseError::Io(e)
+    }
+}
+
+impl From<snow::Error> for NoiseError {
+    fn from(e: snow::Error) -> Self {
+        NoiseError::Noise(e)
+    }
+}
+
+/// A new static keypair, usable with either handshake pattern.
+pub fn generate_keypair() -> Result<Keypair, NoiseError> {
+    Ok(Builder::new(HandshakePattern::XX.params().parse()?).generate_keypair()?)
+}
+
+/// A `Read + Write` transport, such as a `TcpStream`, with everything written to it encrypted.
+///
+/// Writes are sent as one message per call, so wrapping the stream in a `BufWriter` avoids
+/// sending many small messages.
+pub struct NoiseStream<T> {
+    transport: T,
+    state: TransportState,
+    /// Scratch space for the message being sent or received.
+    message: Vec<u8>,
+    /// Plaintext of the message being sent.
+    outgoing: Vec<u8>,
+    /// Decrypted payload of the last message received, read from `read_pos`.
+    payload: Vec<u8>,
+    read_pos: usize,
+    read_len: usize,
+    /// Messages sent with the current key.
+    sent: u64,
+}
+
+impl<T: Read + Write> NoiseStream<T> {
+    /// Runs the initiator's side of the handshake. `remote_public_key` is required for IK, and
+    /// for XX it pins the key the responder must present.
+    pub fn initiate(
+        transport: T,
+        pattern: HandshakePattern,
+        local_private_key: &[u8],
+        remote_public_key: Option<&[u8]>,
+    ) -> Result<Self, NoiseError> {
+        let builder = Builder::new(pattern.params().parse()?).local_private_key(local_private_key);
+        let handshake = match (pattern, remote_public_key) {
+            (HandshakePattern::IK, Some(key)) => builder.remote_public_key(key),
+            (HandshakePattern::IK, None) => return Err(NoiseError::MissingRemoteKey),
+            (HandshakePattern::XX, _) => builder,
+        }
+        .build_initiator()?;
+        Self::handshake(transport, handshake, remote_public_key)
+    }
+
+    /// Runs the responder's side of the handshake. When given, `remote_public_key` pins the key
+    /// the initiator must present.
+    pub fn respond(
+        transport: T,
+        pattern: HandshakePattern,
+        local_private_key: &[u8],
+        remote_public_key: Option<&[u8]>,
+    ) -> Result<Self, NoiseError> {
+        let handshake = Builder::new(pattern.params().parse()?)
+            .local_private_key(local_private_key)
+            .build_responder()?;
+        Self::handshake(transport, handshake, remote_public_key)
+    }
+
+    fn handshake(
+        mut transport: T,
+        mut handshake: HandshakeState,
+        remote_public_key: Option<&[u8]>,
+    ) -> Result<Self, NoiseError> {
+        let mut message = vec![0u8; MAX_MESSAGE_SIZE];
+        let mut payload = vec![0u8; MAX_MESSAGE_SIZE];
+
+        // The initiator sends the first message and the two sides alternate from there.
+        let mut my_turn = handshake.is_initiator();
+        while !handshake.is_handshake_finished() {
+            if my_turn {
+                let len = handshake.write_message(&[], &mut message)?;
+                write_frame(&mut transport, &message[..len])?;
+            } else {
+                let len = read_frame(&mut transport, &mut message)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                handshake.read_message(&message[..len], &mut payload)?;
+                // Reject a pinned key as soon as it arrives, before answering the message that
+                // carried it.
+                check_remote_key(&handshake, remote_public_key, false)?;
+            }
+            my_turn = !my_turn;
+        }
+        check_remote_key(&handshake, remote_public_key, true)?;
+
+        Ok(Self {
+            transport,
+            state: handshake.into_transport_mode()?,
+            message,
+            outgoing: vec![0u8; MAX_MESSAGE_SIZE],
+            payload,
+            read_pos: 0,
+            read_len: 0,
+            sent: 0,
+        })
+    }
+
+    /// The other side's static key, as authenticated by the handshake.
+    pub fn remote_public_key(&self) -> Option<&[u8]> {
+        self.state.get_remote_static()
+    }
+
+    /// Replaces the sending key, telling the other side to do the same for its receiving key.
+    /// Keys are also replaced automatically every so many messages.
+    pub fn rekey(&mut self) -> io::Result<()> {
+        self.send(FRAME_REKEY, &[])?;
+        self.state.rekey_outgoing();
+        self.sent = 0;
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    fn send(&mut self, kind: u8, data: &[u8]) -> io::Result<()> {
+        self.outgoing[0] = kind;
+        self.outgoing[1..=data.len()].copy_from_slice(data);
+        let len = self
+            .state
+            .write_message(&self.outgoing[..=data.len()], &mut self.message)
+            .map_err(invalid_data)?;
+        self.sent += 1;
+        write_frame(&mut self.transport, &self.message[..len])
+    }
+
+    /// Reads the next non-empty data message into `payload`, returning `false` at the end of the
+    /// stream.
+    fn receive(&mut self) -> io::Result<bool> {
+        loop {
+            let len = match read_frame(&mut self.transport, &mut self.message)? {
+                Some(len) => len,
+                None => return Ok(false),
+            };
+            let len = self
+                .state
+                .read_message(&self.message[..len], &mut self.payload)
+                .map_err(invalid_data)?;
+            match self.payload[..len].first() {
+                // An empty data message carries nothing to read, returning it would look like
+                // the end of the stream.
+                Some(&FRAME_DATA) if len == 1 => {}
+                Some(&FRAME_DATA) => {
+                    self.read_pos = 1;
+                    self.read_len = len;
+                    return Ok(true);
+                }
+                Some(&FRAME_REKEY) => self.state.rekey_incoming(),
+                _ => return Err(invalid_data("unknown noise frame")),
+            }
+        }
+    }
+}
+
+impl<T: Read + Write> Read for NoiseStream<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.read_pos == self.read_len && !self.receive()? {
+            return Ok(0);
+        }
+        let len = cmp::min(buf.len(), self.read_len - self.read_pos);
+        buf[..len].copy_from_slice(&self.payload[self.read_pos..self.read_pos + len]);
+        self.read_pos += len;
+        Ok(len)
+    }
+}
+
+impl<T: Read + Write> Write for NoiseStream<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = cmp::min(buf.len(), MAX_CHUNK_SIZE);
+        self.send(FRAME_DATA, &buf[..len])?;
+        if self.sent >= REKEY_INTERVAL {
+            self.rekey()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.transport.flush()
+    }
+}
+
+/// Checks the other side's static key against `expected`, once it is known. A key that never
+/// arrived only counts as a mismatch when the handshake is `finished`.
+fn check_remote_key(
+    handshake: &HandshakeState,
+    expected: Option<&[u8]>,
+    finished: bool,
+) -> Result<(), NoiseError> {
+    match (expected, handshake.get_remote_static()) {
+        (Some(expected), Some(remote)) if remote != expected => {
+            Err(NoiseError::UnexpectedRemoteKey)
+        }
+        (Some(_), None) if finished => Err(NoiseError::UnexpectedRemoteKey),
+        _ => Ok(()),
+    }
+}
+
+fn write_frame(transport: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    transport.write_all(&(message.len() as u16).to_be_bytes())?;
+    transport.write_all(message)
+}
+
+/// Reads one length prefixed message into `message`, returning `None` if the stream ended
+/// cleanly before it.
+fn read_frame(transport: &mut impl Read, message: &mut [u8]) -> io::Result<Option<usize>> {
+    let mut len = [0u8; 2];
+    if transport.read(&mut len[..1])? == 0 {
+        return Ok(None);
+    }
+    transport.read_exact(&mut len[1..])?;
+    let len = usize::from(u16::from_be_bytes(len));
+    transport.read_exact(&mut message[..len])?;
+    Ok(Some(len))
+}
+
+fn invalid_data<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    #[test]
+    fn empty_data_frame() {
+        let client_key = generate_keypair().unwrap();
+        let server_key = generate_keypair().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream =
+                NoiseStream::respond(stream, HandshakePattern::XX, &server_key.private, None)
+                    .unwrap();
+            stream.send(FRAME_DATA, &[]).unwrap();
+            stream.write_all(b"veil").unwrap();
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut stream =
+            NoiseStream::initiate(stream, HandshakePattern::XX, &client_key.private, None).unwrap();
+        let mut buf = [0u8; 8];
+        let len = stream.read(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"veil");
+        server.join().unwrap();
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+}
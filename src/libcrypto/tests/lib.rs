@@ -0,0 +1,108 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crypto::noise::{HandshakePattern, NoiseError, NoiseStream, generate_keypair};
+use std::io::{Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+const DATA_SIZE: usize = 200_000;
+
+#[test]
+fn noise_round_trip() {
+    let client_key = generate_keypair().unwrap();
+    let server_key = generate_keypair().unwrap();
+
+    for &pattern in &[HandshakePattern::XX, HandshakePattern::IK] {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_private = server_key.private.clone();
+        let client_public = client_key.public.clone();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream =
+                NoiseStream::respond(stream, pattern, &server_private, Some(&client_public))
+                    .unwrap();
+
+            // Echo the data back, it spans several messages and a rekey each way.
+            let mut data = vec![0u8; DATA_SIZE];
+            stream.read_exact(&mut data).unwrap();
+            stream.write_all(&data[..DATA_SIZE / 2]).unwrap();
+            stream.rekey().unwrap();
+            stream.write_all(&data[DATA_SIZE / 2..]).unwrap();
+        });
+
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut stream = NoiseStream::initiate(
+            stream,
+            pattern,
+            &client_key.private,
+            Some(&server_key.public),
+        )
+        .unwrap();
+        assert_eq!(stream.remote_public_key(), Some(&server_key.public[..]));
+
+        let data: Vec<u8> = (0..DATA_SIZE).map(|i| i as u8).collect();
+        stream.write_all(&data[..DATA_SIZE / 2]).unwrap();
+        stream.rekey().unwrap();
+        stream.write_all(&data[DATA_SIZE / 2..]).unwrap();
+
+        let mut echoed = vec![0u8; DATA_SIZE];
+        stream.read_exact(&mut echoed).unwrap();
+        assert_eq!(echoed, data);
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn noise_pinning() {
+    let client_key = generate_keypair().unwrap();
+    let server_key = generate_keypair().unwrap();
+    let other_key = generate_keypair().unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        NoiseStream::respond(stream, HandshakePattern::XX, &server_key.private, None).map(|_| ())
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let client = NoiseStream::initiate(
+        stream,
+        HandshakePattern::XX,
+        &client_key.private,
+        Some(&other_key.public),
+    );
+    assert!(matches!(client, Err(NoiseError::UnexpectedRemoteKey)));
+    // The client rejects the key before sending its last handshake message.
+    assert!(server.join().unwrap().is_err());
+
+    let client = NoiseStream::initiate(
+        Cursor::new(Vec::new()),
+        HandshakePattern::IK,
+        &client_key.private,
+        None,
+    );
+    assert!(matches!(client, Err(NoiseError::MissingRemoteKey)));
+}
@@ -22,17 +22,641 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
+use crate::error::{Error, ErrorKind};
+use crate::storage::DB;
+use crate::Result;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+
 pub enum Network {
     Main,
     Test,
 }
 
-pub struct Block {}
+/// A block hash in internal byte order.
+pub type BlockHash = [u8; 32];
+
+/// The parts of a block header needed to place it in the chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub hash: BlockHash,
+    pub prev_hash: BlockHash,
+    pub time: u32,
+    pub bits: u32,
+}
+
+/// A header in the block index along with its position in the chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockIndex {
+    header: Header,
+    height: u32,
+    chain_work: u128,
+    sequence: u64,
+}
+
+impl BlockIndex {
+    /// Length of an encoded entry, the hash itself is stored as the key.
+    const ENCODED_LEN: usize = 32 + 4 + 4 + 4 + 16 + 8;
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn hash(&self) -> &BlockHash {
+        &self.header.hash
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Total work of the chain up to and including this block.
+    pub fn chain_work(&self) -> u128 {
+        self.chain_work
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::ENCODED_LEN);
+        buf.extend_from_slice(&self.header.prev_hash);
+        buf.extend_from_slice(&self.header.time.to_be_bytes());
+        buf.extend_from_slice(&self.header.bits.to_be_bytes());
+        buf.extend_from_slice(&self.height.to_be_bytes());
+        buf.extend_from_slice(&self.chain_work.to_be_bytes());
+        buf.extend_from_slice(&self.sequence.to_be_bytes());
+        buf
+    }
+
+    fn from_bytes(key: &[u8], value: &[u8]) -> Result<Self> {
+        if key.len() != 32 || value.len() != Self::ENCODED_LEN {
+            return Err(Error::new(ErrorKind::CorruptHeaderIndex));
+        }
+
+        let mut hash = [0u8; 32];
+        let mut prev_hash = [0u8; 32];
+        hash.copy_from_slice(key);
+        prev_hash.copy_from_slice(&value[..32]);
+
+        Ok(Self {
+            header: Header {
+                hash,
+                prev_hash,
+                time: u32::from_be_bytes(value[32..36].try_into().unwrap()),
+                bits: u32::from_be_bytes(value[36..40].try_into().unwrap()),
+            },
+            height: u32::from_be_bytes(value[40..44].try_into().unwrap()),
+            chain_work: u128::from_be_bytes(value[44..60].try_into().unwrap()),
+            sequence: u64::from_be_bytes(value[60..68].try_into().unwrap()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TipStatus {
+    /// The tip of the most-work chain.
+    Active,
+    /// A branch that is not part of the most-work chain.
+    ValidFork,
+}
+
+/// A tip of the header index, the local counterpart of a `getchaintips` entry from veild.
+#[derive(Debug, PartialEq)]
+pub struct HeaderTip {
+    pub height: u32,
+    pub hash: BlockHash,
+    /// Length of the branch connecting the tip to the active chain.
+    pub branch_len: u32,
+    pub status: TipStatus,
+}
+
+/// A switch of the active chain to a branch that does not extend the old tip.
+///
+/// Anything indexed from the `disconnected` blocks has to be rolled back before the `connected`
+/// blocks are processed.
+#[derive(Debug, PartialEq)]
+pub struct Reorg {
+    fork_point: BlockHash,
+    disconnected: Vec<BlockHash>,
+    connected: Vec<BlockHash>,
+}
 
-impl Block {}
+impl Reorg {
+    /// The last block both the old and new active chains have in common.
+    pub fn fork_point(&self) -> &BlockHash {
+        &self.fork_point
+    }
 
+    /// Blocks removed from the active chain, starting from the old tip.
+    pub fn disconnected(&self) -> &[BlockHash] {
+        &self.disconnected
+    }
+
+    /// Blocks added to the active chain, starting after the fork point.
+    pub fn connected(&self) -> &[BlockHash] {
+        &self.connected
+    }
+
+    /// Number of blocks that were disconnected.
+    pub fn depth(&self) -> usize {
+        self.disconnected.len()
+    }
+}
+
+/// Header chain with best-chain selection.
+///
+/// Every header is stored by hash with its height and accumulated chain work. The active tip is
+/// the header with the most chain work, ties going to the header that was seen first.
+///
+/// Chain work is taken from the claimed `bits` without checking the header hash meets the target,
+/// or that the bits are right for the height. Headers must come from a trusted veild, which has
+/// validated them.
 pub struct Blockchain {
     chain: Network,
+    index: HashMap<BlockHash, BlockIndex>,
+    tip: Option<BlockHash>,
+    /// Hashes of the active chain by height, so lookups on it do not walk parent links.
+    active: Vec<BlockHash>,
+    next_sequence: u64,
+}
+
+impl Blockchain {
+    pub fn new(chain: Network) -> Self {
+        Self {
+            chain,
+            index: HashMap::new(),
+            tip: None,
+            active: Vec::new(),
+            next_sequence: 0,
+        }
+    }
+
+    /// Rebuilds the block index from the headers stored in `db`.
+    pub fn load(chain: Network, db: &DB) -> Result<Self> {
+        let entries = db
+            .headers()?
+            .into_iter()
+            .map(|(key, value)| BlockIndex::from_bytes(&key, &value))
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_entries(chain, entries)
+    }
+
+    /// Builds the index from stored entries in any order, failing if one of them is missing its
+    /// parent.
+    fn from_entries(chain: Network, entries: Vec<BlockIndex>) -> Result<Self> {
+        let mut blockchain = Self::new(chain);
+
+        for entry in entries {
+            blockchain.next_sequence = blockchain.next_sequence.max(entry.sequence + 1);
+            if blockchain.is_better(&entry) {
+                blockchain.tip = Some(entry.header.hash);
+            }
+            blockchain.index.insert(entry.header.hash, entry);
+        }
+        for entry in blockchain.index.values() {
+            let linked = match blockchain.index.get(&entry.header.prev_hash) {
+                Some(prev) => prev.height + 1 == entry.height,
+                None => entry.height == 0 && entry.header.prev_hash == [0u8; 32],
+            };
+            if !linked {
+                return Err(Error::new(ErrorKind::CorruptHeaderIndex));
+            }
+        }
+        if let Some(tip) = blockchain.tip {
+            blockchain.set_active(tip);
+        }
+
+        Ok(blockchain)
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.chain
+    }
+
+    pub fn get(&self, hash: &BlockHash) -> Option<&BlockIndex> {
+        self.index.get(hash)
+    }
+
+    pub fn contains(&self, hash: &BlockHash) -> bool {
+        self.index.contains_key(hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The tip of the active chain, `None` until the genesis header is added.
+    pub fn tip(&self) -> Option<&BlockIndex> {
+        self.tip.as_ref().and_then(|hash| self.index.get(hash))
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.tip().map(|tip| tip.height)
+    }
+
+    /// Writes a header to `db` and then adds it to the index.
+    ///
+    /// The index is left untouched when the write fails, so it never holds headers missing from
+    /// the database.
+    pub fn connect_header(&mut self, db: &mut DB, header: Header) -> Result<Option<Reorg>> {
+        if self.index.contains_key(&header.hash) {
+            return Ok(None);
+        }
+
+        let entry = self.index_entry(header)?;
+        db.put_header(entry.header.hash, &entry.to_bytes())?;
+        Ok(self.insert_entry(entry))
+    }
+
+    /// Adds a header to the index, updating the active tip if it has more work.
+    ///
+    /// The parent of the header has to be indexed already, only the genesis header (with a zeroed
+    /// previous hash) may be added to an empty index. Returns the reorg if the active chain
+    /// switched to another branch.
+    pub fn insert_header(&mut self, header: Header) -> Result<Option<Reorg>> {
+        if self.index.contains_key(&header.hash) {
+            return Ok(None);
+        }
+
+        let entry = self.index_entry(header)?;
+        Ok(self.insert_entry(entry))
+    }
+
+    /// Places a header not yet in the index after its parent.
+    fn index_entry(&mut self, header: Header) -> Result<BlockIndex> {
+        let work = block_work(header.bits).ok_or_else(|| Error::new(ErrorKind::InvalidBits))?;
+        let (height, chain_work) = match self.index.get(&header.prev_hash) {
+            Some(prev) => (prev.height + 1, prev.chain_work.saturating_add(work)),
+            None if self.index.is_empty() && header.prev_hash == [0u8; 32] => (0, work),
+            None => return Err(Error::new(ErrorKind::OrphanHeader)),
+        };
+
+        let entry = BlockIndex {
+            header,
+            height,
+            chain_work,
+            sequence: self.next_sequence,
+        };
+        self.next_sequence += 1;
+        Ok(entry)
+    }
+
+    fn insert_entry(&mut self, entry: BlockIndex) -> Option<Reorg> {
+        let hash = entry.header.hash;
+        let prev_hash = entry.header.prev_hash;
+        let is_better = self.is_better(&entry);
+        self.index.insert(hash, entry);
+
+        if !is_better {
+            return None;
+        }
+
+        let old_tip = self.tip.replace(hash);
+        self.set_active(hash);
+        match old_tip {
+            Some(old_tip) if old_tip != prev_hash => Some(self.reorg(&old_tip, &hash)),
+            _ => None,
+        }
+    }
+
+    /// Points the active chain at `tip`, rewriting heights back to where it joins the old one.
+    fn set_active(&mut self, tip: BlockHash) {
+        let height = self.index[&tip].height as usize;
+        self.active.resize(height + 1, [0u8; 32]);
+
+        let mut hash = tip;
+        for height in (0..=height).rev() {
+            if self.active[height] == hash {
+                break;
+            }
+            self.active[height] = hash;
+            hash = self.index[&hash].header.prev_hash;
+        }
+    }
+
+    /// Returns the ancestor of `hash` at `height`.
+    ///
+    /// Only the part of a branch off the active chain is walked, the rest is looked up by height.
+    pub fn ancestor(&self, hash: &BlockHash, height: u32) -> Option<&BlockIndex> {
+        let mut entry = self.index.get(hash)?;
+        if height > entry.height {
+            return None;
+        }
+
+        while entry.height > height {
+            if self.is_active(&entry.header.hash) {
+                return self.index.get(&self.active[height as usize]);
+            }
+            entry = self.index.get(&entry.header.prev_hash)?;
+        }
+
+        Some(entry)
+    }
+
+    /// Returns the last block the chains ending in `a` and `b` have in common.
+    pub fn fork_point(&self, a: &BlockHash, b: &BlockHash) -> Option<&BlockIndex> {
+        let (a, b) = (self.index.get(a)?, self.index.get(b)?);
+        let height = a.height.min(b.height);
+        let mut a = self.ancestor(&a.header.hash, height)?;
+        let mut b = self.ancestor(&b.header.hash, height)?;
+
+        while a.header.hash != b.header.hash {
+            a = self.index.get(&a.header.prev_hash)?;
+            b = self.index.get(&b.header.prev_hash)?;
+        }
+
+        Some(a)
+    }
+
+    /// Whether `hash` is part of the active chain.
+    pub fn is_active(&self, hash: &BlockHash) -> bool {
+        self.index
+            .get(hash)
+            .and_then(|entry| self.active.get(entry.height as usize))
+            .is_some_and(|active| active == hash)
+    }
+
+    /// Lists every chain tip, in the same order as veild's `getchaintips`.
+    pub fn chain_tips(&self) -> Vec<HeaderTip> {
+        let tip = match self.tip {
+            Some(tip) => tip,
+            None => return Vec::new(),
+        };
+        let parents: HashSet<&BlockHash> = self
+            .index
+            .values()
+            .map(|entry| &entry.header.prev_hash)
+            .collect();
+
+        let mut tips: Vec<HeaderTip> = self
+            .index
+            .values()
+            .filter(|entry| !parents.contains(&entry.header.hash))
+            .map(|entry| {
+                let fork_height = self
+                    .fork_point(&entry.header.hash, &tip)
+                    .map_or(0, |fork| fork.height);
+                HeaderTip {
+                    height: entry.height,
+                    hash: entry.header.hash,
+                    branch_len: entry.height - fork_height,
+                    status: if entry.header.hash == tip {
+                        TipStatus::Active
+                    } else {
+                        TipStatus::ValidFork
+                    },
+                }
+            })
+            .collect();
+
+        tips.sort_by(|a, b| b.height.cmp(&a.height).then(a.hash.cmp(&b.hash)));
+        tips
+    }
+
+    fn is_better(&self, entry: &BlockIndex) -> bool {
+        match self.tip() {
+            Some(tip) => {
+                entry.chain_work > tip.chain_work
+                    || (entry.chain_work == tip.chain_work && entry.sequence < tip.sequence)
+            }
+            None => true,
+        }
+    }
+
+    fn reorg(&self, old_tip: &BlockHash, new_tip: &BlockHash) -> Reorg {
+        // Both tips are indexed and share the genesis header, so a fork point always exists.
+        let fork_point = self
+            .fork_point(old_tip, new_tip)
+            .expect("Blockchain::reorg tips do not share a fork point")
+            .header
+            .hash;
+
+        Reorg {
+            fork_point,
+            disconnected: self.branch(old_tip, &fork_point),
+            connected: self
+                .branch(new_tip, &fork_point)
+                .into_iter()
+                .rev()
+                .collect(),
+        }
+    }
+
+    /// Hashes from `from` back to, but excluding, `to`.
+    fn branch(&self, from: &BlockHash, to: &BlockHash) -> Vec<BlockHash> {
+        let mut hashes = Vec::new();
+        let mut hash = *from;
+        while hash != *to {
+            hashes.push(hash);
+            hash = self.index[&hash].header.prev_hash;
+        }
+        hashes
+    }
+}
+
+/// Expected number of hashes needed to meet the compact target `bits`, `None` if the target is
+/// invalid.
+fn block_work(bits: u32) -> Option<u128> {
+    let target = compact_to_target(bits)?;
+
+    // 2^256 / (target + 1) does not fit in 256 bits, so this computes the equivalent
+    // (~target / (target + 1)) + 1 instead.
+    let mut denominator = target;
+    if !increment(&mut denominator) {
+        return Some(1);
+    }
+    let mut work = divide(
+        [!target[0], !target[1], !target[2], !target[3]],
+        denominator,
+    );
+    increment(&mut work);
+
+    if work[2] != 0 || work[3] != 0 {
+        Some(u128::MAX)
+    } else {
+        Some((u128::from(work[1]) << 64) | u128::from(work[0]))
+    }
 }
 
-impl Blockchain {}
+/// Expands a compact target into little-endian 64-bit words.
+///
+/// Returns `None` if the target is zero, negative or overflows 256 bits.
+fn compact_to_target(bits: u32) -> Option<[u64; 4]> {
+    let size = bits >> 24;
+    let mut word = bits & 0x007f_ffff;
+
+    let negative = word != 0 && (bits & 0x0080_0000) != 0;
+    let overflow =
+        word != 0 && (size > 34 || (word > 0xff && size > 33) || (word > 0xffff && size > 32));
+    if negative || overflow {
+        return None;
+    }
+
+    let mut target = [0u64; 4];
+    if size <= 3 {
+        word >>= 8 * (3 - size);
+        target[0] = u64::from(word);
+    } else {
+        let shift = 8 * (size - 3) as usize;
+        let (index, offset) = (shift / 64, shift % 64);
+        target[index] = u64::from(word) << offset;
+        if offset > 0 && index < 3 {
+            target[index + 1] = u64::from(word) >> (64 - offset);
+        }
+    }
+
+    if target == [0u64; 4] {
+        None
+    } else {
+        Some(target)
+    }
+}
+
+/// Adds one in place, returns `false` if it wrapped around to zero.
+fn increment(n: &mut [u64; 4]) -> bool {
+    for word in n.iter_mut() {
+        let (sum, carry) = word.overflowing_add(1);
+        *word = sum;
+        if !carry {
+            return true;
+        }
+    }
+    false
+}
+
+/// Long division of 256-bit little-endian numbers, `denominator` must not be zero.
+fn divide(numerator: [u64; 4], denominator: [u64; 4]) -> [u64; 4] {
+    let mut quotient = [0u64; 4];
+    let mut remainder = [0u64; 4];
+
+    for bit in (0..256).rev() {
+        let carry = remainder[3] >> 63;
+        for i in (1..4).rev() {
+            remainder[i] = (remainder[i] << 1) | (remainder[i - 1] >> 63);
+        }
+        remainder[0] = (remainder[0] << 1) | ((numerator[bit / 64] >> (bit % 64)) & 1);
+
+        if carry == 1 || !less_than(&remainder, &denominator) {
+            let mut borrow = false;
+            for (r, d) in remainder.iter_mut().zip(denominator.iter()) {
+                let (diff, b1) = r.overflowing_sub(*d);
+                let (diff, b2) = diff.overflowing_sub(borrow as u64);
+                *r = diff;
+                borrow = b1 || b2;
+            }
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    quotient
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()) == std::cmp::Ordering::Less
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BITS: u32 = 0x207f_ffff;
+
+    fn header(n: u8, prev: u8, bits: u32) -> Header {
+        Header {
+            hash: [n; 32],
+            prev_hash: [prev; 32],
+            time: u32::from(n),
+            bits,
+        }
+    }
+
+    #[test]
+    fn block_work_from_bits() {
+        assert_eq!(block_work(0x1d00_ffff), Some(0x1_0001_0001));
+        assert_eq!(block_work(BITS), Some(2));
+        assert_eq!(block_work(0), None);
+        assert_eq!(block_work(0x0180_0000), None);
+        assert_eq!(block_work(0x2300_ffff), None);
+    }
+
+    #[test]
+    fn rejects_invalid_bits() {
+        let mut chain = Blockchain::new(Network::Main);
+        chain.insert_header(header(1, 0, BITS)).unwrap();
+        chain.insert_header(header(2, 1, BITS)).unwrap();
+        let err = chain.insert_header(header(3, 2, 0x0180_0000)).unwrap_err();
+        assert!(matches!(err.source(), ErrorKind::InvalidBits));
+        assert!(!chain.contains(&[3; 32]));
+
+        // Without an indexed zero-work header, its child cannot fake a reorg.
+        assert!(chain.insert_header(header(4, 3, BITS)).is_err());
+        assert_eq!(chain.insert_header(header(4, 2, BITS)).unwrap(), None);
+    }
+
+    #[test]
+    fn load_requires_parents() {
+        let mut chain = Blockchain::new(Network::Main);
+        for n in 1..5 {
+            chain.insert_header(header(n, n - 1, BITS)).unwrap();
+        }
+        let entries = |skip: u8| {
+            let mut entries: Vec<BlockIndex> = chain
+                .index
+                .values()
+                .filter(|entry| entry.hash() != &[skip; 32])
+                .cloned()
+                .collect();
+            entries.reverse();
+            entries
+        };
+
+        let loaded = Blockchain::from_entries(Network::Main, entries(0)).unwrap();
+        assert_eq!(loaded.tip().unwrap().hash(), &[4; 32]);
+        assert_eq!(loaded.ancestor(&[4; 32], 1).unwrap().hash(), &[2; 32]);
+        assert!(Blockchain::from_entries(Network::Main, entries(2)).is_err());
+        assert!(Blockchain::from_entries(Network::Main, entries(1)).is_err());
+    }
+
+    #[test]
+    fn extends_and_reorgs() {
+        let mut chain = Blockchain::new(Network::Main);
+        assert_eq!(chain.insert_header(header(1, 0, BITS)).unwrap(), None);
+        for n in 2..5 {
+            assert_eq!(chain.insert_header(header(n, n - 1, BITS)).unwrap(), None);
+        }
+        assert_eq!(chain.height(), Some(3));
+
+        // A competing branch off block 2, it needs a third block to take over.
+        assert_eq!(chain.insert_header(header(10, 2, BITS)).unwrap(), None);
+        assert_eq!(chain.insert_header(header(11, 10, BITS)).unwrap(), None);
+        assert_eq!(chain.tip().unwrap().hash(), &[4; 32]);
+
+        let reorg = chain.insert_header(header(12, 11, BITS)).unwrap().unwrap();
+        assert_eq!(reorg.fork_point(), &[2; 32]);
+        assert_eq!(reorg.disconnected(), &[[4; 32], [3; 32]]);
+        assert_eq!(reorg.connected(), &[[10; 32], [11; 32], [12; 32]]);
+        assert_eq!(reorg.depth(), 2);
+        assert!(chain.is_active(&[11; 32]));
+        assert!(!chain.is_active(&[3; 32]));
+        assert_eq!(chain.ancestor(&[12; 32], 2).unwrap().hash(), &[10; 32]);
+        assert_eq!(chain.ancestor(&[4; 32], 1).unwrap().hash(), &[2; 32]);
+        assert_eq!(chain.ancestor(&[4; 32], 3).unwrap().hash(), &[4; 32]);
+        assert!(chain.ancestor(&[4; 32], 4).is_none());
+
+        let tips = chain.chain_tips();
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[0].status, TipStatus::Active);
+        assert_eq!(tips[1].hash, [4; 32]);
+        assert_eq!(tips[1].branch_len, 2);
+    }
+
+    #[test]
+    fn rejects_orphans() {
+        let mut chain = Blockchain::new(Network::Test);
+        assert!(chain.insert_header(header(2, 1, BITS)).is_err());
+        chain.insert_header(header(1, 0, BITS)).unwrap();
+        assert!(chain.insert_header(header(3, 7, BITS)).is_err());
+    }
+}
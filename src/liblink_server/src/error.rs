@@ -40,6 +40,9 @@ pub enum ErrorKind {
     MpscRecvError(mpsc::RecvError),
     MpscSendError(mpsc::SendError<_>),
     Quic(quiche::Error),
+    OrphanHeader,
+    CorruptHeaderIndex,
+    InvalidBits,
 }
 
 impl From<&ErrorKind> for i32 {
@@ -55,6 +58,9 @@ impl From<&ErrorKind> for i32 {
             ErrorKind::MpscRecvError(_) => 8,
             ErrorKind::MpscSendError(_) => 9,
             ErrorKind::Quic(_) => 10,
+            ErrorKind::OrphanHeader => 11,
+            ErrorKind::CorruptHeaderIndex => 12,
+            ErrorKind::InvalidBits => 13,
         }
     }
 }
@@ -93,6 +99,9 @@ impl fmt::Display for Error {
             ErrorKind::MpscRecvError(ref e) => e.fmt(f),
             ErrorKind::MpscSendError(ref e) => e.fmt(f),
             ErrorKind::Quic(ref e) => e.fmt(f),
+            ErrorKind::OrphanHeader => write!(f, "previous block of header is not indexed"),
+            ErrorKind::CorruptHeaderIndex => write!(f, "stored block index entry is corrupt"),
+            ErrorKind::InvalidBits => write!(f, "header has an invalid difficulty target"),
         }
     }
 }
@@ -110,6 +119,9 @@ impl error::Error for Error {
             ErrorKind::MpscRecvError(ref e) => Some(e),
             ErrorKind::MpscSendError(ref e) => Some(e),
             ErrorKind::Quic(ref e) => Some(e),
+            ErrorKind::OrphanHeader => None,
+            ErrorKind::CorruptHeaderIndex => None,
+            ErrorKind::InvalidBits => None,
         }
     }
 }
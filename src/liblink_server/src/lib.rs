@@ -40,6 +40,7 @@
 // Connect to RPC server and parse all RingCT transactions into a key value
 // storage
 
+pub mod blockchain;
 pub mod client;
 pub mod config;
 pub mod core_rpc;
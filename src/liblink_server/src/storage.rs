@@ -43,9 +43,14 @@ use veil;
 // Used for RingCT inputs
 // k: index # / v: txid:8, vout, whole data of the transaction out
 
+//////
+// HEADERS: Block index
+// k: block hash / v: prev hash, time, bits, height, chain work, sequence
+
 const KEYIMAGE_FAMILY: &str = "K";
 const TRANSACTION_FAMILY: &str = "T";
 const INDEX_FAMILY: &str = "I";
+const HEADER_FAMILY: &str = "H";
 
 pub struct Config {
     pub path: path::PathBuf,
@@ -69,14 +74,17 @@ impl DB {
             rocksdb::ColumnFamilyDescriptor::new(KEYIMAGE_FAMILY, rocksdb::Options::default());
         let index_cf =
             rocksdb::ColumnFamilyDescriptor::new(INDEX_FAMILY, rocksdb::Options::default());
+        let header_cf =
+            rocksdb::ColumnFamilyDescriptor::new(HEADER_FAMILY, rocksdb::Options::default());
         let mut db_cfg = rocksdb::Options::default();
         db_cfg.create_if_missing(true);
-        //        db_cfg.create_missing_column_families(true); // Might reduce code
+        // Databases created before a family was added open with it empty.
+        db_cfg.create_missing_column_families(true);
 
         let db = rocksdb::DB::open_cf_descriptors(
             &db_cfg,
             config.path,
-            vec![tx_cf, keyimage_cf, index_cf],
+            vec![tx_cf, keyimage_cf, index_cf, header_cf],
         )?;
         Ok(Self(db))
     }
@@ -99,6 +107,12 @@ impl DB {
             .expect("index column family missing")
     }
 
+    fn header_cf(&self) -> &rocksdb::ColumnFamily {
+        self.0
+            .cf_handle(HEADER_FAMILY)
+            .expect("header column family missing")
+    }
+
     /// Places a `KeyImage` into the database.
     ///
     /// * `key` - `KeyImage`
@@ -126,4 +140,20 @@ impl DB {
     pub fn put_index(&mut self, key: [u8; 32], value: u64) -> Result<()> {
         Ok(self.0.put_cf(self.index_cf(), key, value.to_be_bytes())?)
     }
+
+    /// Stores a block index entry.
+    ///
+    /// * `key` - `BlockHash`
+    /// * `value` - Encoded `BlockIndex` entry.
+    pub fn put_header(&mut self, key: [u8; 32], value: &[u8]) -> Result<()> {
+        Ok(self.0.put_cf(self.header_cf(), key, value)?)
+    }
+
+    /// Returns every stored block index entry as key-value pairs.
+    pub fn headers(&self) -> Result<Vec<(Box<[u8]>, Box<[u8]>)>> {
+        Ok(self
+            .0
+            .iterator_cf(self.header_cf(), rocksdb::IteratorMode::Start)?
+            .collect())
+    }
 }
@@ -3,8 +3,22 @@
 use std::env;
 use std::path::PathBuf;
 
+/// Optional modules of secp256k1-veil, the cargo feature enabling each and the define that
+/// compiles it in.
+const MODULES: [(&str, &str); 8] = [
+    ("ecdh", "ENABLE_MODULE_ECDH"),
+    ("recovery", "ENABLE_MODULE_RECOVERY"),
+    ("generator", "ENABLE_MODULE_GENERATOR"),
+    ("rangeproof", "ENABLE_MODULE_RANGEPROOF"),
+    ("mlsag", "ENABLE_MODULE_MLSAG"),
+    ("whitelist", "ENABLE_MODULE_WHITELIST"),
+    ("surjectionproof", "ENABLE_MODULE_SURJECTIONPROOF"),
+    ("schnorr", "ENABLE_MODULE_SCHNORR"),
+];
+
 fn main() {
-    let mut build = cc::Build::new()
+    let mut build = cc::Build::new();
+    build
         .include("depends/secp256k1-veil/")
         .include("depends/secp256k1-veil/include")
         .include("depends/secp256k1-veil/src")
@@ -14,16 +28,24 @@ fn main() {
         .define("USE_NUM_NONE", Some("1"))
         .define("USE_FIELD_INV_BUILTIN", Some("1"))
         .define("USE_SCALAR_INV_BUILTIN", Some("1"))
-        .define("ENABLE_MODULE_ECDH", Some("1"))
         .define("USE_EXTERNAL_DEFAULT_CALLBACKS", Some("1"))
         .define("ECMULT_WINDOW_SIZE", Some("15")) // This is the default in the configure file (`auto`)
         .define("USE_ENDOMORPHISM", Some("1"))
-        .define("ENABLE_MODULE_RECOVERY", Some("1"))
         .define("USE_FIELD_5X52", Some("1"))
         .define("USE_SCALAR_4X64", Some("1"))
-        .define("HAVE___INT128", Some("1"))
+        .define("HAVE___INT128", Some("1"));
+
+    for (feature, define) in MODULES.iter() {
+        let var = format!("CARGO_FEATURE_{}", feature.to_uppercase());
+        if env::var_os(var).is_some() {
+            build.define(define, Some("1"));
+        }
+    }
+
+    // secp256k1.c does not include the schnorr module, the wrapper adds it.
+    build
         .file("depends/secp256k1-veil/contrib/lax_der_parsing.c")
-        .file("depends/secp256k1-veil/src/secp256k1.c")
+        .file("depends/secp256k1_veil.c")
         .compile("libsecp256k1-veil.a");
 
     // The bindgen::Builder is the main entry point
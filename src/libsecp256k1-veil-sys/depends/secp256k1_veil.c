@@ -0,0 +1,43 @@
+/* Compiles secp256k1-veil with the modules its own secp256k1.c leaves out. */
+
+#include "src/secp256k1.c"
+
+#ifdef ENABLE_MODULE_SCHNORR
+# include "modules/schnorr/main_impl.h"
+#endif
+
+#ifdef ENABLE_MODULE_ECDH
+/** Like secp256k1_ecdh, but writes the shared point's x and y coordinates to `result` instead
+ *  of hashing them, so callers can apply the hash another implementation uses. The point is
+ *  computed and serialized in constant time.
+ *  Returns: 1: exponentiation was successful
+ *           0: scalar was invalid (zero or overflow)
+ *  Out:     result: a 64-byte array, x followed by y, both big endian
+ */
+int secp256k1_ecdh_point(const secp256k1_context* ctx, unsigned char *result, const secp256k1_pubkey *point, const unsigned char *scalar) {
+    int ret = 0;
+    int overflow = 0;
+    secp256k1_gej res;
+    secp256k1_ge pt;
+    secp256k1_scalar s;
+    VERIFY_CHECK(ctx != NULL);
+    ARG_CHECK(result != NULL);
+    ARG_CHECK(point != NULL);
+    ARG_CHECK(scalar != NULL);
+
+    secp256k1_pubkey_load(ctx, &pt, point);
+    secp256k1_scalar_set_b32(&s, scalar, &overflow);
+    if (!overflow && !secp256k1_scalar_is_zero(&s)) {
+        secp256k1_ecmult_const(&res, &pt, &s, 256);
+        secp256k1_ge_set_gej(&pt, &res);
+        secp256k1_fe_normalize(&pt.x);
+        secp256k1_fe_normalize(&pt.y);
+        secp256k1_fe_get_b32(result, &pt.x);
+        secp256k1_fe_get_b32(result + 32, &pt.y);
+        ret = 1;
+    }
+
+    secp256k1_scalar_clear(&s);
+    return ret;
+}
+#endif
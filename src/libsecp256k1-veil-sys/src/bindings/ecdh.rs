@@ -54,4 +54,33 @@ extern "C" {
         public_key: *const PublicKey,
         private_key: *const c_uchar,
     ) -> c_int;
+
+    /// Compute an EC Diffie-Hellman point in constant time, without hashing
+    /// it.
+    ///
+    /// # Argument
+    ///
+    /// `ctx` - A pointer to a context object (cannot be NULL).
+    ///
+    /// # Out
+    ///
+    /// `result` - A 64-byte array which will be populated by the x and y
+    /// coordinates of the shared point, both big endian.
+    ///
+    /// # In
+    ///
+    /// `public_key` - A pointer to a secp256k1_pubkey containing an initialized
+    /// public key.
+    /// `private_key` - A 32-byte scalar with which to multiply the point.
+    ///
+    /// # Returns
+    ///
+    /// `1` - Exponentiation was successful.
+    /// `0` - Scalar was invalid (zero or overflow).
+    pub fn secp256k1_ecdh_point(
+        ctx: *const Context,
+        result: *mut c_uchar,
+        public_key: *const PublicKey,
+        private_key: *const c_uchar,
+    ) -> c_int;
 }
@@ -44,13 +44,21 @@
 use crate::c_types::{c_int, c_uchar, c_uint, c_void, size_t};
 use crate::{Context, PublicKey, Signature};
 
+#[cfg(feature = "ecdh")]
 pub mod ecdh;
+#[cfg(feature = "generator")]
 pub mod generator;
+#[cfg(feature = "mlsag")]
 pub mod mlsag;
+#[cfg(feature = "rangeproof")]
 pub mod rangeproof;
+#[cfg(feature = "recovery")]
 pub mod recovery;
+#[cfg(feature = "schnorr")]
 pub mod schnorr;
+#[cfg(feature = "surjectionproof")]
 pub mod surjectionproof;
+#[cfg(feature = "whitelist")]
 pub mod whitelist;
 
 const FLAGS_TYPE_MASK: u32 = ((1 << 8) - 1);
@@ -301,6 +309,42 @@ extern "C" {
         input_len: size_t,
     ) -> c_int;
 
+    /// Parse a signature in "lax DER" format.
+    ///
+    /// This function accepts signatures violating DER in the ways older
+    /// implementations did: numbers parsed as nonnegative, excess padding,
+    /// trailing garbage and incorrect lengths. It is built from
+    /// `contrib/lax_der_parsing.c` and is not part of the library's API, do not
+    /// use it for new systems.
+    ///
+    /// After the call, sig will always be initialized. If parsing failed or the
+    /// encoded numbers are out of range, signature validation with it is
+    /// guaranteed to fail for every message and public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - A [`Context`] object.
+    ///
+    /// # Out
+    ///
+    /// * `sig` - A pointer to a [`Signature`] object.
+    ///
+    /// # In
+    ///
+    /// * `input` - A pointer to the signature to be parsed.
+    /// * `input_len` - The length of the array pointed to be input.
+    ///
+    /// Returns
+    ///
+    /// * `1` - Signature can be parsed.
+    /// * `0` - Signature can not be parsed.
+    pub fn ecdsa_signature_parse_der_lax(
+        ctx: *const Context,
+        sig: *mut Signature,
+        input: *const c_uchar,
+        input_len: size_t,
+    ) -> c_int;
+
     /// Serialize an ECDSA signature in DER format.
     ///
     /// # Arguments
@@ -23,15 +23,12 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 use crate::c_types::{c_int, c_uchar, size_t, uint64_t};
-use crate::{Context, Generator};
-
-#[repr(C)]
-pub struct PedersenCommitment([c_uchar; 33]);
+use crate::{Context, Generator, PedersenCommitment};
 
 #[link(name = "secp256k1-veil")]
 extern "C" {
-    // Not sure if this is needed.
-    pub static mut SECP256K1_GENERATOR_H: *const Generator;
+    /// The alternate generator used for the value in Pedersen commitments.
+    pub static secp256k1_generator_h: *const Generator;
 
     pub fn secp256k1_pedersen_commitment_parse(
         ctx: *const Context,
@@ -45,8 +42,6 @@ extern "C" {
         commit: *const PedersenCommitment,
     ) -> c_int;
 
-    pub fn secp256k1_pedersen_commitment_initialize(ctx: *mut Context);
-
     pub fn secp256k1_pedersen_commit(
         ctx: *const Context,
         commit: *mut PedersenCommitment,
@@ -87,8 +82,6 @@ extern "C" {
         n_inputs: size_t,
     ) -> c_int;
 
-    pub fn secp256k1_rangeproof_context_initialize(ctx: *mut Context);
-
     pub fn secp256k1_rangeproof_verify(
         ctx: *const Context,
         min_value: *mut uint64_t,
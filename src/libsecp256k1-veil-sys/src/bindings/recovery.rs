@@ -24,10 +24,7 @@
 
 use super::NonceFunction;
 use crate::c_types::{c_int, c_uchar, c_void};
-use crate::{Context, PublicKey, Signature};
-
-#[repr(C)]
-pub struct RecoverableSignature([c_uchar; 65]);
+use crate::{Context, PublicKey, RecoverableSignature, Signature};
 
 #[link(name = "secp256k1-veil")]
 extern "C" {
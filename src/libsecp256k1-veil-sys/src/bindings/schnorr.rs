@@ -23,7 +23,7 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 use super::NonceFunction;
-use crate::c_types::{c_int, c_uchar, c_void};
+use crate::c_types::{c_int, c_uchar, c_void, size_t};
 use crate::{Context, PublicKey};
 
 #[link(name = "secp256k1-veil")]
@@ -74,6 +74,6 @@ extern "C" {
         ctx: *const Context,
         sig64: *mut c_uchar,
         sig64_sin: *const *const c_uchar,
-        n: usize,
+        n: size_t,
     ) -> c_int;
 }
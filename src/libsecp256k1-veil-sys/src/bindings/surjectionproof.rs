@@ -23,19 +23,7 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 use crate::c_types::{c_int, c_uchar, size_t};
-use crate::{Context, Generator};
-
-#[derive(Copy, Clone)]
-#[repr(C)]
-pub struct SurjectionProof {
-    n_inputs: size_t,
-    used_inputs: [c_uchar; 32],
-    data: [c_uchar; 8224],
-}
-
-#[derive(Copy, Clone)]
-#[repr(C)]
-pub struct FixedAssetTag([c_uchar; 32]);
+use crate::{Context, FixedAssetTag, Generator, SurjectionProof};
 
 #[link(name = "secp256k1-veil")]
 extern "C" {
@@ -84,9 +72,9 @@ extern "C" {
         ctx: *const Context,
         proof: *mut SurjectionProof,
         ephemeral_input_tags: *const Generator,
-        n_ephemeral_input_tags: usize,
+        n_ephemeral_input_tags: size_t,
         ephemeral_output_tag: *const Generator,
-        input_index: usize,
+        input_index: size_t,
         input_blinding_key: *const c_uchar,
         output_blinding_key: *const c_uchar,
     ) -> c_int;
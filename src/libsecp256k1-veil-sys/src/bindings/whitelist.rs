@@ -24,49 +24,41 @@
 
 use super::NonceFunction;
 use crate::c_types::{c_int, c_uchar, c_void, size_t};
-use crate::{Context, PublicKey};
-
-const WHITELIST_MAX_N_KEYS: usize = 256;
-
-#[repr(C)]
-pub struct Secp256k1WhitelistSignature {
-    n_keys: size_t,
-    data: [c_uchar; 32 * (1 + WHITELIST_MAX_N_KEYS)],
-}
+use crate::{Context, PublicKey, WhitelistSignature};
 
 #[link(name = "secp256k1-veil")]
 extern "C" {
     pub fn secp256k1_whitelist_signature_parse(
         ctx: *const Context,
-        sig: *mut Secp256k1WhitelistSignature,
+        sig: *mut WhitelistSignature,
         input: *const c_uchar,
     ) -> c_int;
 
-    pub fn secp256k1_whitelist_signature_n_keys(sig: *const Secp256k1WhitelistSignature) -> usize;
+    pub fn secp256k1_whitelist_signature_n_keys(sig: *const WhitelistSignature) -> size_t;
 
     pub fn secp256k1_whitelist_signature_serialize(
         ctx: *const Context,
         output: *mut c_uchar,
-        sig: *const Secp256k1WhitelistSignature,
+        sig: *const WhitelistSignature,
     ) -> c_int;
 
     pub fn secp256k1_whitelist_sign(
         ctx: *const Context,
-        sig: *mut Secp256k1WhitelistSignature,
+        sig: *mut WhitelistSignature,
         online_pub_keys: *const PublicKey,
         offline_pub_keys: *const PublicKey,
-        n_keys: usize,
+        n_keys: size_t,
         sub_pub_key: *const PublicKey,
         online_sec_key: *const c_uchar,
         summed_sec_key: *const c_uchar,
-        index: usize,
+        index: size_t,
         nonce_fp: NonceFunction,
         nonce_data: *const c_void,
     ) -> c_int;
 
     pub fn secp256k1_whitelist_verify(
         ctx: *const Context,
-        sig: *const Secp256k1WhitelistSignature,
+        sig: *const WhitelistSignature,
         online_pubkeys: *const PublicKey,
         offline_pubkeys: *const PublicKey,
         sub_pubkey: *const PublicKey,
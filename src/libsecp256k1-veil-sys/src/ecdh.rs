@@ -37,3 +37,16 @@ pub unsafe fn ecdh(
         Ok(res)
     }
 }
+
+pub unsafe fn ecdh_point(
+    ctx: &Context,
+    public_key: &PublicKey,
+    private_key: *const c_uchar,
+) -> Result<[u8; 64]> {
+    let mut res = [0u8; 64];
+    if bindings::ecdh::secp256k1_ecdh_point(ctx, res.as_mut_ptr(), public_key, private_key) == 0 {
+        Err(Error::new(ErrorKind::Ecdh))
+    } else {
+        Ok(res)
+    }
+}
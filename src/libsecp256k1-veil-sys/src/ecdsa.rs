@@ -22,7 +22,7 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{bindings, c_types::*, Context, Error, ErrorKind, PublicKey, Result};
+use crate::{Context, Error, ErrorKind, PublicKey, Result, bindings, c_types::*};
 
 /// Opaque data structured that holds a parsed ECDSA signature.
 ///
@@ -32,18 +32,14 @@ use crate::{bindings, c_types::*, Context, Error, ErrorKind, PublicKey, Result};
 /// If you need to convert to a format suitable for storage, transmission, or
 /// comparison, use the secp256k1_ecdsa_signature_serialize_* and
 /// secp256k1_ecdsa_signature_parse_* functions.
-#[derive(Clone)] // Todo implement debug
+#[derive(Clone, Copy)]
 #[repr(C)]
 pub struct Signature(pub [c_uchar; 64]);
 
 impl Signature {
-    pub unsafe fn new() -> *mut Self {
-        &mut Self([0; 64]) as *mut Self
-    }
-
-    pub unsafe fn parse_compact(ctx: *const Context, input64: *const c_uchar) -> Result<*mut Self> {
-        let sig = Self::new();
-        if bindings::secp256k1_ecdsa_signature_parse_compact(ctx, sig, input64) == 0 {
+    pub unsafe fn parse_compact(ctx: *const Context, input64: *const c_uchar) -> Result<Self> {
+        let mut sig = Self([0; 64]);
+        if bindings::secp256k1_ecdsa_signature_parse_compact(ctx, &mut sig, input64) == 0 {
             Err(Error::new(ErrorKind::ParseKey))
         } else {
             Ok(sig)
@@ -54,9 +50,9 @@ impl Signature {
         ctx: *const Context,
         input: *const c_uchar,
         input_len: size_t,
-    ) -> Result<*mut Self> {
-        let sig = Self::new();
-        if bindings::secp256k1_ecdsa_signature_parse_der(ctx, sig, input, input_len) == 0 {
+    ) -> Result<Self> {
+        let mut sig = Self([0; 64]);
+        if bindings::secp256k1_ecdsa_signature_parse_der(ctx, &mut sig, input, input_len) == 0 {
             Err(Error::new(ErrorKind::ParseSignature))
         } else {
             Ok(sig)
@@ -69,24 +65,44 @@ impl Signature {
         Ok(output64)
     }
 
-    pub unsafe fn serialize_der(&self, ctx: *const Context) -> Result<(*mut c_uchar, *mut size_t)> {
-        let output: *mut c_uchar = 0 as *mut c_uchar;
-        let output_len: *mut size_t = 0 as *mut size_t;
-        if bindings::secp256k1_ecdsa_signature_serialize_der(ctx, output, output_len, self) == 0 {
-            Err(Error::new(ErrorKind::SerializeSignature))
+    /// Lax DER parsing, for signatures from systems that do not strictly obey DER.
+    pub unsafe fn parse_der_lax(
+        ctx: *const Context,
+        input: *const c_uchar,
+        input_len: size_t,
+    ) -> Result<Self> {
+        let mut sig = Self([0; 64]);
+        if bindings::ecdsa_signature_parse_der_lax(ctx, &mut sig, input, input_len) == 0 {
+            Err(Error::new(ErrorKind::ParseSignature))
         } else {
-            Ok((output, output_len))
+            Ok(sig)
         }
     }
 
-    pub unsafe fn normalize(&self, ctx: *const Context) -> Result<*mut Self> {
-        let signature_out = Self::new();
-        if bindings::secp256k1_ecdsa_signature_normalize(ctx, signature_out, self) == 0 {
-            Err(Error::new(ErrorKind::NormalizeSignature))
+    /// Returns the DER encoding in the first `len` bytes of the buffer.
+    pub unsafe fn serialize_der(&self, ctx: *const Context) -> Result<([u8; 72], size_t)> {
+        let mut output = [0u8; 72];
+        let mut output_len = output.len();
+        if bindings::secp256k1_ecdsa_signature_serialize_der(
+            ctx,
+            output.as_mut_ptr(),
+            &mut output_len,
+            self,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::SerializeSignature))
         } else {
-            Ok(signature_out)
+            Ok((output, output_len))
         }
     }
+
+    /// Returns the lower-S form of the signature and whether it differs from `self`.
+    pub unsafe fn normalize(&self, ctx: *const Context) -> (Self, bool) {
+        let mut signature_out = Self([0; 64]);
+        let normalized =
+            bindings::secp256k1_ecdsa_signature_normalize(ctx, &mut signature_out, self);
+        (signature_out, normalized == 1)
+    }
 }
 
 pub struct Ecdsa;
@@ -109,11 +125,17 @@ impl Ecdsa {
         ctx: *const Context,
         msg32: *const c_uchar,
         private_key: *const c_uchar,
-    ) -> Result<*mut Signature> {
-        let sig = Signature::new();
+    ) -> Result<Signature> {
+        let mut sig = Signature([0; 64]);
         let nonce_fp = bindings::secp256k1_nonce_function_rfc6979;
-        if bindings::secp256k1_ecdsa_sign(ctx, sig, msg32, private_key, nonce_fp, core::ptr::null())
-            == 0
+        if bindings::secp256k1_ecdsa_sign(
+            ctx,
+            &mut sig,
+            msg32,
+            private_key,
+            nonce_fp,
+            core::ptr::null(),
+        ) == 0
         {
             Err(Error::new(ErrorKind::SignMessage))
         } else {
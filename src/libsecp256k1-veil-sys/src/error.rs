@@ -42,10 +42,33 @@ pub enum ErrorKind {
     VerifySignature,
     SignMessage,
     Ecdh,
+    RecoverPublicKey,
+    GenerateNonce,
+    PartialSign,
+    PartialCombine,
     ParseGenerator,
     BadSeed,
     PrepareMlsag,
     KeyImageMlsag,
+    GenerateMlsag,
+    VerifyMlsag,
+    ParseCommitment,
+    Commit,
+    CommitmentSum,
+    VerifyTally,
+    BlindSum,
+    VerifyRangeProof,
+    RewindRangeProof,
+    SignRangeProof,
+    RangeProofInfo,
+    ParseSurjectionProof,
+    SerializeSurjectionProof,
+    InitializeSurjectionProof,
+    GenerateSurjectionProof,
+    VerifySurjectionProof,
+    ParseWhitelist,
+    SignWhitelist,
+    VerifyWhitelist,
 }
 
 #[derive(Debug)]
@@ -80,12 +103,40 @@ impl fmt::Display for Error {
             SignMessage => write!(f, "nonce generation function failed or the private key was invalid"),
             // Ecdh
             Ecdh => write!(f, "scalar was invalid (zero or overflow)"),
+            // Recovery
+            RecoverPublicKey => write!(f, "public key can not be recovered from the signature"),
+            // Schnorr
+            GenerateNonce => write!(f, "nonce generation function failed"),
+            PartialSign => write!(f, "invalid private key, nonce or public nonces"),
+            PartialCombine => write!(f, "partial signatures are invalid or do not share the same nonces"),
             // Generator
-            ParseGenerator => write!(f, "input is an invalid signature"),
+            ParseGenerator => write!(f, "input is an invalid generator"),
             BadSeed => write!(f, "seed supplied it not acceptable"),
             // Mlsag
             PrepareMlsag => write!(f, "could not prepare MLSAG, wrong size matrix or inputs"),
             KeyImageMlsag => write!(f, "key image error"),
+            GenerateMlsag => write!(f, "could not generate MLSAG, invalid keys or matrix"),
+            VerifyMlsag => write!(f, "MLSAG signature is invalid"),
+            // Rangeproof
+            ParseCommitment => write!(f, "input is an invalid commitment"),
+            Commit => write!(f, "blinding factor is invalid"),
+            CommitmentSum => write!(f, "sum of the commitments is not valid"),
+            VerifyTally => write!(f, "commitments do not sum to zero"),
+            BlindSum => write!(f, "sum of the blinding factors is not valid"),
+            VerifyRangeProof => write!(f, "range proof is invalid"),
+            RewindRangeProof => write!(f, "range proof can not be rewound with this nonce"),
+            SignRangeProof => write!(f, "range proof could not be created"),
+            RangeProofInfo => write!(f, "range proof header is invalid"),
+            // Surjectionproof
+            ParseSurjectionProof => write!(f, "input is an invalid surjection proof"),
+            SerializeSurjectionProof => write!(f, "not enough space was available to serialize"),
+            InitializeSurjectionProof => write!(f, "no input matching the output tag was found within the iteration limit"),
+            GenerateSurjectionProof => write!(f, "surjection proof could not be created, blinding keys or input index invalid"),
+            VerifySurjectionProof => write!(f, "surjection proof is invalid"),
+            // Whitelist
+            ParseWhitelist => write!(f, "whitelist signature has too many keys"),
+            SignWhitelist => write!(f, "whitelist signature could not be created, keys or index invalid"),
+            VerifyWhitelist => write!(f, "whitelist signature is invalid"),
         }
     }
 }
@@ -109,10 +160,33 @@ impl error::Error for Error {
             VerifySignature => None,
             SignMessage => None,
             Ecdh => None,
+            RecoverPublicKey => None,
+            GenerateNonce => None,
+            PartialSign => None,
+            PartialCombine => None,
             ParseGenerator => None,
             BadSeed => None,
             PrepareMlsag => None,
             KeyImageMlsag => None,
+            GenerateMlsag => None,
+            VerifyMlsag => None,
+            ParseCommitment => None,
+            Commit => None,
+            CommitmentSum => None,
+            VerifyTally => None,
+            BlindSum => None,
+            VerifyRangeProof => None,
+            RewindRangeProof => None,
+            SignRangeProof => None,
+            RangeProofInfo => None,
+            ParseSurjectionProof => None,
+            SerializeSurjectionProof => None,
+            InitializeSurjectionProof => None,
+            GenerateSurjectionProof => None,
+            VerifySurjectionProof => None,
+            ParseWhitelist => None,
+            SignWhitelist => None,
+            VerifyWhitelist => None,
         }
     }
 }
@@ -36,32 +36,29 @@ use crate::{Context, Error, ErrorKind, Result};
 ///
 /// Furthermore, it is guaranteed to identical points will have identical
 /// representation, so they can be memcmp'ed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(C)]
-pub struct Generator([c_uchar; 33]);
+pub struct Generator(pub [c_uchar; 33]);
 
 impl Generator {
-    pub unsafe fn new() -> *mut Self {
-        &mut Self([0; 33]) as *mut Self
-    }
-
-    pub unsafe fn parse(ctx: *const Context, input: *const c_uchar) -> Result<*mut Self> {
-        let commit = Self::new();
-        if secp256k1_generator_parse(ctx, commit, input) == 0 {
+    pub unsafe fn parse(ctx: *const Context, input: *const c_uchar) -> Result<Self> {
+        let mut generator = Self([0; 33]);
+        if secp256k1_generator_parse(ctx, &mut generator, input) == 0 {
             Err(Error::new(ErrorKind::ParseGenerator))
         } else {
-            Ok(commit)
+            Ok(generator)
         }
     }
 
-    pub unsafe fn serialize(ctx: *const Context, commit: *const Generator) -> Result<[u8; 33]> {
+    pub unsafe fn serialize(&self, ctx: *const Context) -> [u8; 33] {
         let mut output = [0 as c_uchar; 33];
-        secp256k1_generator_serialize(ctx, output.as_mut_ptr(), commit);
-        Ok(output)
+        secp256k1_generator_serialize(ctx, output.as_mut_ptr(), self); // can not fail
+        output
     }
 
-    pub unsafe fn generate(ctx: *const Context, seed32: *const c_uchar) -> Result<*mut Self> {
-        let generator = Self::new();
-        if secp256k1_generator_generate(ctx, generator, seed32) == 0 {
+    pub unsafe fn generate(ctx: *const Context, seed32: *const c_uchar) -> Result<Self> {
+        let mut generator = Self([0; 33]);
+        if secp256k1_generator_generate(ctx, &mut generator, seed32) == 0 {
             Err(Error::new(ErrorKind::BadSeed))
         } else {
             Ok(generator)
@@ -72,9 +69,9 @@ impl Generator {
         ctx: *const Context,
         seed32: *const c_uchar,
         blind32: *const c_uchar,
-    ) -> Result<*mut Self> {
-        let generator = Self::new();
-        if secp256k1_generator_generate_blinded(ctx, generator, seed32, blind32) == 0 {
+    ) -> Result<Self> {
+        let mut generator = Self([0; 33]);
+        if secp256k1_generator_generate_blinded(ctx, &mut generator, seed32, blind32) == 0 {
             Err(Error::new(ErrorKind::BadSeed))
         } else {
             Ok(generator)
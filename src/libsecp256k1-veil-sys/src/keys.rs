@@ -31,22 +31,18 @@ use crate::{bindings, c_types::*, Context, Error, ErrorKind, Result};
 /// however guaranteed to be 64 bytes in size, and can be safely copied/moved.
 /// If you need to convert to a format suitable for storage, transmission, or
 /// comparison, use secp256k1_ec_pubkey_serialize and secp256k1_ec_pubkey_parse.
-#[derive(Clone)] // Todo implement debug
+#[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct PublicKey(pub [c_uchar; 64]);
 
 impl PublicKey {
-    pub unsafe fn new() -> *mut Self {
-        &mut Self([0; 64]) as *mut Self
-    }
-
     pub unsafe fn parse(
         ctx: *const Context,
         input: *const c_uchar,
         input_len: size_t,
-    ) -> Result<*mut Self> {
-        let public_key = Self::new();
-        if bindings::secp256k1_ec_pubkey_parse(ctx, public_key, input, input_len) == 0 {
+    ) -> Result<Self> {
+        let mut public_key = Self([0; 64]);
+        if bindings::secp256k1_ec_pubkey_parse(ctx, &mut public_key, input, input_len) == 0 {
             Err(Error::new(ErrorKind::ParseKey))
         } else {
             Ok(public_key)
@@ -57,9 +53,9 @@ impl PublicKey {
         ctx: *const Context,
         ins: *const *const PublicKey,
         n: size_t,
-    ) -> Result<*mut Self> {
-        let public_key = Self::new();
-        if bindings::secp256k1_ec_pubkey_combine(ctx, public_key, ins, n) == 0 {
+    ) -> Result<Self> {
+        let mut public_key = Self([0; 64]);
+        if bindings::secp256k1_ec_pubkey_combine(ctx, &mut public_key, ins, n) == 0 {
             Err(Error::new(ErrorKind::CombinePublicKey))
         } else {
             Ok(public_key)
@@ -69,7 +65,7 @@ impl PublicKey {
     pub unsafe fn serialize_compressed(&self, ctx: *const Context) -> Result<[u8; 33]> {
         let flags = bindings::EC_COMPRESSED;
         let output = &mut [0u8; 33];
-        let output_len = &mut 0;
+        let output_len = &mut 33;
 
         bindings::secp256k1_ec_pubkey_serialize(
             ctx,
@@ -89,7 +85,7 @@ impl PublicKey {
     pub unsafe fn serialize_uncompressed(&self, ctx: *const Context) -> Result<[u8; 65]> {
         let flags = bindings::EC_UNCOMPRESSED;
         let output = &mut [0u8; 65];
-        let output_len: &mut size_t = &mut 0;
+        let output_len: &mut size_t = &mut 65;
 
         bindings::secp256k1_ec_pubkey_serialize(
             ctx,
@@ -106,10 +102,9 @@ impl PublicKey {
         }
     }
 
-    pub unsafe fn create(ctx: *const Context, secret_key: *const c_uchar) -> Result<*mut Self> {
-        let public_key = Self::new();
-        if bindings::secp256k1_ec_pubkey_create(ctx, public_key as *mut PublicKey, secret_key) == 0
-        {
+    pub unsafe fn create(ctx: *const Context, secret_key: *const c_uchar) -> Result<Self> {
+        let mut public_key = Self([0; 64]);
+        if bindings::secp256k1_ec_pubkey_create(ctx, &mut public_key, secret_key) == 0 {
             Err(Error::new(ErrorKind::CreatePublicKey))
         } else {
             Ok(public_key)
@@ -23,6 +23,7 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 #![no_std]
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
 
 #[cfg(any(feature = "std", test))]
 #[macro_use]
@@ -32,20 +33,45 @@ pub mod bindings;
 pub mod c_types;
 
 mod context;
+#[cfg(feature = "ecdh")]
 mod ecdh;
 mod ecdsa;
 mod error;
+#[cfg(feature = "generator")]
 mod generator;
 mod keys;
+#[cfg(feature = "mlsag")]
 mod mlsag;
+#[cfg(feature = "rangeproof")]
+mod rangeproof;
+#[cfg(feature = "recovery")]
+mod recovery;
+#[cfg(feature = "schnorr")]
+mod schnorr;
+#[cfg(feature = "surjectionproof")]
+mod surjectionproof;
+#[cfg(feature = "whitelist")]
+mod whitelist;
 
 pub use context::Context;
 #[cfg(feature = "ecdh")]
-pub use ecdh::ecdh;
+pub use ecdh::{ecdh, ecdh_point};
 pub use ecdsa::{Ecdsa, Signature};
 pub use error::{Error, ErrorKind};
 #[cfg(feature = "generator")]
 pub use generator::Generator;
 pub use keys::{PrivateKey, PublicKey};
+#[cfg(feature = "mlsag")]
+pub use mlsag::Mlsag;
+#[cfg(feature = "rangeproof")]
+pub use rangeproof::{blind_sum, PedersenCommitment, RangeProof};
+#[cfg(feature = "recovery")]
+pub use recovery::RecoverableSignature;
+#[cfg(feature = "schnorr")]
+pub use schnorr::Schnorr;
+#[cfg(feature = "surjectionproof")]
+pub use surjectionproof::{FixedAssetTag, SurjectionProof, SURJECTIONPROOF_MAX_N_INPUTS};
+#[cfg(feature = "whitelist")]
+pub use whitelist::{WhitelistSignature, WHITELIST_MAX_N_KEYS};
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
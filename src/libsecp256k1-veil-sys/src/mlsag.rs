@@ -22,53 +22,45 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
+use crate::Context;
 use crate::bindings::mlsag;
 use crate::c_types::*;
-use crate::Context;
 use crate::{Error, ErrorKind, Result};
 
-// pub fn combine(&self, ctx: &ContextNone, other: &PublicKey) -> Result<Self> {
-//     let ins: *const *const secp256k1_veil_sys::PublicKey = [
-//         self.0 as *const secp256k1_veil_sys::PublicKey,
-//         other.0 as *const secp256k1_veil_sys::PublicKey,
-//     ]
-//     .as_ptr();
-
-pub struct PublicKeyMatrix {
-    components: *mut c_uchar,
-    cols: size_t,
-    rows: size_t,
-}
-
+/// Multilayered linkable spontaneous anonymous group signatures, as used by RingCT.
+///
+/// Unlike the rest of the library these functions return `0` on success.
+///
+/// The public key matrix `pk` holds `cols * rows` 33 byte keys where the key in column `col`
+/// and row `row` is at `col + cols * row`. The last row is for the commitment sums.
 pub struct Mlsag;
 
 impl Mlsag {
     /// Prepares inputs for MLSAG algo
     ///
     /// Notes:
-    /// * It takes in both a public key matrix and a private key vector.
-    /// * It adds to the public_key matrix an extra "row" for the sum
-    /// * It adds to the private_key vector an extra "row" for the sum
+    /// * It fills the last row of the public key matrix with, for each column, the sum of the
+    ///   input commitments minus the sum of the output commitments.
+    /// * If `sk` and `blinds` are not null, it writes the input blinds minus the first
+    ///   `n_blinded` output blinds to `sk`.
     pub unsafe fn prepare(
-        pubkey_m: &PublicKeyMatrix,
-        privkey_m: *mut c_uchar,
-        pcm_in: *mut *const c_uchar, // &mut [*const c_uchar]
+        m: *mut c_uchar,
+        sk: *mut c_uchar,
+        n_outs: size_t,
+        n_blinded: size_t,
+        n_cols: size_t,
+        n_rows: size_t,
+        pcm_in: *mut *const c_uchar,
         pcm_out: *mut *const c_uchar,
         blinds: *mut *const c_uchar,
-        outs: size_t,
     ) -> Result<()> {
-        // TODO: Check if pubkey rows and cols is same size as private key's.
+        if n_rows < 2 || n_cols < 1 || n_outs < 1 {
+            return Err(Error::new(ErrorKind::PrepareMlsag));
+        }
+
         if mlsag::secp256k1_prepare_mlsag(
-            pubkey_m.components,
-            privkey_m,
-            outs,
-            outs,
-            pubkey_m.cols,
-            pubkey_m.rows,
-            pcm_in, // The pederson commitment
-            pcm_out,
-            blinds,
-        ) == 0
+            m, sk, n_outs, n_blinded, n_cols, n_rows, pcm_in, pcm_out, blinds,
+        ) != 0
         {
             Err(Error::new(ErrorKind::PrepareMlsag))
         } else {
@@ -81,16 +73,59 @@ impl Mlsag {
         public_key: *const c_uchar,
         private_key: *const c_uchar,
     ) -> Result<[u8; 33]> {
-        let key_image = &mut [0u8; 33]; // FIXME: Check actual size
-        if mlsag::secp256k1_get_keyimage(ctx, key_image.as_mut_ptr(), public_key, private_key) == 0
+        let mut key_image = [0u8; 33];
+        if mlsag::secp256k1_get_keyimage(ctx, key_image.as_mut_ptr(), public_key, private_key) != 0
         {
             Err(Error::new(ErrorKind::KeyImageMlsag))
         } else {
-            Ok(*key_image)
+            Ok(key_image)
+        }
+    }
+
+    /// Signs at column `index`, writing `n_rows - 1` key images to `ki`, the first challenge to
+    /// `pc` and `n_cols * n_rows` 32 byte responses to `ps`. `sk` points to a secret key for each
+    /// row.
+    pub unsafe fn generate(
+        ctx: *const Context,
+        ki: *mut c_uchar,
+        pc: *mut c_uchar,
+        ps: *mut c_uchar,
+        nonce: *const c_uchar,
+        pre_image: *const c_uchar,
+        n_cols: size_t,
+        n_rows: size_t,
+        index: size_t,
+        sk: *mut *const c_uchar,
+        pk: *const c_uchar,
+    ) -> Result<()> {
+        if mlsag::secp256k1_generate_mlsag(
+            ctx, ki, pc, ps, nonce, pre_image, n_cols, n_rows, index, sk, pk,
+        ) != 0
+        {
+            Err(Error::new(ErrorKind::GenerateMlsag))
+        } else {
+            Ok(())
         }
     }
 
-    pub unsafe fn generate(ctx: *const Context) {
-        unimplemented!()
+    pub unsafe fn verify(
+        ctx: *const Context,
+        pre_image: *const c_uchar,
+        n_cols: size_t,
+        n_rows: size_t,
+        pk: *const c_uchar,
+        ki: *const c_uchar,
+        pc: *const c_uchar,
+        ps: *const c_uchar,
+    ) -> Result<()> {
+        if n_rows < 2 || n_cols < 1 {
+            return Err(Error::new(ErrorKind::VerifyMlsag));
+        }
+
+        if mlsag::secp256k1_verify_mlsag(ctx, pre_image, n_cols, n_rows, pk, ki, pc, ps) != 0 {
+            Err(Error::new(ErrorKind::VerifyMlsag))
+        } else {
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,267 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+use crate::bindings::rangeproof::*;
+use crate::c_types::*;
+use crate::{Context, Error, ErrorKind, Generator, Result};
+
+/// Opaque data structure that stores a Pedersen commitment
+///
+/// The exact representation of data inside is implementation defined and not
+/// guaranteed to be portable between different platforms or versions. It is
+/// however guaranteed to be 33 bytes in size, and can be safely copied/moved.
+/// If you need to convert to a format suitable for storage or transmission, use
+/// secp256k1_pedersen_commitment_serialize and
+/// secp256k1_pedersen_commitment_parse.
+///
+/// Furthermore, it is guaranteed to identical commitments will have identical
+/// representation, so they can be memcmp'ed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct PedersenCommitment(pub [c_uchar; 33]);
+
+impl PedersenCommitment {
+    pub unsafe fn parse(ctx: *const Context, input: *const c_uchar) -> Result<Self> {
+        let mut commit = Self([0; 33]);
+        if secp256k1_pedersen_commitment_parse(ctx, &mut commit, input) == 0 {
+            Err(Error::new(ErrorKind::ParseCommitment))
+        } else {
+            Ok(commit)
+        }
+    }
+
+    pub unsafe fn serialize(&self, ctx: *const Context) -> [u8; 33] {
+        let mut output = [0 as c_uchar; 33];
+        secp256k1_pedersen_commitment_serialize(ctx, output.as_mut_ptr(), self); // can not fail
+        output
+    }
+
+    /// Commits to `value` with the blinding factor `blind`, using `gen` for the value or the
+    /// default H generator if `gen` is null.
+    pub unsafe fn commit(
+        ctx: *const Context,
+        blind: *const c_uchar,
+        value: uint64_t,
+        gen: *const Generator,
+    ) -> Result<Self> {
+        let mut commit = Self([0; 33]);
+        if secp256k1_pedersen_commit(ctx, &mut commit, blind, value, generator_or_h(gen)) == 0 {
+            Err(Error::new(ErrorKind::Commit))
+        } else {
+            Ok(commit)
+        }
+    }
+
+    pub unsafe fn sum(ctx: *const Context, commits: *const *const Self, n: size_t) -> Result<Self> {
+        let mut sum = Self([0; 33]);
+        if secp256k1_pedersen_commitment_sum(ctx, &mut sum, commits, n) == 0 {
+            Err(Error::new(ErrorKind::CommitmentSum))
+        } else {
+            Ok(sum)
+        }
+    }
+
+    pub unsafe fn verify_tally(
+        ctx: *const Context,
+        commits: *const *const Self,
+        p_cnt: size_t,
+        n_commits: *const *const Self,
+        n_cnt: size_t,
+    ) -> Result<()> {
+        if secp256k1_pedersen_verify_tally(ctx, commits, p_cnt, n_commits, n_cnt) == 0 {
+            Err(Error::new(ErrorKind::VerifyTally))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Sums the blinding factors, the first `n_positive` added and the rest subtracted.
+pub unsafe fn blind_sum(
+    ctx: *const Context,
+    blinds: *const *const c_uchar,
+    n: size_t,
+    n_positive: size_t,
+) -> Result<[u8; 32]> {
+    let mut blind_out = [0u8; 32];
+    if secp256k1_pedersen_blind_sum(ctx, blind_out.as_mut_ptr(), blinds, n, n_positive) == 0 {
+        Err(Error::new(ErrorKind::BlindSum))
+    } else {
+        Ok(blind_out)
+    }
+}
+
+pub struct RangeProof;
+
+impl RangeProof {
+    /// Verifies the proof, returning the minimum and maximum value committed to.
+    pub unsafe fn verify(
+        ctx: *const Context,
+        commit: *const PedersenCommitment,
+        proof: *const c_uchar,
+        p_len: size_t,
+        extra_commit: *const c_uchar,
+        extra_commit_len: size_t,
+        gen: *const Generator,
+    ) -> Result<(u64, u64)> {
+        let mut min_value = 0;
+        let mut max_value = 0;
+        if secp256k1_rangeproof_verify(
+            ctx,
+            &mut min_value,
+            &mut max_value,
+            commit,
+            proof,
+            p_len,
+            extra_commit,
+            extra_commit_len,
+            generator_or_h(gen),
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::VerifyRangeProof))
+        } else {
+            Ok((min_value, max_value))
+        }
+    }
+
+    /// Recovers the value, blinding factor and message of a proof. `message_out` must point to
+    /// 4096 bytes, `out_len` receives the length of the message.
+    ///
+    /// Returns the value, blinding factor and the minimum and maximum value.
+    pub unsafe fn rewind(
+        ctx: *const Context,
+        message_out: *mut c_uchar,
+        out_len: *mut size_t,
+        nonce: *const c_uchar,
+        commit: *const PedersenCommitment,
+        proof: *const c_uchar,
+        p_len: size_t,
+        extra_commit: *const c_uchar,
+        extra_commit_len: size_t,
+        gen: *const Generator,
+    ) -> Result<(u64, [u8; 32], u64, u64)> {
+        let mut blind_out = [0u8; 32];
+        let mut value_out = 0;
+        let mut min_value = 0;
+        let mut max_value = 0;
+        if secp256k1_rangeproof_rewind(
+            ctx,
+            blind_out.as_mut_ptr(),
+            &mut value_out,
+            message_out,
+            out_len,
+            nonce,
+            &mut min_value,
+            &mut max_value,
+            commit,
+            proof,
+            p_len,
+            extra_commit,
+            extra_commit_len,
+            generator_or_h(gen),
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::RewindRangeProof))
+        } else {
+            Ok((value_out, blind_out, min_value, max_value))
+        }
+    }
+
+    /// Writes a proof to `proof`, `p_len` holds the space available and receives the length of
+    /// the proof.
+    pub unsafe fn sign(
+        ctx: *const Context,
+        proof: *mut c_uchar,
+        p_len: *mut size_t,
+        min_value: uint64_t,
+        commit: *const PedersenCommitment,
+        blind: *const c_uchar,
+        nonce: *const c_uchar,
+        exp: c_int,
+        min_bits: c_int,
+        value: uint64_t,
+        message: *const c_uchar,
+        msg_len: size_t,
+        extra_commit: *const c_uchar,
+        extra_commit_len: size_t,
+        gen: *const Generator,
+    ) -> Result<()> {
+        if secp256k1_rangeproof_sign(
+            ctx,
+            proof,
+            p_len,
+            min_value,
+            commit,
+            blind,
+            nonce,
+            exp,
+            min_bits,
+            value,
+            message,
+            msg_len,
+            extra_commit,
+            extra_commit_len,
+            generator_or_h(gen),
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::SignRangeProof))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the exponent, mantissa and the minimum and maximum value of a proof.
+    pub unsafe fn info(
+        ctx: *const Context,
+        proof: *const c_uchar,
+        p_len: size_t,
+    ) -> Result<(c_int, c_int, u64, u64)> {
+        let mut exp = 0;
+        let mut mantissa = 0;
+        let mut min_value = 0;
+        let mut max_value = 0;
+        if secp256k1_rangeproof_info(
+            ctx,
+            &mut exp,
+            &mut mantissa,
+            &mut min_value,
+            &mut max_value,
+            proof,
+            p_len,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::RangeProofInfo))
+        } else {
+            Ok((exp, mantissa, min_value, max_value))
+        }
+    }
+}
+
+unsafe fn generator_or_h(gen: *const Generator) -> *const Generator {
+    if gen.is_null() {
+        secp256k1_generator_h
+    } else {
+        gen
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::bindings::{self, recovery::*};
+use crate::c_types::*;
+use crate::{Context, Error, ErrorKind, PublicKey, Result, Signature};
+
+/// Opaque data structured that holds a parsed ECDSA signature, supporting pubkey
+/// recovery.
+///
+/// The exact representation of data inside is implementation defined and not
+/// guaranteed to be portable between different platforms or versions. It is
+/// however guaranteed to be 65 bytes in size, and can be safely copied/moved.
+/// If you need to convert to a format suitable for storage or transmission, use
+/// the secp256k1_ecdsa_signature_serialize_* and
+/// secp256k1_ecdsa_signature_parse_* functions.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct RecoverableSignature(pub [c_uchar; 65]);
+
+impl RecoverableSignature {
+    pub unsafe fn parse_compact(
+        ctx: *const Context,
+        input64: *const c_uchar,
+        rec_id: c_int,
+    ) -> Result<Self> {
+        let mut sig = Self([0; 65]);
+        if secp256k1_ecdsa_recoverable_signature_parse_compact(ctx, &mut sig, input64, rec_id) == 0
+        {
+            Err(Error::new(ErrorKind::ParseSignature))
+        } else {
+            Ok(sig)
+        }
+    }
+
+    /// Returns the compact signature along with its recovery id.
+    pub unsafe fn serialize_compact(&self, ctx: *const Context) -> ([u8; 64], c_int) {
+        let mut output64 = [0u8; 64];
+        let mut rec_id = 0;
+        secp256k1_ecdsa_recoverable_signature_serialize_compact(
+            ctx,
+            output64.as_mut_ptr(),
+            &mut rec_id,
+            self,
+        ); // can not fail
+        (output64, rec_id)
+    }
+
+    /// Drops the recovery id, giving a signature usable with `Ecdsa::verify`.
+    pub unsafe fn convert(&self, ctx: *const Context) -> Signature {
+        let mut sig = Signature([0; 64]);
+        secp256k1_ecdsa_recoverable_signature_convert(ctx, &mut sig, self); // can not fail
+        sig
+    }
+
+    pub unsafe fn sign(
+        ctx: *const Context,
+        msg32: *const c_uchar,
+        private_key: *const c_uchar,
+    ) -> Result<Self> {
+        let mut sig = Self([0; 65]);
+        let nonce_fp = bindings::secp256k1_nonce_function_rfc6979;
+        if secp256k1_ecdsa_sign_recoverable(
+            ctx,
+            &mut sig,
+            msg32,
+            private_key,
+            nonce_fp,
+            core::ptr::null(),
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::SignMessage))
+        } else {
+            Ok(sig)
+        }
+    }
+
+    pub unsafe fn recover(&self, ctx: *const Context, msg32: *const c_uchar) -> Result<PublicKey> {
+        let mut public_key = PublicKey([0; 64]);
+        if secp256k1_ecdsa_recover(ctx, &mut public_key, self, msg32) == 0 {
+            Err(Error::new(ErrorKind::RecoverPublicKey))
+        } else {
+            Ok(public_key)
+        }
+    }
+}
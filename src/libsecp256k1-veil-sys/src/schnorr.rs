@@ -0,0 +1,145 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::bindings::{self, schnorr::*};
+use crate::c_types::*;
+use crate::{Context, Error, ErrorKind, PublicKey, Result};
+
+pub struct Schnorr;
+
+impl Schnorr {
+    /// Signs with the default nonce function, `nonce_data` is passed to it as extra entropy and
+    /// may be null.
+    pub unsafe fn sign(
+        ctx: *const Context,
+        msg32: *const c_uchar,
+        private_key: *const c_uchar,
+        nonce_data: *const c_void,
+    ) -> Result<[u8; 64]> {
+        let mut sig64 = [0u8; 64];
+        let nonce_fp = bindings::secp256k1_nonce_function_default;
+        if secp256k1_schnorr_sign(
+            ctx,
+            sig64.as_mut_ptr(),
+            msg32,
+            private_key,
+            nonce_fp,
+            nonce_data,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::SignMessage))
+        } else {
+            Ok(sig64)
+        }
+    }
+
+    pub unsafe fn verify(
+        ctx: *const Context,
+        sig64: *const c_uchar,
+        msg32: *const c_uchar,
+        public_key: *const PublicKey,
+    ) -> Result<()> {
+        if secp256k1_schnorr_verify(ctx, sig64, msg32, public_key) == 0 {
+            Err(Error::new(ErrorKind::VerifySignature))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub unsafe fn recover(
+        ctx: *const Context,
+        sig64: *const c_uchar,
+        msg32: *const c_uchar,
+    ) -> Result<PublicKey> {
+        let mut public_key = PublicKey([0; 64]);
+        if secp256k1_schnorr_recover(ctx, &mut public_key, sig64, msg32) == 0 {
+            Err(Error::new(ErrorKind::RecoverPublicKey))
+        } else {
+            Ok(public_key)
+        }
+    }
+
+    /// Returns the public and private nonce, see `sign` for `nonce_data`.
+    pub unsafe fn generate_nonce_pair(
+        ctx: *const Context,
+        msg32: *const c_uchar,
+        private_key: *const c_uchar,
+        nonce_data: *const c_void,
+    ) -> Result<(PublicKey, [u8; 32])> {
+        let mut pub_nonce = PublicKey([0; 64]);
+        let mut priv_nonce32 = [0u8; 32];
+        let nonce_fp = bindings::secp256k1_nonce_function_default;
+        if secp256k1_schnorr_generate_nonce_pair(
+            ctx,
+            &mut pub_nonce,
+            priv_nonce32.as_mut_ptr(),
+            msg32,
+            private_key,
+            nonce_fp,
+            nonce_data,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::GenerateNonce))
+        } else {
+            Ok((pub_nonce, priv_nonce32))
+        }
+    }
+
+    /// Signs with our private key and nonce, `pub_nonce_others` being the sum of the other
+    /// signers' public nonces.
+    pub unsafe fn partial_sign(
+        ctx: *const Context,
+        msg32: *const c_uchar,
+        private_key: *const c_uchar,
+        pub_nonce_others: *const PublicKey,
+        sec_nonce32: *const c_uchar,
+    ) -> Result<[u8; 64]> {
+        let mut sig64 = [0u8; 64];
+        if secp256k1_schnorr_partial_sign(
+            ctx,
+            sig64.as_mut_ptr(),
+            msg32,
+            private_key,
+            pub_nonce_others,
+            sec_nonce32,
+        ) != 1
+        {
+            Err(Error::new(ErrorKind::PartialSign))
+        } else {
+            Ok(sig64)
+        }
+    }
+
+    pub unsafe fn partial_combine(
+        ctx: *const Context,
+        sig64_ins: *const *const c_uchar,
+        n: size_t,
+    ) -> Result<[u8; 64]> {
+        let mut sig64 = [0u8; 64];
+        if secp256k1_schnorr_partial_combine(ctx, sig64.as_mut_ptr(), sig64_ins, n) != 1 {
+            Err(Error::new(ErrorKind::PartialCombine))
+        } else {
+            Ok(sig64)
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::bindings::surjectionproof::*;
+use crate::c_types::*;
+use crate::{Context, Error, ErrorKind, Generator, Result};
+
+/// Most inputs a surjection proof can cover.
+pub const SURJECTIONPROOF_MAX_N_INPUTS: usize = 256;
+
+/// Opaque data structure that holds a parsed surjection proof
+///
+/// The representation mirrors the C struct so it can live on the stack, it
+/// should only be accessed through the secp256k1_surjectionproof_* functions.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct SurjectionProof {
+    n_inputs: size_t,
+    used_inputs: [c_uchar; SURJECTIONPROOF_MAX_N_INPUTS / 8],
+    data: [c_uchar; 32 * (1 + SURJECTIONPROOF_MAX_N_INPUTS)],
+}
+
+/// Data structure that holds a fixed asset tag.
+///
+/// This data type is *not* opaque. It will always be 32 bytes of whatever
+/// data the API user wants to use as an asset tag. Its contents have no
+/// semantic meaning to libsecp whatsoever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct FixedAssetTag(pub [c_uchar; 32]);
+
+impl SurjectionProof {
+    fn empty() -> Self {
+        Self {
+            n_inputs: 0,
+            used_inputs: [0; SURJECTIONPROOF_MAX_N_INPUTS / 8],
+            data: [0; 32 * (1 + SURJECTIONPROOF_MAX_N_INPUTS)],
+        }
+    }
+
+    pub unsafe fn parse(
+        ctx: *const Context,
+        input: *const c_uchar,
+        input_len: size_t,
+    ) -> Result<Self> {
+        let mut proof = Self::empty();
+        if secp256k1_surjectionproof_parse(ctx, &mut proof, input, input_len) == 0 {
+            Err(Error::new(ErrorKind::ParseSurjectionProof))
+        } else {
+            Ok(proof)
+        }
+    }
+
+    /// Writes the proof to `output`, `output_len` holds the space available and receives the
+    /// length of the proof.
+    pub unsafe fn serialize(
+        &self,
+        ctx: *const Context,
+        output: *mut c_uchar,
+        output_len: *mut size_t,
+    ) -> Result<()> {
+        if secp256k1_surjectionproof_serialize(ctx, output, output_len, self) == 0 {
+            Err(Error::new(ErrorKind::SerializeSurjectionProof))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub unsafe fn n_total_inputs(&self, ctx: *const Context) -> size_t {
+        secp256k1_surjectionproof_n_total_inputs(ctx, self)
+    }
+
+    pub unsafe fn n_used_inputs(&self, ctx: *const Context) -> size_t {
+        secp256k1_surjectionproof_n_used_inputs(ctx, self)
+    }
+
+    pub unsafe fn serialized_size(&self, ctx: *const Context) -> size_t {
+        secp256k1_surjectionproof_serialized_size(ctx, self)
+    }
+
+    /// Picks `n_input_tags_to_use` of the inputs at random, making sure one of them matches
+    /// `fixed_output_tag`.
+    ///
+    /// Returns the proof along with the index of the matching input.
+    pub unsafe fn initialize(
+        ctx: *const Context,
+        fixed_input_tags: *const FixedAssetTag,
+        n_input_tags: size_t,
+        n_input_tags_to_use: size_t,
+        fixed_output_tag: *const FixedAssetTag,
+        n_max_iterations: size_t,
+        random_seed32: *const c_uchar,
+    ) -> Result<(Self, size_t)> {
+        let mut proof = Self::empty();
+        let mut input_index = 0;
+        if secp256k1_surjectionproof_initialize(
+            ctx,
+            &mut proof,
+            &mut input_index,
+            fixed_input_tags,
+            n_input_tags,
+            n_input_tags_to_use,
+            fixed_output_tag,
+            n_max_iterations,
+            random_seed32,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::InitializeSurjectionProof))
+        } else {
+            Ok((proof, input_index))
+        }
+    }
+
+    pub unsafe fn generate(
+        &mut self,
+        ctx: *const Context,
+        ephemeral_input_tags: *const Generator,
+        n_ephemeral_input_tags: size_t,
+        ephemeral_output_tag: *const Generator,
+        input_index: size_t,
+        input_blinding_key: *const c_uchar,
+        output_blinding_key: *const c_uchar,
+    ) -> Result<()> {
+        if secp256k1_surjectionproof_generate(
+            ctx,
+            self,
+            ephemeral_input_tags,
+            n_ephemeral_input_tags,
+            ephemeral_output_tag,
+            input_index,
+            input_blinding_key,
+            output_blinding_key,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::GenerateSurjectionProof))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub unsafe fn verify(
+        &self,
+        ctx: *const Context,
+        ephemeral_input_tags: *const Generator,
+        n_ephemeral_input_tags: size_t,
+        ephemeral_output_tag: *const Generator,
+    ) -> Result<()> {
+        if secp256k1_surjectionproof_verify(
+            ctx,
+            self,
+            ephemeral_input_tags,
+            n_ephemeral_input_tags,
+            ephemeral_output_tag,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::VerifySurjectionProof))
+        } else {
+            Ok(())
+        }
+    }
+}
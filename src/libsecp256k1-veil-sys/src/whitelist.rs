@@ -0,0 +1,128 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::bindings::{self, whitelist::*};
+use crate::c_types::*;
+use crate::{Context, Error, ErrorKind, PublicKey, Result};
+
+/// Most key pairs a whitelist signature can cover.
+pub const WHITELIST_MAX_N_KEYS: usize = 256;
+
+/// Opaque data structure that holds a parsed whitelist proof
+///
+/// The representation mirrors the C struct so it can live on the stack, it
+/// should only be accessed through the secp256k1_whitelist_* functions.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct WhitelistSignature {
+    n_keys: size_t,
+    data: [c_uchar; 32 * (1 + WHITELIST_MAX_N_KEYS)],
+}
+
+impl WhitelistSignature {
+    fn empty() -> Self {
+        Self {
+            n_keys: 0,
+            data: [0; 32 * (1 + WHITELIST_MAX_N_KEYS)],
+        }
+    }
+
+    /// Parses a signature, `input` must hold `33 + 32 * n_keys` bytes where `n_keys` is its
+    /// first byte.
+    pub unsafe fn parse(ctx: *const Context, input: *const c_uchar) -> Result<Self> {
+        let mut sig = Self::empty();
+        if secp256k1_whitelist_signature_parse(ctx, &mut sig, input) == 0 {
+            Err(Error::new(ErrorKind::ParseWhitelist))
+        } else {
+            Ok(sig)
+        }
+    }
+
+    pub unsafe fn n_keys(&self) -> size_t {
+        secp256k1_whitelist_signature_n_keys(self)
+    }
+
+    /// Writes `33 + 32 * n_keys` bytes to `output`.
+    pub unsafe fn serialize(&self, ctx: *const Context, output: *mut c_uchar) {
+        secp256k1_whitelist_signature_serialize(ctx, output, self);
+    }
+
+    /// Signs with the default nonce function, `nonce_data` may be null.
+    ///
+    /// `summed_private_key` is the private key of the sum of `sub_public_key` and the signer's
+    /// offline public key.
+    pub unsafe fn sign(
+        ctx: *const Context,
+        online_public_keys: *const PublicKey,
+        offline_public_keys: *const PublicKey,
+        n_keys: size_t,
+        sub_public_key: *const PublicKey,
+        online_private_key: *const c_uchar,
+        summed_private_key: *const c_uchar,
+        index: size_t,
+        nonce_data: *const c_void,
+    ) -> Result<Self> {
+        let mut sig = Self::empty();
+        let nonce_fp = bindings::secp256k1_nonce_function_default;
+        if secp256k1_whitelist_sign(
+            ctx,
+            &mut sig,
+            online_public_keys,
+            offline_public_keys,
+            n_keys,
+            sub_public_key,
+            online_private_key,
+            summed_private_key,
+            index,
+            nonce_fp,
+            nonce_data,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::SignWhitelist))
+        } else {
+            Ok(sig)
+        }
+    }
+
+    /// Both key lists must hold `n_keys` keys.
+    pub unsafe fn verify(
+        &self,
+        ctx: *const Context,
+        online_public_keys: *const PublicKey,
+        offline_public_keys: *const PublicKey,
+        sub_public_key: *const PublicKey,
+    ) -> Result<()> {
+        if secp256k1_whitelist_verify(
+            ctx,
+            self,
+            online_public_keys,
+            offline_public_keys,
+            sub_public_key,
+        ) == 0
+        {
+            Err(Error::new(ErrorKind::VerifyWhitelist))
+        } else {
+            Ok(())
+        }
+    }
+}
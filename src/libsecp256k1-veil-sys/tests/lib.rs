@@ -0,0 +1,170 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Calls into every optional module, checking its C code was compiled and linked.
+
+// The helpers go unused when every module is disabled.
+#![allow(dead_code)]
+
+use secp256k1_veil_sys::bindings::{CONTEXT_SIGN, CONTEXT_VERIFY};
+use secp256k1_veil_sys::{Context, PublicKey};
+
+const SECRET: [u8; 32] = [1; 32];
+const MESSAGE: [u8; 32] = [2; 32];
+
+fn context() -> *mut Context {
+    unsafe { Context::new(CONTEXT_SIGN | CONTEXT_VERIFY) }
+}
+
+fn public_key(ctx: *const Context) -> PublicKey {
+    unsafe { PublicKey::create(ctx, SECRET.as_ptr()).unwrap() }
+}
+
+#[cfg(feature = "ecdh")]
+#[test]
+fn ecdh() {
+    let ctx = context();
+    let public_key = public_key(ctx);
+    unsafe {
+        let secret = secp256k1_veil_sys::ecdh(&*ctx, &public_key, SECRET.as_ptr()).unwrap();
+        assert_ne!(secret, [0; 32]);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "recovery")]
+#[test]
+fn recovery() {
+    use secp256k1_veil_sys::RecoverableSignature;
+
+    let ctx = context();
+    unsafe {
+        let sig = RecoverableSignature::sign(ctx, MESSAGE.as_ptr(), SECRET.as_ptr()).unwrap();
+        let recovered = sig.recover(ctx, MESSAGE.as_ptr()).unwrap();
+        assert_eq!(recovered.0[..], public_key(ctx).0[..]);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn generator() {
+    let ctx = context();
+    unsafe {
+        let gen = secp256k1_veil_sys::Generator::generate(ctx, MESSAGE.as_ptr()).unwrap();
+        assert!(gen.serialize(ctx)[0] == 0x0a || gen.serialize(ctx)[0] == 0x0b);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "rangeproof")]
+#[test]
+fn rangeproof() {
+    let ctx = context();
+    unsafe {
+        let commit = secp256k1_veil_sys::PedersenCommitment::commit(
+            ctx,
+            SECRET.as_ptr(),
+            5,
+            std::ptr::null(),
+        )
+        .unwrap();
+        assert!(commit.serialize(ctx)[0] == 0x08 || commit.serialize(ctx)[0] == 0x09);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "mlsag")]
+#[test]
+fn mlsag() {
+    let ctx = context();
+    unsafe {
+        let public_key = public_key(ctx).serialize_compressed(ctx).unwrap();
+        let key_image =
+            secp256k1_veil_sys::Mlsag::get_keyimage(ctx, public_key.as_ptr(), SECRET.as_ptr())
+                .unwrap();
+        assert_ne!(key_image, public_key);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "whitelist")]
+#[test]
+fn whitelist() {
+    use secp256k1_veil_sys::WhitelistSignature;
+
+    let ctx = context();
+    unsafe {
+        let input = [0u8; 33];
+        let sig = WhitelistSignature::parse(ctx, input.as_ptr()).unwrap();
+        assert_eq!(sig.n_keys(), 0);
+
+        let mut output = [0xffu8; 33];
+        sig.serialize(ctx, output.as_mut_ptr());
+        assert_eq!(output, input);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "surjectionproof")]
+#[test]
+fn surjectionproof() {
+    use secp256k1_veil_sys::{FixedAssetTag, SurjectionProof};
+
+    let ctx = context();
+    let tags = [FixedAssetTag([3; 32]), FixedAssetTag([4; 32])];
+    unsafe {
+        let (proof, input_index) =
+            SurjectionProof::initialize(ctx, tags.as_ptr(), 2, 1, &tags[1], 100, MESSAGE.as_ptr())
+                .unwrap();
+        assert_eq!(input_index, 1);
+        assert_eq!(proof.n_total_inputs(ctx), 2);
+        (*ctx).destroy();
+    }
+}
+
+#[cfg(feature = "schnorr")]
+#[test]
+fn schnorr() {
+    use secp256k1_veil_sys::bindings::{self, schnorr::*};
+
+    let ctx = context();
+    let public_key = public_key(ctx);
+    unsafe {
+        let mut sig = [0u8; 64];
+        let signed = secp256k1_schnorr_sign(
+            ctx,
+            sig.as_mut_ptr(),
+            MESSAGE.as_ptr(),
+            SECRET.as_ptr(),
+            bindings::secp256k1_nonce_function_default,
+            std::ptr::null(),
+        );
+        assert_eq!(signed, 1);
+        assert_eq!(
+            secp256k1_schnorr_verify(ctx, sig.as_ptr(), MESSAGE.as_ptr(), &public_key),
+            1
+        );
+        (*ctx).destroy();
+    }
+}
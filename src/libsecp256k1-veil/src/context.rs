@@ -45,6 +45,12 @@ pub trait Context {
     fn as_mut_ptr(&mut self) -> *mut secp256k1_veil_sys::Context;
 }
 
+/// Contexts initialized for signing, needed to multiply by the generator.
+pub trait Signing: Context {}
+
+/// Contexts initialized for verification, needed to multiply arbitrary points.
+pub trait Verification: Context {}
+
 #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
 pub trait ContextRandom {
     fn randomize(&mut self);
@@ -102,6 +108,31 @@ macro_rules! impl_context {
 impl_context!(ContextNone, bindings::CONTEXT_NONE);
 impl_context!(ContextSign, bindings::CONTEXT_SIGN);
 impl_context!(ContextVerify, bindings::CONTEXT_VERIFY);
+impl_context!(ContextAll, bindings::CONTEXT_SIGN | bindings::CONTEXT_VERIFY);
+
+impl Signing for ContextSign {}
+impl Signing for ContextAll {}
+impl Verification for ContextVerify {}
+impl Verification for ContextAll {}
+
+/// Runs `f` with a context holding no tables, for trait impls that can not be given one.
+pub(crate) fn with_context_none<T>(f: impl FnOnce(&ContextNone) -> T) -> T {
+    let mut ctx = ContextNone::new();
+    let result = f(&ctx);
+    ctx.destroy();
+    result
+}
+
+#[cfg(feature = "global_context")]
+lazy_static! {
+    /// A context for every operation shared by the whole process, created and randomized on
+    /// first use.
+    pub static ref GLOBAL_CONTEXT: ContextAll = {
+        let mut ctx = ContextAll::new();
+        ctx.randomize();
+        ctx
+    };
+}
 
 #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
 impl ContextRandom for ContextVerify {
@@ -130,3 +161,53 @@ impl ContextRandom for ContextSign {
         unsafe { bindings::secp256k1_context_randomize(self.0, seed.as_mut_ptr()) };
     }
 }
+
+#[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+impl ContextRandom for ContextAll {
+    fn randomize(&mut self) {
+        let mut rng = OsRng;
+        self.randomize_with_rng(&mut rng);
+    }
+
+    #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+    fn randomize_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut seed: [c_uchar; 32] = rng.gen();
+        unsafe { bindings::secp256k1_context_randomize(self.0, seed.as_mut_ptr()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::{PrivateKey, PublicKey};
+    use crate::message::Message;
+    use crate::signature::Signature;
+
+    fn sign_verify(ctx: &(impl Signing + Verification)) {
+        let mut private_key = PrivateKey::new(&ContextNone::new());
+        private_key.add_assign(ctx, [1; 32]).unwrap();
+        let mut public_key = PublicKey::new(ctx, &private_key).unwrap();
+        public_key.add_assign(ctx, [2; 32]).unwrap();
+        private_key.add_assign(ctx, [2; 32]).unwrap();
+        let combined = public_key.combine(ctx, &public_key).unwrap();
+        assert_ne!(
+            combined.serialize_compressed(ctx).unwrap(),
+            public_key.serialize_compressed(ctx).unwrap()
+        );
+
+        let msg = Message::new(&[5; 32]);
+        let sig = Signature::sign(ctx, &msg, &private_key).unwrap();
+        assert!(sig.verify(ctx, &msg, &public_key).is_ok());
+    }
+
+    #[test]
+    fn context_all() {
+        sign_verify(&ContextAll::new());
+    }
+
+    #[cfg(feature = "global_context")]
+    #[test]
+    fn global_context() {
+        sign_verify(&*GLOBAL_CONTEXT);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use core::ptr;
+
+use crate::Result;
+use crate::context::{Context, ContextNone};
+use crate::keys::{PrivateKey, PublicKey};
+
+/// A 32 byte secret shared by the holders of two key pairs, `a * B == b * A`.
+///
+/// The secret is zeroed when dropped.
+pub struct SharedSecret([u8; 32]);
+
+impl SharedSecret {
+    /// Computes the SHA256 of the compressed shared point in constant time.
+    ///
+    /// ECDH does not use the context's precomputed tables so an empty context is made for the
+    /// call.
+    pub fn new(public_key: &PublicKey, private_key: &PrivateKey) -> Result<Self> {
+        let mut ctx = ContextNone::new();
+        let secret = unsafe {
+            secp256k1_veil_sys::ecdh(&*ctx.as_ptr(), &*public_key.as_ptr(), private_key.as_ptr())
+        };
+        ctx.destroy();
+        Ok(Self(secret?))
+    }
+
+    /// Hashes the shared point with `hash`, which is given its x and y coordinates.
+    ///
+    /// Like `new` the point is computed in constant time, only the hash is swapped out so a key
+    /// compatible with another implementation can be derived.
+    pub fn new_with_hash<F>(
+        public_key: &PublicKey,
+        private_key: &PrivateKey,
+        hash: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce(&[u8; 32], &[u8; 32]) -> [u8; 32],
+    {
+        let mut ctx = ContextNone::new();
+        let point = unsafe {
+            secp256k1_veil_sys::ecdh_point(
+                &*ctx.as_ptr(),
+                &*public_key.as_ptr(),
+                private_key.as_ptr(),
+            )
+        };
+        ctx.destroy();
+        let mut point = point?;
+        let mut x = [0u8; 32];
+        let mut y = [0u8; 32];
+        x.copy_from_slice(&point[..32]);
+        y.copy_from_slice(&point[32..]);
+        let secret = Self(hash(&x, &y));
+
+        unsafe {
+            ptr::write_volatile(&mut point, [0; 64]);
+            ptr::write_volatile(&mut x, [0; 32]);
+            ptr::write_volatile(&mut y, [0; 32]);
+        }
+        Ok(secret)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        unsafe { ptr::write_volatile(&mut self.0, [0; 32]) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextSign, ContextVerify};
+
+    #[test]
+    fn shared_secret() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let alice = PrivateKey::new(&ctx);
+        let bob = PrivateKey::new(&ctx);
+        let alice_public = PublicKey::new(&sign, &alice).unwrap();
+        let bob_public = PublicKey::new(&sign, &bob).unwrap();
+
+        let ours = SharedSecret::new(&bob_public, &alice).unwrap();
+        let theirs = SharedSecret::new(&alice_public, &bob).unwrap();
+        assert_eq!(ours.as_bytes(), theirs.as_bytes());
+
+        let other = SharedSecret::new(&alice_public, &alice).unwrap();
+        assert_ne!(ours.as_bytes(), other.as_bytes());
+    }
+
+    #[test]
+    fn custom_hash() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let verify = ContextVerify::new();
+        let alice = PrivateKey::new(&ctx);
+        let bob = PrivateKey::new(&ctx);
+        let alice_public = PublicKey::new(&sign, &alice).unwrap();
+        let bob_public = PublicKey::new(&sign, &bob).unwrap();
+
+        let x_only = |x: &[u8; 32], _: &[u8; 32]| *x;
+        let ours = SharedSecret::new_with_hash(&bob_public, &alice, x_only).unwrap();
+        let theirs = SharedSecret::new_with_hash(&alice_public, &bob, x_only).unwrap();
+        assert_eq!(ours.as_bytes(), theirs.as_bytes());
+
+        let mut point = bob_public;
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(unsafe { core::slice::from_raw_parts(alice.as_ptr(), 32) });
+        point.mul_assign(&verify, tweak).unwrap();
+        assert_eq!(
+            ours.as_bytes()[..],
+            point.serialize_compressed(&ctx).unwrap()[1..]
+        );
+    }
+}
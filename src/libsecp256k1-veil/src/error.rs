@@ -34,6 +34,10 @@ pub enum ErrorKind {
     InvalidPrivateKey,
     InvalidMessage,
     InvalidSignature,
+    InvalidRangeProof,
+    InvalidMatrix,
+    InvalidRecoveryId,
+    InvalidWhitelist,
 }
 
 #[derive(Debug)]
@@ -54,10 +58,14 @@ impl fmt::Display for Error {
         use ErrorKind::*;
         match self.0 {
             Secp256k1Binding(ref e) => e.fmt(f),
-            InvalidPublicKey => unimplemented!(),
-            InvalidPrivateKey => unimplemented!(),
+            InvalidPublicKey => write!(f, "public key is invalid or none were given"),
+            InvalidPrivateKey => write!(f, "private key is not 32 bytes in length"),
             InvalidMessage => write!(f, "message is empty or not 32 bytes in length"),
-            InvalidSignature => unimplemented!(),
+            InvalidSignature => write!(f, "signature is invalid or too few or too many were given"),
+            InvalidRangeProof => write!(f, "range proof is empty or too large"),
+            InvalidMatrix => write!(f, "matrix dimensions or number of keys do not match"),
+            InvalidRecoveryId => write!(f, "recovery id is not between 0 and 3"),
+            InvalidWhitelist => write!(f, "whitelist keys, index or signature length do not match"),
         }
     }
 }
@@ -72,6 +80,10 @@ impl error::Error for Error {
             InvalidPrivateKey => None,
             InvalidMessage => None,
             InvalidSignature => None,
+            InvalidRangeProof => None,
+            InvalidMatrix => None,
+            InvalidRecoveryId => None,
+            InvalidWhitelist => None,
         }
     }
 }
@@ -0,0 +1,135 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::Result;
+use crate::context::{Context, Signing};
+use crate::pedersen::BlindingFactor;
+
+/// A curve point standing in for H in commitments to an asset other than the default one.
+///
+/// The generator of an asset is derived from its tag, blinding it hides which asset an output
+/// holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Generator(secp256k1_veil_sys::Generator);
+
+impl Generator {
+    /// Derives the generator of the asset identified by `seed`.
+    pub fn new(ctx: &impl Signing, seed: &[u8; 32]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Generator::generate(ctx.as_ptr(), seed.as_ptr())?
+        }))
+    }
+
+    /// Derives the generator of the asset identified by `seed`, offset by `blind * G`.
+    pub fn new_blinded(
+        ctx: &impl Signing,
+        seed: &[u8; 32],
+        blind: &BlindingFactor,
+    ) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Generator::generate_blinded(
+                ctx.as_ptr(),
+                seed.as_ptr(),
+                blind.as_ptr(),
+            )?
+        }))
+    }
+
+    pub fn parse(ctx: &impl Context, input: &[u8; 33]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Generator::parse(ctx.as_ptr(), input.as_ptr())?
+        }))
+    }
+
+    pub fn serialize(&self, ctx: &impl Context) -> [u8; 33] {
+        unsafe { self.0.serialize(ctx.as_ptr()) }
+    }
+
+    pub fn as_ptr(&self) -> *const secp256k1_veil_sys::Generator {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextNone, ContextSign};
+
+    // The x coordinates of the generators for seeds 1 to 4 from the C library's generator
+    // tests.
+    const VECTORS: [[u8; 32]; 4] = [
+        [
+            0x80, 0x6c, 0xd8, 0xed, 0xd6, 0xc1, 0x53, 0xe3, 0x4a, 0xa9, 0xb9, 0xa0, 0x87, 0x55,
+            0xc4, 0xbe, 0x47, 0x18, 0xb1, 0xef, 0xb2, 0x6c, 0xb9, 0x3f, 0xfd, 0xd9, 0x9e, 0x1b,
+            0x21, 0xf2, 0xaf, 0x8e,
+        ],
+        [
+            0xd9, 0x1b, 0x15, 0xec, 0x47, 0xa8, 0x11, 0xf4, 0xaa, 0x18, 0x95, 0x61, 0xd1, 0x3f,
+            0x5c, 0x4d, 0x4e, 0x81, 0xf1, 0x0d, 0xc7, 0xdc, 0x55, 0x1f, 0x4f, 0xea, 0x9b, 0x84,
+            0x61, 0x03, 0x14, 0xc4,
+        ],
+        [
+            0x11, 0xc0, 0x0d, 0xe6, 0xf8, 0x85, 0x03, 0x5e, 0x76, 0x05, 0x14, 0x30, 0xa3, 0xc3,
+            0x8b, 0x2a, 0x5f, 0x86, 0xab, 0x8c, 0xf6, 0x6d, 0xae, 0x58, 0x04, 0xea, 0x73, 0x07,
+            0x34, 0x8b, 0x19, 0xbf,
+        ],
+        [
+            0x98, 0x6b, 0x99, 0xeb, 0x31, 0x30, 0xe7, 0xf0, 0xe7, 0x79, 0xf6, 0x74, 0xb8, 0x5c,
+            0xb5, 0x14, 0x46, 0xa6, 0x76, 0xbf, 0xb1, 0xdf, 0xb6, 0x03, 0x4c, 0x4b, 0xb6, 0x39,
+            0x7c, 0x40, 0x62, 0x10,
+        ],
+    ];
+
+    #[test]
+    fn generate_vectors() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let zero = BlindingFactor::new([0; 32]);
+
+        for (i, x) in VECTORS.iter().enumerate() {
+            let mut seed = [0u8; 32];
+            seed[31] = i as u8 + 1;
+
+            let gen = Generator::new(&sign, &seed).unwrap();
+            let bytes = gen.serialize(&ctx);
+            assert!(bytes[0] == 0x0a || bytes[0] == 0x0b);
+            assert_eq!(&bytes[1..], &x[..]);
+
+            assert_eq!(Generator::new_blinded(&sign, &seed, &zero).unwrap(), gen);
+            assert_eq!(Generator::parse(&ctx, &bytes).unwrap(), gen);
+        }
+    }
+
+    #[test]
+    fn blinded_generator() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let gen = Generator::new(&sign, &[7; 32]).unwrap();
+        let blinded =
+            Generator::new_blinded(&sign, &[7; 32], &BlindingFactor::new([1; 32])).unwrap();
+
+        assert_ne!(blinded, gen);
+        assert!(Generator::parse(&ctx, &[0; 33]).is_err());
+    }
+}
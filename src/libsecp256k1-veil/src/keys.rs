@@ -22,31 +22,35 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-#[cfg(any(feature = "alloc"))]
+#[cfg(any(feature = "alloc", test))]
 use alloc::boxed::Box;
-#[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+#[cfg(all(feature = "rand_os", any(feature = "std", feature = "alloc", test)))]
 use rand::{rngs::OsRng, Rng};
+use core::{fmt, ptr, str};
 use secp256k1_veil_sys::c_types::*;
-#[cfg(any(feature = "std", test))]
-use std::boxed::Box;
 
-use crate::context::{Context, ContextNone, ContextSign, ContextVerify};
+use crate::context::{with_context_none, Context, Signing, Verification};
+use crate::error::{Error, ErrorKind};
+use crate::hex;
+#[cfg(feature = "serde")]
+use crate::serde_util::{BytesVisitor, FromStrVisitor};
 use crate::traits::AsNative;
 use crate::Result;
 
-#[derive(Debug)]
-pub struct PublicKey(*mut secp256k1_veil_sys::PublicKey);
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct PublicKey(secp256k1_veil_sys::PublicKey);
 
 impl PublicKey {
-    pub fn new(ctx: &ContextSign, private_key: &PrivateKey) -> Result<Self> {
+    pub fn new(ctx: &impl Signing, private_key: &PrivateKey) -> Result<Self> {
         Ok(Self(unsafe {
             secp256k1_veil_sys::PublicKey::create(ctx.as_ptr(), private_key.data.as_ptr())?
         }))
     }
 
-    pub fn parse(ctx: &ContextNone, input: &[u8]) -> Result<Self> {
-        if input.len() != 33 || input.len() != 65 {
-            // TODO: error
+    pub fn parse(ctx: &impl Context, input: &[u8]) -> Result<Self> {
+        if input.len() != 33 && input.len() != 65 {
+            return Err(Error::new(ErrorKind::InvalidPublicKey));
         }
 
         Ok(Self(unsafe {
@@ -54,56 +58,52 @@ impl PublicKey {
         }))
     }
 
-    pub fn combine(&self, ctx: &ContextNone, other: &PublicKey) -> Result<Self> {
-        let ins: *const *const secp256k1_veil_sys::PublicKey = [
-            self.0 as *const secp256k1_veil_sys::PublicKey,
-            other.0 as *const secp256k1_veil_sys::PublicKey,
-        ]
-        .as_ptr();
+    pub fn combine(&self, ctx: &impl Context, other: &PublicKey) -> Result<Self> {
+        let ins = [self.as_ptr(), other.as_ptr()];
 
         Ok(Self(unsafe {
-            secp256k1_veil_sys::PublicKey::combine(ctx.as_ptr(), ins, 2 as size_t)?
+            secp256k1_veil_sys::PublicKey::combine(ctx.as_ptr(), ins.as_ptr(), 2 as size_t)?
         }))
     }
 
-    pub fn serialize_compressed(&self, ctx: &ContextNone) -> Result<[u8; 33]> {
+    pub fn serialize_compressed(&self, ctx: &impl Context) -> Result<[u8; 33]> {
         unsafe {
             let public_key = self.as_native_ref();
             Ok(public_key.serialize_compressed(ctx.as_ptr())?)
         }
     }
 
-    pub fn serialize_uncompressed(&self, ctx: &ContextNone) -> Result<[u8; 65]> {
+    pub fn serialize_uncompressed(&self, ctx: &impl Context) -> Result<[u8; 65]> {
         unsafe {
             let public_key = self.as_native_ref();
             Ok(public_key.serialize_uncompressed(ctx.as_ptr())?)
         }
     }
 
-    pub fn neg(&mut self, ctx: &ContextNone) -> Result<()> {
+    pub fn neg(&mut self, ctx: &impl Context) -> Result<()> {
         let public_key = unsafe { self.as_native_mut() };
         unsafe { public_key.negate(ctx.as_ptr())? };
         Ok(())
     }
 
-    pub fn add_assign(&mut self, ctx: &ContextVerify, other: [u8; 32]) -> Result<()> {
+    pub fn add_assign(&mut self, ctx: &impl Verification, other: [u8; 32]) -> Result<()> {
         let public_key = unsafe { self.as_native_mut() };
         unsafe { public_key.tweak_add(ctx.as_ptr(), other.as_ptr())? };
         Ok(())
     }
 
-    pub fn mul_assign(&mut self, ctx: &ContextVerify, other: [u8; 32]) -> Result<()> {
+    pub fn mul_assign(&mut self, ctx: &impl Verification, other: [u8; 32]) -> Result<()> {
         let public_key = unsafe { self.as_native_mut() };
         unsafe { public_key.tweak_mul(ctx.as_ptr(), other.as_ptr())? };
         Ok(())
     }
 
     pub fn as_ptr(&self) -> *const secp256k1_veil_sys::PublicKey {
-        self.0 as *const secp256k1_veil_sys::PublicKey
+        &self.0
     }
 
     pub fn as_mut_ptr(&mut self) -> *mut secp256k1_veil_sys::PublicKey {
-        self.0
+        &mut self.0
     }
 
     pub fn as_slice(&self) -> [u8; 64] {
@@ -114,24 +114,91 @@ impl PublicKey {
 
 impl AsNative<secp256k1_veil_sys::PublicKey> for PublicKey {
     unsafe fn as_native_ref(&self) -> &secp256k1_veil_sys::PublicKey {
-        &*self.0
+        &self.0
     }
 
     unsafe fn as_native_mut(&mut self) -> &mut secp256k1_veil_sys::PublicKey {
-        &mut *self.0
+        &mut self.0
     }
 }
 
-impl From<PrivateKey> for PublicKey {
-    fn from(private_key: PrivateKey) -> Self {
-        unimplemented!()
+impl From<secp256k1_veil_sys::PublicKey> for PublicKey {
+    fn from(public_key: secp256k1_veil_sys::PublicKey) -> Self {
+        Self(public_key)
+    }
+}
+
+// The library stores both coordinates fully reduced, so equal keys have equal bytes.
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 .0[..] == other.0 .0[..]
+    }
+}
+
+impl Eq for PublicKey {}
+
+/// Hex of the compressed serialization.
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let serialized = with_context_none(|ctx| self.serialize_compressed(ctx))
+            .map_err(|_| fmt::Error)?;
+        hex::write(f, &serialized)
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PublicKey({})", self)
+    }
+}
+
+/// Parses the hex of a compressed or uncompressed serialization.
+impl str::FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut buf = [0u8; 65];
+        let input = hex::decode(s, &mut buf).ok_or(Error::new(ErrorKind::InvalidPublicKey))?;
+        with_context_none(|ctx| Self::parse(ctx, input))
+    }
+}
+
+/// Hex in human readable formats, otherwise the compressed serialization.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PublicKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let serialized = with_context_none(|ctx| self.serialize_compressed(ctx))
+                .map_err(serde::ser::Error::custom)?;
+            serializer.serialize_bytes(&serialized)
+        }
+    }
+}
+
+/// Rejects points that are not on the curve.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FromStrVisitor::new("a hex encoded public key"))
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor::new("a serialized public key", |data: &[u8]| {
+                with_context_none(|ctx| Self::parse(ctx, data))
+            }))
+        }
     }
 }
 
 unsafe impl Send for PublicKey {}
 unsafe impl Sync for PublicKey {}
 
-#[derive(Debug)]
+/// A secret key, zeroed when dropped.
+///
+/// With the `mlock` feature the key is kept in locked memory so it is never written to swap.
+/// Locking is best effort, it fails silently when over the process's limit, and pages are not
+/// unlocked when the key is dropped.
 pub struct PrivateKey {
     #[cfg(any(feature = "std", feature = "alloc", test))]
     data: Box<[u8; 32]>,
@@ -141,33 +208,51 @@ pub struct PrivateKey {
 
 impl PrivateKey {
     #[cfg(all(feature = "rand_os", any(feature = "std", feature = "alloc", test)))]
-    pub fn new(ctx: &ContextNone) -> Self {
+    pub fn new(ctx: &impl Context) -> Self {
         let mut rng = OsRng;
         Self::new_with_rng(&mut rng, ctx)
     }
 
     #[cfg(all(feature = "rand_os", any(feature = "std", feature = "alloc", test)))]
-    pub fn new_with_rng<R: Rng + ?Sized>(rng: &mut R, ctx: &ContextNone) -> Self {
-        let mut secret_key: Box<[c_uchar; 32]> = Box::new(rng.gen());
+    pub fn new_with_rng<R: Rng + ?Sized>(rng: &mut R, ctx: &impl Context) -> Self {
+        let mut key = Self::zeroed();
+        rng.fill_bytes(&mut key.data[..]);
 
         unsafe {
-            while !secp256k1_veil_sys::PrivateKey::verify(ctx.as_ptr(), secret_key.as_ptr()).is_ok()
-            {
-                *secret_key = rng.gen();
+            while secp256k1_veil_sys::PrivateKey::verify(ctx.as_ptr(), key.as_ptr()).is_err() {
+                rng.fill_bytes(&mut key.data[..]);
             }
         }
 
-        Self { data: secret_key }
+        key
+    }
+
+    /// Reads a key from 32 big endian bytes, which must be below the curve order and not zero.
+    pub fn from_slice(ctx: &impl Context, data: &[u8]) -> Result<Self> {
+        if data.len() != 32 {
+            return Err(Error::new(ErrorKind::InvalidPrivateKey));
+        }
+
+        let mut key = Self::zeroed();
+        key.data.copy_from_slice(data);
+        unsafe { secp256k1_veil_sys::PrivateKey::verify(ctx.as_ptr(), key.as_ptr())? };
+        Ok(key)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&self.data[..]);
+        bytes
     }
 
-    pub fn neg(&mut self, ctx: ContextNone) -> Result<()> {
+    pub fn neg(&mut self, ctx: &impl Context) -> Result<()> {
         unsafe {
             secp256k1_veil_sys::PrivateKey::negate(ctx.as_ptr(), self.data.as_mut_ptr())?;
             Ok(())
         }
     }
 
-    pub fn add_assign(&mut self, ctx: ContextVerify, other: [u8; 32]) -> Result<()> {
+    pub fn add_assign(&mut self, ctx: &impl Context, other: [u8; 32]) -> Result<()> {
         unsafe {
             secp256k1_veil_sys::PrivateKey::tweak_add(
                 ctx.as_ptr(),
@@ -178,7 +263,7 @@ impl PrivateKey {
         }
     }
 
-    pub fn mul_assign(&mut self, ctx: ContextVerify, other: [u8; 32]) -> Result<()> {
+    pub fn mul_assign(&mut self, ctx: &impl Context, other: [u8; 32]) -> Result<()> {
         unsafe {
             secp256k1_veil_sys::PrivateKey::tweak_mul(
                 ctx.as_ptr(),
@@ -197,15 +282,103 @@ impl PrivateKey {
         self.data.as_mut_ptr()
     }
 
-    #[cfg(all(any(feature = "std", feature = "alloc", test)))]
+    /// Overwrites the key with zeros.
     pub fn drop_key(&mut self) {
-        *self.data = [0; 32];
+        for byte in self.data.iter_mut() {
+            unsafe { ptr::write_volatile(byte, 0) };
+        }
+    }
+
+    fn zeroed() -> Self {
+        #[cfg(any(feature = "std", feature = "alloc", test))]
+        let key = Self {
+            data: Box::new([0; 32]),
+        };
+        #[cfg(all(not(feature = "std"), not(feature = "alloc"), not(test)))]
+        let key = Self { data: [0; 32] };
+
+        #[cfg(all(feature = "mlock", unix))]
+        mlock::lock(&key.data[..]);
+        key
+    }
+}
+
+impl PartialEq for PrivateKey {
+    /// Compares in constant time, the running time does not depend on where the keys differ.
+    fn eq(&self, other: &Self) -> bool {
+        let diff = self
+            .data
+            .iter()
+            .zip(other.data.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        unsafe { ptr::read_volatile(&diff) == 0 }
+    }
+}
+
+impl Eq for PrivateKey {}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PrivateKey(..)")
     }
 }
 
-#[cfg(all(any(feature = "std", feature = "alloc", test)))]
 impl Drop for PrivateKey {
     fn drop(&mut self) {
         self.drop_key();
     }
 }
+
+#[cfg(all(feature = "mlock", unix))]
+mod mlock {
+    use libc::{c_void, mlock};
+
+    // Locking works on whole pages and keys share pages, so they are never unlocked: unlocking
+    // the page of a dropped key would unlock every other key on it. A page stays locked until
+    // the process exits, which bounds the locked memory by the heap pages that ever held a key.
+    pub fn lock(data: &[u8]) {
+        unsafe { mlock(data.as_ptr() as *const c_void, data.len()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ContextNone;
+    use std::format;
+
+    // The order of the curve, one above the largest private key.
+    const ORDER: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36,
+        0x41, 0x41,
+    ];
+
+    #[test]
+    fn private_key_bytes() {
+        let ctx = ContextNone::new();
+        let key = PrivateKey::new(&ctx);
+        let parsed = PrivateKey::from_slice(&ctx, &key.to_bytes()).unwrap();
+        assert_eq!(parsed.to_bytes(), key.to_bytes());
+        assert_eq!(parsed, key);
+        assert_ne!(PrivateKey::new(&ctx), key);
+
+        let mut below_order = ORDER;
+        below_order[31] -= 1;
+        assert!(PrivateKey::from_slice(&ctx, &below_order).is_ok());
+        assert!(PrivateKey::from_slice(&ctx, &ORDER).is_err());
+        assert!(PrivateKey::from_slice(&ctx, &[0; 32]).is_err());
+        assert!(PrivateKey::from_slice(&ctx, &[1; 31]).is_err());
+        assert!(PrivateKey::from_slice(&ctx, &[1; 33]).is_err());
+    }
+
+    #[test]
+    fn private_key_hidden() {
+        let ctx = ContextNone::new();
+        let mut key = PrivateKey::from_slice(&ctx, &[0xab; 32]).unwrap();
+        assert_eq!(format!("{:?}", key), "PrivateKey(..)");
+
+        key.drop_key();
+        assert_eq!(key.to_bytes(), [0; 32]);
+    }
+}
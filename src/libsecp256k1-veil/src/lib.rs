@@ -24,21 +24,60 @@
 
 #![no_std]
 
-#[cfg(all(feature = "alloc", not(feature = "std")))]
+#[cfg(any(feature = "alloc", test))]
 extern crate alloc;
 #[cfg(any(feature = "std", test))]
 #[macro_use]
 extern crate std;
 #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
 extern crate rand;
+#[cfg(feature = "global_context")]
+#[macro_use]
+extern crate lazy_static;
+#[cfg(all(feature = "mlock", unix))]
+extern crate libc;
 extern crate secp256k1_veil_sys;
 
 mod context;
+mod ecdh;
 mod error;
+mod generator;
+pub mod hex;
 mod keys;
 mod message;
 mod mlsag;
+mod pedersen;
+mod rangeproof;
+mod recovery;
+mod schnorr;
+#[cfg(feature = "serde")]
+pub mod serde_util;
 mod signature;
+mod stealth;
+mod surjectionproof;
 mod traits;
+mod whitelist;
+
+#[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+pub use context::ContextRandom;
+#[cfg(feature = "global_context")]
+pub use context::GLOBAL_CONTEXT;
+pub use context::{
+    Context, ContextAll, ContextNone, ContextSign, ContextVerify, Signing, Verification,
+};
+pub use ecdh::SharedSecret;
+pub use error::{Error, ErrorKind};
+pub use generator::Generator;
+pub use keys::{PrivateKey, PublicKey};
+pub use message::Message;
+pub use mlsag::{key_image, Mlsag, PublicKeyMatrix};
+pub use pedersen::{BlindingFactor, PedersenCommitment};
+pub use rangeproof::{ProofInfo, ProofParams, RangeProof, Rewind, ValueRange};
+pub use recovery::RecoverableSignature;
+pub use schnorr::{PartialSignature, PublicNonce, SchnorrSignature, SecretNonce};
+pub use signature::{SerializedSignature, Signature};
+pub use stealth::{StealthAddress, StealthScanKey};
+pub use surjectionproof::SurjectionProof;
+pub use whitelist::WhitelistSignature;
 
 pub type Result<T, E = error::Error> = core::result::Result<T, E>;
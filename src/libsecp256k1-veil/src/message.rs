@@ -23,16 +23,20 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 use crate::error::{Error, ErrorKind};
+use crate::hex;
+#[cfg(feature = "serde")]
+use crate::serde_util::{BytesVisitor, FromStrVisitor};
 use crate::Result;
-use core::convert;
+use core::{convert, fmt, str};
 use secp256k1_veil_sys::c_types::c_uchar;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Message([u8; 32]);
 
 impl Message {
     // Maybe should have it by size.. and &[u8] input... ?
     pub fn new(bytes: &[u8; 32]) -> Self {
-        Self::from(bytes)
+        Self(*bytes)
     }
 
     pub fn as_ptr(&self) -> *const c_uchar {
@@ -73,3 +77,49 @@ impl convert::TryFrom<&[u8]> for Message {
         Self::from_slice(slice)
     }
 }
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Message({})", self)
+    }
+}
+
+impl str::FromStr for Message {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut buf = [0u8; 32];
+        let input = hex::decode(s, &mut buf).ok_or(Error::new(ErrorKind::InvalidMessage))?;
+        Self::from_slice(input)
+    }
+}
+
+/// Hex in human readable formats, otherwise the 32 bytes.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Message {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+/// Rejects anything `from_slice` does.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Message {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FromStrVisitor::new("a hex encoded message hash"))
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor::new("a message hash", Self::from_slice))
+        }
+    }
+}
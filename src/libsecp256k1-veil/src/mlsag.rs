@@ -22,30 +22,334 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
+use secp256k1_veil_sys::c_types::*;
+
+use crate::Result;
+use crate::context::{Signing, Verification};
+use crate::error::{Error, ErrorKind};
 use crate::keys::PrivateKey;
-use core::slice;
+use crate::pedersen::{BlindingFactor, PedersenCommitment};
 
-// pub struct Member {
-//     private_key: Option<PrivateKey>,
-// }
+/// Largest ring size.
+pub const MAX_COLS: usize = 32;
 
-// pub struct Mlsag {
-//     members: [Member; 16],
-//     members_len: usize,
-// }
+/// Largest number of rows, one per input plus the commitment row.
+pub const MAX_ROWS: usize = 33;
 
-// impl Mlsag {
-//     pub fn add_member(&mut self, member: Member) {
-//         self.members[self.members_len] = member;
-//         self.members_len += 1;
-//     }
+/// Public keys of a ring signature, a column per ring member and a row per input, plus a last
+/// row for the commitments.
+pub struct PublicKeyMatrix {
+    keys: [u8; MAX_COLS * MAX_ROWS * 33],
+    cols: usize,
+    rows: usize,
+}
 
-//     pub fn sign(key_image: &[u8], pub_keys: &[u8]) {}
+impl PublicKeyMatrix {
+    /// An empty matrix for a ring of `cols` members spending `inputs` inputs each.
+    pub fn new(cols: usize, inputs: usize) -> Result<Self> {
+        if cols == 0 || cols > MAX_COLS || inputs == 0 || inputs >= MAX_ROWS {
+            return Err(Error::new(ErrorKind::InvalidMatrix));
+        }
 
-//     pub fn verify() {}
-// }
+        Ok(Self {
+            keys: [0; MAX_COLS * MAX_ROWS * 33],
+            cols,
+            rows: inputs + 1,
+        })
+    }
 
-// G - curve basepoint
-// x - signers key
-//
-// fn sign(public_keys: &[PublicKey], secret_index: &u8, )
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Sets the compressed public key of member `col` for input `row`.
+    pub fn set(&mut self, col: usize, row: usize, public_key: &[u8; 33]) {
+        assert!(col < self.cols && row + 1 < self.rows);
+        let offset = (col + self.cols * row) * 33;
+        self.keys[offset..offset + 33].copy_from_slice(public_key);
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> &[u8] {
+        assert!(col < self.cols && row < self.rows);
+        let offset = (col + self.cols * row) * 33;
+        &self.keys[offset..offset + 33]
+    }
+
+    /// Fills the commitment row. For every column it holds the sum of the column's input
+    /// commitments minus the sum of the output commitments, which is a public key only when the
+    /// column holds the real inputs.
+    ///
+    /// `input_commits` holds a commitment for each key, in the same order as the keys, and
+    /// `output_commits` the commitments of the outputs including the fee.
+    ///
+    /// The secret key for the commitment row is the sum of the input blinding factors minus the
+    /// output ones, see `BlindingFactor::sum`.
+    pub fn prepare(
+        &mut self,
+        input_commits: &[&PedersenCommitment],
+        output_commits: &[&PedersenCommitment],
+    ) -> Result<()> {
+        if input_commits.len() != self.cols * (self.rows - 1) {
+            return Err(Error::new(ErrorKind::InvalidMatrix));
+        }
+
+        unsafe {
+            secp256k1_veil_sys::Mlsag::prepare(
+                self.keys.as_mut_ptr(),
+                core::ptr::null_mut(),
+                output_commits.len() as size_t,
+                0,
+                self.cols as size_t,
+                self.rows as size_t,
+                input_commits.as_ptr() as *mut *const c_uchar,
+                output_commits.as_ptr() as *mut *const c_uchar,
+                core::ptr::null_mut(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.cols * self.rows * 33
+    }
+}
+
+/// Returns the key image of a public key, which is the same however the key is signed with and
+/// so shows when an output is spent twice.
+///
+/// Convert it with `veil::KeyImage::from`.
+pub fn key_image(
+    ctx: &impl Verification,
+    public_key: &[u8; 33],
+    private_key: &PrivateKey,
+) -> Result<[u8; 33]> {
+    Ok(unsafe {
+        secp256k1_veil_sys::Mlsag::get_keyimage(
+            ctx.as_ptr(),
+            public_key.as_ptr(),
+            private_key.as_ptr(),
+        )?
+    })
+}
+
+/// A MLSAG ring signature over a prepared `PublicKeyMatrix`.
+pub struct Mlsag {
+    key_images: [u8; (MAX_ROWS - 1) * 33],
+    c: [u8; 32],
+    s: [u8; MAX_COLS * MAX_ROWS * 32],
+    cols: usize,
+    rows: usize,
+}
+
+impl Mlsag {
+    /// Signs `pre_image`, usually the transaction hash, as the member in column `index`.
+    ///
+    /// `private_keys` are the keys of the inputs in column `index` and `commitment_key` the
+    /// secret for the commitment row.
+    pub fn sign(
+        ctx: &(impl Signing + Verification),
+        matrix: &PublicKeyMatrix,
+        index: usize,
+        private_keys: &[&PrivateKey],
+        commitment_key: &BlindingFactor,
+        nonce: &[u8; 32],
+        pre_image: &[u8; 32],
+    ) -> Result<Self> {
+        if index >= matrix.cols || private_keys.len() + 1 != matrix.rows {
+            return Err(Error::new(ErrorKind::InvalidMatrix));
+        }
+
+        let mut secrets = [core::ptr::null(); MAX_ROWS];
+        for (secret, key) in secrets.iter_mut().zip(private_keys) {
+            *secret = key.as_ptr();
+        }
+        secrets[private_keys.len()] = commitment_key.as_ptr();
+
+        let mut mlsag = Self {
+            key_images: [0; (MAX_ROWS - 1) * 33],
+            c: [0; 32],
+            s: [0; MAX_COLS * MAX_ROWS * 32],
+            cols: matrix.cols,
+            rows: matrix.rows,
+        };
+        unsafe {
+            secp256k1_veil_sys::Mlsag::generate(
+                ctx.as_ptr(),
+                mlsag.key_images.as_mut_ptr(),
+                mlsag.c.as_mut_ptr(),
+                mlsag.s.as_mut_ptr(),
+                nonce.as_ptr(),
+                pre_image.as_ptr(),
+                matrix.cols as size_t,
+                matrix.rows as size_t,
+                index as size_t,
+                secrets.as_mut_ptr(),
+                matrix.keys.as_ptr(),
+            )?;
+        }
+        Ok(mlsag)
+    }
+
+    /// Reads a signature for a `cols` by `rows` matrix, with the key images and the signature
+    /// as veild stores them in a RingCT input: the challenge followed by the responses.
+    pub fn from_slices(
+        cols: usize,
+        rows: usize,
+        key_images: &[u8],
+        signature: &[u8],
+    ) -> Result<Self> {
+        if cols == 0
+            || cols > MAX_COLS
+            || !(2..=MAX_ROWS).contains(&rows)
+            || key_images.len() != (rows - 1) * 33
+            || signature.len() != 32 + cols * rows * 32
+        {
+            return Err(Error::new(ErrorKind::InvalidMatrix));
+        }
+
+        let mut mlsag = Self {
+            key_images: [0; (MAX_ROWS - 1) * 33],
+            c: [0; 32],
+            s: [0; MAX_COLS * MAX_ROWS * 32],
+            cols,
+            rows,
+        };
+        mlsag.key_images[..key_images.len()].copy_from_slice(key_images);
+        mlsag.c.copy_from_slice(&signature[..32]);
+        mlsag.s[..signature.len() - 32].copy_from_slice(&signature[32..]);
+        Ok(mlsag)
+    }
+
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        matrix: &PublicKeyMatrix,
+        pre_image: &[u8; 32],
+    ) -> Result<()> {
+        if matrix.cols != self.cols || matrix.rows != self.rows {
+            return Err(Error::new(ErrorKind::InvalidMatrix));
+        }
+
+        unsafe {
+            secp256k1_veil_sys::Mlsag::verify(
+                ctx.as_ptr(),
+                pre_image.as_ptr(),
+                self.cols as size_t,
+                self.rows as size_t,
+                matrix.keys[..matrix.len()].as_ptr(),
+                self.key_images.as_ptr(),
+                self.c.as_ptr(),
+                self.s.as_ptr(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The key images, one per input.
+    pub fn key_images(&self) -> impl Iterator<Item = [u8; 33]> + '_ {
+        self.key_images[..(self.rows - 1) * 33]
+            .chunks(33)
+            .map(|chunk| {
+                let mut key_image = [0u8; 33];
+                key_image.copy_from_slice(chunk);
+                key_image
+            })
+    }
+
+    /// The first challenge.
+    pub fn c(&self) -> &[u8; 32] {
+        &self.c
+    }
+
+    /// The responses, 32 bytes for every key in the matrix.
+    pub fn s(&self) -> &[u8] {
+        &self.s[..self.cols * self.rows * 32]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{Context, ContextAll, ContextNone, ContextSign, ContextVerify};
+    use crate::keys::PublicKey;
+    use rand::rngs::OsRng;
+
+    fn public_key(ctx: &impl Signing, private_key: &PrivateKey) -> [u8; 33] {
+        let none = ContextNone::new();
+        PublicKey::new(ctx, private_key)
+            .unwrap()
+            .serialize_compressed(&none)
+            .unwrap()
+    }
+
+    #[test]
+    fn sign_verify() {
+        let ctx = ContextAll::new();
+        let sign = ContextSign::new();
+        let verify = ContextVerify::new();
+        let none = ContextNone::new();
+        let mut rng = OsRng;
+
+        let (cols, index) = (4, 2);
+        let mut matrix = PublicKeyMatrix::new(cols, 1).unwrap();
+        let mut commits =
+            [PedersenCommitment::new(&sign, &BlindingFactor::new([1; 32]), 1).unwrap(); 4];
+        let private_key = PrivateKey::new_with_rng(&mut rng, &none);
+        let in_blind = BlindingFactor::random(&none);
+        for (col, commit) in commits.iter_mut().enumerate() {
+            if col == index {
+                matrix.set(col, 0, &public_key(&sign, &private_key));
+                *commit = PedersenCommitment::new(&sign, &in_blind, 1000).unwrap();
+            } else {
+                let decoy = PrivateKey::new_with_rng(&mut rng, &none);
+                matrix.set(col, 0, &public_key(&sign, &decoy));
+                let blind = BlindingFactor::random(&none);
+                *commit = PedersenCommitment::new(&sign, &blind, 500 + col as u64).unwrap();
+            }
+        }
+
+        let out_blind = BlindingFactor::random(&none);
+        let output = PedersenCommitment::new(&sign, &out_blind, 900).unwrap();
+        let fee = PedersenCommitment::new(&sign, &BlindingFactor::new([0; 32]), 100).unwrap();
+        let input_commits: [&PedersenCommitment; 4] =
+            [&commits[0], &commits[1], &commits[2], &commits[3]];
+        matrix.prepare(&input_commits, &[&output, &fee]).unwrap();
+        let commitment_key = BlindingFactor::sum(&none, &[&in_blind, &out_blind], 1).unwrap();
+
+        let pre_image = [9u8; 32];
+        let mlsag = Mlsag::sign(
+            &ctx,
+            &matrix,
+            index,
+            &[&private_key],
+            &commitment_key,
+            &[5; 32],
+            &pre_image,
+        )
+        .unwrap();
+        assert!(mlsag.verify(&verify, &matrix, &pre_image).is_ok());
+        assert!(mlsag.verify(&verify, &matrix, &[8u8; 32]).is_err());
+
+        let image = mlsag.key_images().next().unwrap();
+        let expected = key_image(&verify, &public_key(&sign, &private_key), &private_key).unwrap();
+        assert_eq!(image, expected);
+
+        let mut signature = [0u8; 32 + 4 * 2 * 32];
+        signature[..32].copy_from_slice(mlsag.c());
+        signature[32..].copy_from_slice(mlsag.s());
+        let copy = Mlsag::from_slices(cols, 2, &image, &signature).unwrap();
+        assert!(copy.verify(&verify, &matrix, &pre_image).is_ok());
+    }
+
+    #[test]
+    fn invalid_matrix() {
+        assert!(PublicKeyMatrix::new(0, 1).is_err());
+        assert!(PublicKeyMatrix::new(MAX_COLS + 1, 1).is_err());
+        assert!(PublicKeyMatrix::new(3, MAX_ROWS).is_err());
+        assert!(Mlsag::from_slices(3, 2, &[0; 33], &[0; 10]).is_err());
+    }
+}
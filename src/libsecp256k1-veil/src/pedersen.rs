@@ -0,0 +1,233 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+use core::{fmt, ptr};
+#[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+use rand::{Rng, rngs::OsRng};
+use secp256k1_veil_sys::c_types::*;
+
+use crate::Result;
+use crate::context::{Context, Signing};
+use crate::generator::Generator;
+
+/// A 32 byte blinding factor, hiding the value of a commitment, zeroed when dropped.
+#[derive(Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct BlindingFactor([u8; 32]);
+
+impl BlindingFactor {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+    pub fn random(ctx: &impl Context) -> Self {
+        let mut rng = OsRng;
+        Self::random_with_rng(&mut rng, ctx)
+    }
+
+    #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+    pub fn random_with_rng<R: Rng + ?Sized>(rng: &mut R, ctx: &impl Context) -> Self {
+        let mut blind: [c_uchar; 32] = rng.gen();
+
+        unsafe {
+            while secp256k1_veil_sys::PrivateKey::verify(ctx.as_ptr(), blind.as_ptr()).is_err() {
+                blind = rng.gen();
+            }
+        }
+
+        Self(blind)
+    }
+
+    /// Sums the blinding factors, adding the first `positive` and subtracting the rest.
+    ///
+    /// Used to pick the last blinding factor of a transaction so its commitments balance.
+    pub fn sum(ctx: &impl Context, blinds: &[&BlindingFactor], positive: usize) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::blind_sum(
+                ctx.as_ptr(),
+                blinds.as_ptr() as *const *const c_uchar,
+                blinds.len() as size_t,
+                positive as size_t,
+            )?
+        }))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+}
+
+impl fmt::Debug for BlindingFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BlindingFactor(..)")
+    }
+}
+
+impl Drop for BlindingFactor {
+    fn drop(&mut self) {
+        for byte in self.0.iter_mut() {
+            unsafe { ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+/// A commitment to a value, `blind * G + value * H`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct PedersenCommitment(secp256k1_veil_sys::PedersenCommitment);
+
+impl PedersenCommitment {
+    /// Commits to `value` of the default asset, `blind * G + value * H`.
+    pub fn new(ctx: &impl Signing, blind: &BlindingFactor, value: u64) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::PedersenCommitment::commit(
+                ctx.as_ptr(),
+                blind.as_ptr(),
+                value,
+                core::ptr::null(),
+            )?
+        }))
+    }
+
+    /// Commits to `value` of the asset with generator `gen`, `blind * G + value * gen`.
+    pub fn commit(
+        ctx: &impl Signing,
+        value: u64,
+        blind: &BlindingFactor,
+        gen: &Generator,
+    ) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::PedersenCommitment::commit(
+                ctx.as_ptr(),
+                blind.as_ptr(),
+                value,
+                gen.as_ptr(),
+            )?
+        }))
+    }
+
+    pub fn parse(ctx: &impl Context, input: &[u8; 33]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::PedersenCommitment::parse(ctx.as_ptr(), input.as_ptr())?
+        }))
+    }
+
+    pub fn serialize(&self, ctx: &impl Context) -> [u8; 33] {
+        unsafe { self.0.serialize(ctx.as_ptr()) }
+    }
+
+    /// Adds the commitments together.
+    pub fn sum(ctx: &impl Context, commits: &[&PedersenCommitment]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::PedersenCommitment::sum(
+                ctx.as_ptr(),
+                commits.as_ptr() as *const *const secp256k1_veil_sys::PedersenCommitment,
+                commits.len() as size_t,
+            )?
+        }))
+    }
+
+    /// Checks the positive commitments sum up to the negative ones, that is that the inputs
+    /// of a transaction balance its outputs.
+    pub fn verify_tally(
+        ctx: &impl Context,
+        positive: &[&PedersenCommitment],
+        negative: &[&PedersenCommitment],
+    ) -> Result<()> {
+        unsafe {
+            secp256k1_veil_sys::PedersenCommitment::verify_tally(
+                ctx.as_ptr(),
+                positive.as_ptr() as *const *const secp256k1_veil_sys::PedersenCommitment,
+                positive.len() as size_t,
+                negative.as_ptr() as *const *const secp256k1_veil_sys::PedersenCommitment,
+                negative.len() as size_t,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn as_ptr(&self) -> *const secp256k1_veil_sys::PedersenCommitment {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextNone, ContextSign};
+
+    #[test]
+    fn commitments_balance() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let in_blind = BlindingFactor::random(&ctx);
+        let out_blind = BlindingFactor::random(&ctx);
+        let change_blind = BlindingFactor::sum(&ctx, &[&in_blind, &out_blind], 1).unwrap();
+
+        let input = PedersenCommitment::new(&sign, &in_blind, 100).unwrap();
+        let output = PedersenCommitment::new(&sign, &out_blind, 70).unwrap();
+        let change = PedersenCommitment::new(&sign, &change_blind, 30).unwrap();
+        assert!(PedersenCommitment::verify_tally(&ctx, &[&input], &[&output, &change]).is_ok());
+
+        let wrong = PedersenCommitment::new(&sign, &change_blind, 31).unwrap();
+        assert!(PedersenCommitment::verify_tally(&ctx, &[&input], &[&output, &wrong]).is_err());
+
+        let sum = PedersenCommitment::sum(&ctx, &[&output, &change]).unwrap();
+        assert_eq!(sum, input);
+    }
+
+    #[test]
+    fn serialize() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let commit = PedersenCommitment::new(&sign, &BlindingFactor::new([1; 32]), 5).unwrap();
+
+        let bytes = commit.serialize(&ctx);
+        assert!(bytes[0] == 0x08 || bytes[0] == 0x09);
+        assert_eq!(PedersenCommitment::parse(&ctx, &bytes).unwrap(), commit);
+        assert!(PedersenCommitment::parse(&ctx, &[0; 33]).is_err());
+    }
+
+    #[test]
+    fn asset_commitments() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let blind = BlindingFactor::random(&ctx);
+        let gen = Generator::new(&sign, &[3; 32]).unwrap();
+
+        let commit = PedersenCommitment::commit(&sign, 5, &blind, &gen).unwrap();
+        assert_ne!(commit, PedersenCommitment::new(&sign, &blind, 5).unwrap());
+
+        let half_blind = BlindingFactor::random(&ctx);
+        let rest_blind = BlindingFactor::sum(&ctx, &[&blind, &half_blind], 1).unwrap();
+        let half = PedersenCommitment::commit(&sign, 2, &half_blind, &gen).unwrap();
+        let rest = PedersenCommitment::commit(&sign, 3, &rest_blind, &gen).unwrap();
+        assert!(PedersenCommitment::verify_tally(&ctx, &[&commit], &[&half, &rest]).is_ok());
+    }
+}
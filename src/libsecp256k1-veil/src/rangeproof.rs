@@ -0,0 +1,277 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+use secp256k1_veil_sys::c_types::*;
+
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::error::{Error, ErrorKind};
+use crate::pedersen::{BlindingFactor, PedersenCommitment};
+
+/// Largest range proof the library creates or accepts.
+pub const MAX_PROOF_SIZE: usize = 5134;
+
+/// Largest message that can be embedded in a range proof.
+pub const MAX_MESSAGE_SIZE: usize = 4096;
+
+/// Settings for creating a range proof.
+pub struct ProofParams<'a> {
+    /// Smallest value the proof shows, values below it are not hidden.
+    pub min_value: u64,
+    /// Base 10 exponent, digits below it are public. `-1` makes the whole value public.
+    pub exp: i32,
+    /// Minimum number of bits of the value to hide.
+    pub min_bits: i32,
+    /// Data for the receiver, recovered when rewinding the proof.
+    pub message: &'a [u8],
+    /// Extra data the proof commits to, for instance the output script.
+    pub extra_commit: &'a [u8],
+}
+
+impl Default for ProofParams<'_> {
+    fn default() -> Self {
+        Self {
+            min_value: 0,
+            exp: 0,
+            min_bits: 32,
+            message: &[],
+            extra_commit: &[],
+        }
+    }
+}
+
+/// Range of values a proof shows its commitment can hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueRange {
+    pub min_value: u64,
+    pub max_value: u64,
+}
+
+/// Header of a range proof, readable without the commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofInfo {
+    pub exp: i32,
+    pub mantissa: i32,
+    pub range: ValueRange,
+}
+
+/// What the owner of the nonce recovers from a range proof.
+pub struct Rewind {
+    pub value: u64,
+    pub blind: BlindingFactor,
+    pub range: ValueRange,
+    message: [u8; MAX_MESSAGE_SIZE],
+    message_len: usize,
+}
+
+impl Rewind {
+    /// The embedded message, padded with zeros up to the space the proof had for it.
+    pub fn message(&self) -> &[u8] {
+        &self.message[..self.message_len]
+    }
+}
+
+/// A proof that a commitment holds a value within a range, as found in CT and RingCT outputs.
+#[derive(Clone)]
+pub struct RangeProof {
+    proof: [u8; MAX_PROOF_SIZE],
+    len: usize,
+}
+
+impl RangeProof {
+    pub fn from_slice(proof: &[u8]) -> Result<Self> {
+        if proof.is_empty() || proof.len() > MAX_PROOF_SIZE {
+            return Err(Error::new(ErrorKind::InvalidRangeProof));
+        }
+
+        let mut buf = [0u8; MAX_PROOF_SIZE];
+        buf[..proof.len()].copy_from_slice(proof);
+        Ok(Self {
+            proof: buf,
+            len: proof.len(),
+        })
+    }
+
+    /// Proves `commit` holds `value` blinded with `blind`. The `nonce` lets whoever knows it
+    /// rewind the proof.
+    pub fn sign(
+        ctx: &(impl Signing + Verification),
+        commit: &PedersenCommitment,
+        blind: &BlindingFactor,
+        nonce: &[u8; 32],
+        value: u64,
+        params: &ProofParams,
+    ) -> Result<Self> {
+        let mut proof = [0u8; MAX_PROOF_SIZE];
+        let mut len = MAX_PROOF_SIZE as size_t;
+        unsafe {
+            secp256k1_veil_sys::RangeProof::sign(
+                ctx.as_ptr(),
+                proof.as_mut_ptr(),
+                &mut len,
+                params.min_value,
+                commit.as_ptr(),
+                blind.as_ptr(),
+                nonce.as_ptr(),
+                params.exp,
+                params.min_bits,
+                value,
+                params.message.as_ptr(),
+                params.message.len() as size_t,
+                params.extra_commit.as_ptr(),
+                params.extra_commit.len() as size_t,
+                core::ptr::null(),
+            )?;
+        }
+        Ok(Self { proof, len })
+    }
+
+    /// Verifies the proof against its commitment, returning the range of the value.
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        commit: &PedersenCommitment,
+        extra_commit: &[u8],
+    ) -> Result<ValueRange> {
+        let (min_value, max_value) = unsafe {
+            secp256k1_veil_sys::RangeProof::verify(
+                ctx.as_ptr(),
+                commit.as_ptr(),
+                self.proof.as_ptr(),
+                self.len as size_t,
+                extra_commit.as_ptr(),
+                extra_commit.len() as size_t,
+                core::ptr::null(),
+            )?
+        };
+        Ok(ValueRange {
+            min_value,
+            max_value,
+        })
+    }
+
+    /// Recovers the value, blinding factor and message using the nonce the proof was signed
+    /// with.
+    pub fn rewind(
+        &self,
+        ctx: &(impl Signing + Verification),
+        nonce: &[u8; 32],
+        commit: &PedersenCommitment,
+        extra_commit: &[u8],
+    ) -> Result<Rewind> {
+        let mut message = [0u8; MAX_MESSAGE_SIZE];
+        let mut message_len = MAX_MESSAGE_SIZE as size_t;
+        let (value, blind, min_value, max_value) = unsafe {
+            secp256k1_veil_sys::RangeProof::rewind(
+                ctx.as_ptr(),
+                message.as_mut_ptr(),
+                &mut message_len,
+                nonce.as_ptr(),
+                commit.as_ptr(),
+                self.proof.as_ptr(),
+                self.len as size_t,
+                extra_commit.as_ptr(),
+                extra_commit.len() as size_t,
+                core::ptr::null(),
+            )?
+        };
+        Ok(Rewind {
+            value,
+            blind: BlindingFactor::new(blind),
+            range: ValueRange {
+                min_value,
+                max_value,
+            },
+            message,
+            message_len: message_len as usize,
+        })
+    }
+
+    pub fn info(&self, ctx: &impl Context) -> Result<ProofInfo> {
+        let (exp, mantissa, min_value, max_value) = unsafe {
+            secp256k1_veil_sys::RangeProof::info(ctx.as_ptr(), self.proof.as_ptr(), self.len)?
+        };
+        Ok(ProofInfo {
+            exp,
+            mantissa,
+            range: ValueRange {
+                min_value,
+                max_value,
+            },
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.proof[..self.len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextAll, ContextNone, ContextSign, ContextVerify};
+
+    #[test]
+    fn sign_verify_rewind() {
+        let ctx = ContextAll::new();
+        let verify = ContextVerify::new();
+        let none = ContextNone::new();
+        let blind = BlindingFactor::random(&none);
+        let commit = PedersenCommitment::new(&ContextSign::new(), &blind, 12_345).unwrap();
+        let nonce = [7u8; 32];
+        let params = ProofParams {
+            message: b"memo",
+            extra_commit: b"script",
+            ..ProofParams::default()
+        };
+
+        let proof = RangeProof::sign(&ctx, &commit, &blind, &nonce, 12_345, &params).unwrap();
+        let range = proof.verify(&verify, &commit, b"script").unwrap();
+        assert!(range.min_value <= 12_345 && 12_345 <= range.max_value);
+        assert!(proof.verify(&verify, &commit, b"other").is_err());
+
+        let info = proof.info(&none).unwrap();
+        assert_eq!(info.range, range);
+        assert_eq!(info.exp, 0);
+
+        let rewind = proof.rewind(&ctx, &nonce, &commit, b"script").unwrap();
+        assert_eq!(rewind.value, 12_345);
+        assert_eq!(rewind.blind, blind);
+        assert_eq!(&rewind.message()[..4], b"memo");
+        assert!(proof.rewind(&ctx, &[8u8; 32], &commit, b"script").is_err());
+
+        let copy = RangeProof::from_slice(proof.as_bytes()).unwrap();
+        assert!(copy.verify(&verify, &commit, b"script").is_ok());
+    }
+
+    #[test]
+    fn invalid_proof() {
+        let blind = BlindingFactor::new([3; 32]);
+        let commit = PedersenCommitment::new(&ContextSign::new(), &blind, 1).unwrap();
+
+        assert!(RangeProof::from_slice(&[]).is_err());
+        let proof = RangeProof::from_slice(&[0u8; 64]).unwrap();
+        assert!(proof.verify(&ContextVerify::new(), &commit, &[]).is_err());
+    }
+}
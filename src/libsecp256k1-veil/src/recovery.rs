@@ -0,0 +1,135 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::error::{Error, ErrorKind};
+use crate::keys::{PrivateKey, PublicKey};
+use crate::message::Message;
+use crate::signature::Signature;
+
+/// An ECDSA signature along with the id needed to recover the public key that made it.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct RecoverableSignature(secp256k1_veil_sys::RecoverableSignature);
+
+impl RecoverableSignature {
+    /// Signs the message hash with a deterministic RFC6979 nonce.
+    pub fn sign(ctx: &impl Signing, msg: &Message, private_key: &PrivateKey) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::RecoverableSignature::sign(
+                ctx.as_ptr(),
+                msg.as_ptr(),
+                private_key.as_ptr(),
+            )?
+        }))
+    }
+
+    /// Parses a compact signature, `recovery_id` must be between 0 and 3.
+    pub fn parse_compact(ctx: &impl Context, input: &[u8; 64], recovery_id: i32) -> Result<Self> {
+        if !(0..4).contains(&recovery_id) {
+            return Err(Error::new(ErrorKind::InvalidRecoveryId));
+        }
+
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::RecoverableSignature::parse_compact(
+                ctx.as_ptr(),
+                input.as_ptr(),
+                recovery_id,
+            )?
+        }))
+    }
+
+    /// Returns the recovery id and the compact signature.
+    pub fn serialize_compact(&self, ctx: &impl Context) -> (i32, [u8; 64]) {
+        let (output, recovery_id) = unsafe { self.0.serialize_compact(ctx.as_ptr()) };
+        (recovery_id, output)
+    }
+
+    /// Recovers the public key that signed `msg`.
+    pub fn recover(&self, ctx: &impl Verification, msg: &Message) -> Result<PublicKey> {
+        Ok(PublicKey::from(unsafe {
+            self.0.recover(ctx.as_ptr(), msg.as_ptr())?
+        }))
+    }
+
+    /// Converts to a plain signature, dropping the recovery id.
+    pub fn to_signature(&self, ctx: &impl Context) -> Signature {
+        Signature::new(unsafe { self.0.convert(ctx.as_ptr()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextNone, ContextSign, ContextVerify};
+
+    #[test]
+    fn sign_recover() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let verify = ContextVerify::new();
+        let private_key = PrivateKey::new(&ctx);
+        let public_key = PublicKey::new(&sign, &private_key).unwrap();
+        let msg = Message::new(&[3; 32]);
+
+        let sig = RecoverableSignature::sign(&sign, &msg, &private_key).unwrap();
+        let recovered = sig.recover(&verify, &msg).unwrap();
+        assert_eq!(
+            recovered.serialize_compressed(&ctx).unwrap(),
+            public_key.serialize_compressed(&ctx).unwrap()
+        );
+
+        let other = sig.recover(&verify, &Message::new(&[4; 32])).unwrap();
+        assert_ne!(
+            other.serialize_compressed(&ctx).unwrap(),
+            public_key.serialize_compressed(&ctx).unwrap()
+        );
+
+        let plain = sig.to_signature(&ctx);
+        assert!(plain.verify(&verify, &msg, &public_key).is_ok());
+        assert_eq!(
+            plain.serialize_compact(&ctx).unwrap(),
+            Signature::sign(&sign, &msg, &private_key)
+                .unwrap()
+                .serialize_compact(&ctx)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let private_key = PrivateKey::new(&ctx);
+        let msg = Message::new(&[3; 32]);
+
+        let sig = RecoverableSignature::sign(&sign, &msg, &private_key).unwrap();
+        let (recovery_id, compact) = sig.serialize_compact(&ctx);
+        let parsed = RecoverableSignature::parse_compact(&ctx, &compact, recovery_id).unwrap();
+        assert_eq!(parsed.serialize_compact(&ctx), (recovery_id, compact));
+
+        assert!(RecoverableSignature::parse_compact(&ctx, &compact, 4).is_err());
+        assert!(RecoverableSignature::parse_compact(&ctx, &[0xff; 64], 0).is_err());
+    }
+}
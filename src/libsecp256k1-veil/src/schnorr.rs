@@ -0,0 +1,313 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Schnorr signatures, including the multi-party flow where each signer contributes a partial
+//! signature.
+//!
+//! A multi-party signature is made in three rounds:
+//!
+//! 1. Every signer calls [`SecretNonce::new`] and shares the returned [`PublicNonce`].
+//! 2. Every signer sums the public nonces of the others with [`PublicNonce::combine`] and
+//!    consumes its [`SecretNonce`] with [`SecretNonce::partial_sign`].
+//! 3. Anyone combines the partial signatures with [`PartialSignature::combine`]. The result
+//!    verifies against the sum of the signers' public keys.
+//!
+//! Signing twice with the same nonce leaks the private key, so a [`SecretNonce`] is bound to the
+//! message it was made for, can not be cloned and is moved into the partial signature.
+
+use core::ptr;
+#[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+use rand::{Rng, rngs::OsRng};
+
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::keys::{PrivateKey, PublicKey};
+use crate::message::Message;
+
+/// A 64 byte Schnorr signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SchnorrSignature([u8; 64]);
+
+impl SchnorrSignature {
+    /// Signs the message hash with a deterministic RFC6979 nonce.
+    pub fn sign(ctx: &impl Signing, msg: &Message, private_key: &PrivateKey) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Schnorr::sign(
+                ctx.as_ptr(),
+                msg.as_ptr(),
+                private_key.as_ptr(),
+                ptr::null(),
+            )?
+        }))
+    }
+
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        msg: &Message,
+        public_key: &PublicKey,
+    ) -> Result<()> {
+        unsafe {
+            secp256k1_veil_sys::Schnorr::verify(
+                ctx.as_ptr(),
+                self.0.as_ptr(),
+                msg.as_ptr(),
+                public_key.as_ptr(),
+            )
+        }?;
+        Ok(())
+    }
+
+    /// Recovers the public key that signed `msg`.
+    pub fn recover(&self, ctx: &impl Verification, msg: &Message) -> Result<PublicKey> {
+        Ok(PublicKey::from(unsafe {
+            secp256k1_veil_sys::Schnorr::recover(ctx.as_ptr(), self.0.as_ptr(), msg.as_ptr())?
+        }))
+    }
+
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+/// The private half of a signer's nonce pair.
+///
+/// It is zeroed when dropped and is consumed by [`SecretNonce::partial_sign`], so it can sign
+/// exactly once.
+pub struct SecretNonce {
+    nonce: [u8; 32],
+    msg: Message,
+}
+
+impl SecretNonce {
+    /// Generates a nonce pair for signing `msg`, mixing random data into the nonce function.
+    #[cfg(all(feature = "rand_os", any(feature = "std", test)))]
+    pub fn new(
+        ctx: &impl Signing,
+        msg: &Message,
+        private_key: &PrivateKey,
+    ) -> Result<(Self, PublicNonce)> {
+        let mut rng = OsRng;
+        Self::new_with_data(ctx, msg, private_key, &rng.gen())
+    }
+
+    /// Generates a nonce pair for signing `msg` with `extra` as additional nonce entropy.
+    ///
+    /// The nonce is derived from the message, the private key and `extra`. Reusing `extra` for the
+    /// same message and key gives the same nonce, so it must be fresh for every signing session.
+    pub fn new_with_data(
+        ctx: &impl Signing,
+        msg: &Message,
+        private_key: &PrivateKey,
+        extra: &[u8; 32],
+    ) -> Result<(Self, PublicNonce)> {
+        let (public_nonce, nonce) = unsafe {
+            secp256k1_veil_sys::Schnorr::generate_nonce_pair(
+                ctx.as_ptr(),
+                msg.as_ptr(),
+                private_key.as_ptr(),
+                extra.as_ptr() as *const _,
+            )?
+        };
+
+        Ok((
+            Self { nonce, msg: *msg },
+            PublicNonce(PublicKey::from(public_nonce)),
+        ))
+    }
+
+    /// Produces this signer's partial signature, `others` being the sum of every other signer's
+    /// public nonce.
+    pub fn partial_sign(
+        self,
+        ctx: &impl Signing,
+        private_key: &PrivateKey,
+        others: &PublicNonce,
+    ) -> Result<PartialSignature> {
+        Ok(PartialSignature(unsafe {
+            secp256k1_veil_sys::Schnorr::partial_sign(
+                ctx.as_ptr(),
+                self.msg.as_ptr(),
+                private_key.as_ptr(),
+                others.0.as_ptr(),
+                self.nonce.as_ptr(),
+            )?
+        }))
+    }
+}
+
+impl Drop for SecretNonce {
+    fn drop(&mut self) {
+        unsafe { ptr::write_volatile(&mut self.nonce, [0; 32]) };
+    }
+}
+
+/// The public half of a signer's nonce pair, shared with the other signers.
+#[derive(Clone, Copy)]
+pub struct PublicNonce(PublicKey);
+
+impl PublicNonce {
+    /// Sums public nonces, usually those of every signer except ourselves.
+    pub fn combine(ctx: &impl Context, nonces: &[&PublicNonce]) -> Result<Self> {
+        let (first, rest) = nonces
+            .split_first()
+            .ok_or_else(|| crate::Error::new(crate::ErrorKind::InvalidPublicKey))?;
+        let mut sum = first.0;
+        for nonce in rest {
+            sum = sum.combine(ctx, &nonce.0)?;
+        }
+        Ok(Self(sum))
+    }
+
+    pub fn parse(ctx: &impl Context, input: &[u8]) -> Result<Self> {
+        Ok(Self(PublicKey::parse(ctx, input)?))
+    }
+
+    pub fn serialize(&self, ctx: &impl Context) -> Result<[u8; 33]> {
+        self.0.serialize_compressed(ctx)
+    }
+}
+
+/// One signer's share of a multi-party Schnorr signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialSignature([u8; 64]);
+
+impl PartialSignature {
+    /// Combines the partial signatures of every signer, they must all share the same total nonce.
+    pub fn combine(ctx: &impl Context, partials: &[&PartialSignature]) -> Result<SchnorrSignature> {
+        let mut ptrs = [ptr::null(); 32];
+        if partials.is_empty() || partials.len() > ptrs.len() {
+            return Err(crate::Error::new(crate::ErrorKind::InvalidSignature));
+        }
+        for (ptr, partial) in ptrs.iter_mut().zip(partials) {
+            *ptr = partial.0.as_ptr();
+        }
+
+        Ok(SchnorrSignature(unsafe {
+            secp256k1_veil_sys::Schnorr::partial_combine(
+                ctx.as_ptr(),
+                ptrs.as_ptr(),
+                partials.len(),
+            )?
+        }))
+    }
+
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextNone, ContextSign, ContextVerify};
+    use std::vec::Vec;
+
+    #[test]
+    fn sign_verify_recover() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let verify = ContextVerify::new();
+        let private_key = PrivateKey::new(&ctx);
+        let public_key = PublicKey::new(&sign, &private_key).unwrap();
+        let msg = Message::new(&[7; 32]);
+
+        let sig = SchnorrSignature::sign(&sign, &msg, &private_key).unwrap();
+        assert!(sig.verify(&verify, &msg, &public_key).is_ok());
+        assert!(
+            sig.verify(&verify, &Message::new(&[8; 32]), &public_key)
+                .is_err()
+        );
+        assert_eq!(
+            sig.recover(&verify, &msg)
+                .unwrap()
+                .serialize_compressed(&ctx)
+                .unwrap(),
+            public_key.serialize_compressed(&ctx).unwrap()
+        );
+
+        let mut bytes = *sig.as_bytes();
+        bytes[40] ^= 1;
+        let tampered = SchnorrSignature::from_bytes(bytes);
+        assert!(tampered.verify(&verify, &msg, &public_key).is_err());
+    }
+
+    #[test]
+    fn multi_party() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let verify = ContextVerify::new();
+        let msg = Message::new(&[9; 32]);
+        let keys = [
+            PrivateKey::new(&ctx),
+            PrivateKey::new(&ctx),
+            PrivateKey::new(&ctx),
+        ];
+        let public_keys: Vec<_> = keys
+            .iter()
+            .map(|key| PublicKey::new(&sign, key).unwrap())
+            .collect();
+
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .map(|key| SecretNonce::new(&sign, &msg, key).unwrap())
+            .unzip();
+
+        let partials: Vec<_> = secret_nonces
+            .into_iter()
+            .enumerate()
+            .map(|(i, secret_nonce)| {
+                let others: Vec<_> = public_nonces
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, nonce)| nonce)
+                    .collect();
+                let others = PublicNonce::combine(&ctx, &others).unwrap();
+                secret_nonce.partial_sign(&sign, &keys[i], &others).unwrap()
+            })
+            .collect();
+
+        let refs: Vec<_> = partials.iter().collect();
+        let sig = PartialSignature::combine(&ctx, &refs).unwrap();
+
+        let combined_key = public_keys[0]
+            .combine(&ctx, &public_keys[1])
+            .unwrap()
+            .combine(&ctx, &public_keys[2])
+            .unwrap();
+        assert!(sig.verify(&verify, &msg, &combined_key).is_ok());
+        assert!(sig.verify(&verify, &msg, &public_keys[0]).is_err());
+
+        let missing = PartialSignature::combine(&ctx, &refs[..2]).unwrap();
+        assert!(missing.verify(&verify, &msg, &combined_key).is_err());
+    }
+}
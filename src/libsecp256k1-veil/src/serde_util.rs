@@ -0,0 +1,154 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Visitors shared by the `serde` impls.
+//!
+//! Human readable formats carry the same hex as `Display`, binary formats the raw serialization.
+//! Both go through the usual parsing on the way back in, so invalid points and signatures are
+//! rejected rather than deserialized.
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::str::FromStr;
+use serde::de;
+
+use crate::Result;
+
+/// Deserializes a type from a string with its `FromStr` impl.
+pub struct FromStrVisitor<T> {
+    expecting: &'static str,
+    marker: PhantomData<T>,
+}
+
+impl<T> FromStrVisitor<T> {
+    pub fn new(expecting: &'static str) -> Self {
+        Self {
+            expecting,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for FromStrVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+/// Deserializes a type from bytes with `parse`.
+pub struct BytesVisitor<F> {
+    expecting: &'static str,
+    parse: F,
+}
+
+impl<F> BytesVisitor<F> {
+    pub fn new(expecting: &'static str, parse: F) -> Self {
+        Self { expecting, parse }
+    }
+}
+
+impl<'de, F, T> de::Visitor<'de> for BytesVisitor<F>
+where
+    F: FnOnce(&[u8]) -> Result<T>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        (self.parse)(v).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{Context, ContextNone, ContextSign};
+    use crate::{Message, PrivateKey, PublicKey, Signature};
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use std::fmt::Debug;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T, json: &str) {
+        assert_eq!(serde_json::to_string(value).unwrap(), json);
+        assert_eq!(&serde_json::from_str::<T>(json).unwrap(), value);
+
+        let binary = bincode::serialize(value).unwrap();
+        assert_eq!(&bincode::deserialize::<T>(&binary).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trips() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let private_key = PrivateKey::from_slice(&ctx, &[1; 32]).unwrap();
+        let public_key = PublicKey::new(&sign, &private_key).unwrap();
+        let msg = Message::new(&[9; 32]);
+        let sig = Signature::sign(&sign, &msg, &private_key).unwrap();
+
+        round_trip(&public_key, &format!("\"{}\"", public_key));
+        round_trip(&sig, &format!("\"{}\"", sig));
+        round_trip(&msg, &format!("\"{}\"", "09".repeat(32)));
+
+        // Binary formats carry the compressed point and the DER bytes.
+        let binary = bincode::serialize(&public_key).unwrap();
+        assert_eq!(
+            &binary[8..],
+            &public_key.serialize_compressed(&ctx).unwrap()[..]
+        );
+        let binary = bincode::serialize(&sig).unwrap();
+        assert_eq!(&binary[8..], &*sig.serialize_der(&ctx).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        // Not on the curve.
+        let off_curve = format!("\"02{}\"", "00".repeat(32));
+        assert!(serde_json::from_str::<PublicKey>(&off_curve).is_err());
+        assert!(serde_json::from_str::<PublicKey>("\"zz\"").is_err());
+        assert!(serde_json::from_str::<PublicKey>("1").is_err());
+
+        let mut binary = bincode::serialize(&[0x02u8; 33][..]).unwrap();
+        binary[8..].copy_from_slice(&[0; 33]);
+        assert!(bincode::deserialize::<PublicKey>(&binary).is_err());
+
+        assert!(serde_json::from_str::<Signature>("\"3006020101020101\"").is_ok());
+        assert!(serde_json::from_str::<Signature>("\"300602010102010100\"").is_err());
+
+        let zero = format!("\"{}\"", "00".repeat(32));
+        assert!(serde_json::from_str::<Message>(&zero).is_err());
+        assert!(serde_json::from_str::<Message>("\"0909\"").is_err());
+    }
+}
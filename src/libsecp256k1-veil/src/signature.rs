@@ -22,71 +22,313 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-use crate::context::{Context, ContextNone};
+use crate::context::{with_context_none, Context, Signing, Verification};
+use crate::error::{Error, ErrorKind};
+use crate::hex;
+use crate::keys::{PrivateKey, PublicKey};
+use crate::message::Message;
+#[cfg(feature = "serde")]
+use crate::serde_util::{BytesVisitor, FromStrVisitor};
 use crate::traits::AsNative;
 use crate::Result;
-use core::slice;
-use secp256k1_veil_sys::c_types::size_t;
+use core::{fmt, ops, str};
 
-#[derive(Debug)]
-pub struct Signature(*mut secp256k1_veil_sys::Signature);
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Signature(secp256k1_veil_sys::Signature);
 
 impl Signature {
-    pub fn new(sig: *mut secp256k1_veil_sys::Signature) -> Self {
+    pub fn new(sig: secp256k1_veil_sys::Signature) -> Self {
         Self(sig)
     }
 
-    pub fn parse_compact(ctx: &ContextNone, input: [u8; 64]) -> Result<Self> {
+    /// Signs the message hash with a deterministic RFC6979 nonce.
+    pub fn sign(ctx: &impl Signing, msg: &Message, private_key: &PrivateKey) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Ecdsa::sign(ctx.as_ptr(), msg.as_ptr(), private_key.as_ptr())?
+        }))
+    }
+
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        msg: &Message,
+        public_key: &PublicKey,
+    ) -> Result<()> {
+        unsafe {
+            secp256k1_veil_sys::Ecdsa::verify(
+                ctx.as_ptr(),
+                &self.0,
+                msg.as_ptr(),
+                public_key.as_ptr(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_compact(ctx: &impl Context, input: [u8; 64]) -> Result<Self> {
         Ok(Self(unsafe {
             secp256k1_veil_sys::Signature::parse_compact(ctx.as_ptr(), input.as_ptr())?
         }))
     }
 
-    pub fn parse_der(input: &[u8], ctx: &ContextNone) -> Result<Self> {
+    /// Parses a strictly DER encoded signature.
+    pub fn parse_der(ctx: &impl Context, input: &[u8]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::Signature::parse_der(ctx.as_ptr(), input.as_ptr(), input.len())?
+        }))
+    }
+
+    /// Parses a signature that may break the DER rules, with excess padding, wrong lengths or
+    /// trailing bytes. Only use it for signatures from systems that did not enforce DER.
+    pub fn parse_der_lax(ctx: &impl Context, input: &[u8]) -> Result<Self> {
         Ok(Self(unsafe {
-            secp256k1_veil_sys::Signature::parse_der(
-                ctx.as_ptr() as *const secp256k1_veil_sys::Context,
-                input.as_ptr(),
-                input.len() as size_t,
-            )?
+            secp256k1_veil_sys::Signature::parse_der_lax(ctx.as_ptr(), input.as_ptr(), input.len())?
         }))
     }
 
-    pub fn serialize_compact(&self, ctx: &ContextNone) -> Result<[u8; 64]> {
+    pub fn serialize_compact(&self, ctx: &impl Context) -> Result<[u8; 64]> {
         unsafe {
             let sig = self.as_native_ref();
             Ok(sig.serialize_compact(ctx.as_ptr())?)
         }
     }
 
-    // Might not work with no_std.
-    pub fn serialize_der(&self, ctx: &ContextNone) -> Result<&[u8]> {
-        unsafe {
-            let sig = self.as_native_ref();
-            let (output, output_len) = sig.serialize_der(ctx.as_ptr())?;
+    pub fn serialize_der(&self, ctx: &impl Context) -> Result<SerializedSignature> {
+        let (data, len) = unsafe { self.0.serialize_der(ctx.as_ptr())? };
+        Ok(SerializedSignature { data, len })
+    }
 
-            Ok(slice::from_raw_parts(output, output_len as usize))
-        }
+    /// Replaces S with its lower form, as `verify` rejects signatures with a high S.
+    pub fn normalize_s(&mut self, ctx: &impl Context) {
+        self.0 = unsafe { self.0.normalize(ctx.as_ptr()).0 };
+    }
+
+    pub fn is_low_s(&self, ctx: &impl Context) -> bool {
+        !unsafe { self.0.normalize(ctx.as_ptr()).1 }
     }
 
     pub fn as_ptr(&self) -> *const secp256k1_veil_sys::Signature {
-        self.0 as *const secp256k1_veil_sys::Signature
+        &self.0
     }
 
     pub fn as_mut_ptr(&mut self) -> *mut secp256k1_veil_sys::Signature {
-        self.0
+        &mut self.0
     }
 }
 
 impl AsNative<secp256k1_veil_sys::Signature> for Signature {
     unsafe fn as_native_ref(&self) -> &secp256k1_veil_sys::Signature {
-        &*self.0
+        &self.0
     }
 
     unsafe fn as_native_mut(&mut self) -> &mut secp256k1_veil_sys::Signature {
-        &mut *self.0
+        &mut self.0
     }
 }
 
-unsafe impl Send for Signature {}
-unsafe impl Sync for Signature {}
+// The library stores r and s fully reduced, so equal signatures have equal bytes.
+impl PartialEq for Signature {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.0[..] == other.0.0[..]
+    }
+}
+
+impl Eq for Signature {}
+
+/// Hex of the DER serialization.
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let serialized =
+            with_context_none(|ctx| self.serialize_der(ctx)).map_err(|_| fmt::Error)?;
+        hex::write(f, &serialized)
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Signature({})", self)
+    }
+}
+
+/// Parses the hex of a strictly DER encoded signature.
+impl str::FromStr for Signature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut buf = [0u8; 72];
+        let input = hex::decode(s, &mut buf).ok_or(Error::new(ErrorKind::InvalidSignature))?;
+        with_context_none(|ctx| Self::parse_der(ctx, input))
+    }
+}
+
+/// Hex in human readable formats, otherwise the DER bytes.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Signature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let serialized = with_context_none(|ctx| self.serialize_der(ctx))
+                .map_err(serde::ser::Error::custom)?;
+            serializer.serialize_bytes(&serialized)
+        }
+    }
+}
+
+/// Only accepts strict DER, like `FromStr`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Signature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FromStrVisitor::new("a hex encoded DER signature"))
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor::new("a DER signature", |data: &[u8]| {
+                with_context_none(|ctx| Self::parse_der(ctx, data))
+            }))
+        }
+    }
+}
+
+/// A DER encoded signature, at most 72 bytes.
+#[derive(Clone, Copy)]
+pub struct SerializedSignature {
+    data: [u8; 72],
+    len: usize,
+}
+
+impl ops::Deref for SerializedSignature {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for SerializedSignature {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl PartialEq for SerializedSignature {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for SerializedSignature {}
+
+impl fmt::Debug for SerializedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SerializedSignature(")?;
+        hex::write(f, self)?;
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextNone, ContextSign, ContextVerify};
+    use std::string::ToString;
+
+    // The order of the curve.
+    const ORDER: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36,
+        0x41, 0x41,
+    ];
+
+    fn signed() -> (Signature, Message, PublicKey) {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let private_key = PrivateKey::new(&ctx);
+        let public_key = PublicKey::new(&sign, &private_key).unwrap();
+        let msg = Message::new(&[9; 32]);
+        (
+            Signature::sign(&sign, &msg, &private_key).unwrap(),
+            msg,
+            public_key,
+        )
+    }
+
+    #[test]
+    fn der_round_trip() {
+        let ctx = ContextNone::new();
+        let (sig, _, _) = signed();
+
+        let der = sig.serialize_der(&ctx).unwrap();
+        assert_eq!(der[0], 0x30);
+        assert_eq!(der.len(), der[1] as usize + 2);
+        assert_eq!(Signature::parse_der(&ctx, &der).unwrap(), sig);
+        assert_eq!(Signature::parse_der_lax(&ctx, &der).unwrap(), sig);
+
+        // Pad r with an extra zero, which only lax parsing allows.
+        let mut padded = [0u8; 73];
+        padded[0] = 0x30;
+        padded[1] = der[1] + 1;
+        padded[2] = 0x02;
+        padded[3] = der[3] + 1;
+        padded[5..der.len() + 1].copy_from_slice(&der[4..]);
+        let padded = &padded[..der.len() + 1];
+        assert!(Signature::parse_der(&ctx, padded).is_err());
+        assert_eq!(Signature::parse_der_lax(&ctx, padded).unwrap(), sig);
+
+        assert!(Signature::parse_der(&ctx, &der[..der.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn low_s() {
+        let ctx = ContextNone::new();
+        let verify = ContextVerify::new();
+        let (sig, msg, public_key) = signed();
+        assert!(sig.is_low_s(&ctx));
+
+        // Replace s with n - s.
+        let compact = sig.serialize_compact(&ctx).unwrap();
+        let mut high = compact;
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = ORDER[i] as i16 - compact[32 + i] as i16 - borrow;
+            high[32 + i] = diff as u8;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+
+        let mut high = Signature::parse_compact(&ctx, high).unwrap();
+        assert!(!high.is_low_s(&ctx));
+        assert!(high.verify(&verify, &msg, &public_key).is_err());
+
+        high.normalize_s(&ctx);
+        assert!(high.is_low_s(&ctx));
+        assert_eq!(high, sig);
+        assert!(high.verify(&verify, &msg, &public_key).is_ok());
+    }
+
+    #[test]
+    fn hex() {
+        let ctx = ContextNone::new();
+        let (sig, _, public_key) = signed();
+
+        let encoded = sig.to_string();
+        assert!(encoded.starts_with("30"));
+        assert_eq!(encoded.parse::<Signature>().unwrap(), sig);
+        assert_eq!(
+            std::format!("{:?}", sig),
+            std::format!("Signature({})", encoded)
+        );
+        assert!("30zz".parse::<Signature>().is_err());
+
+        let encoded = public_key.to_string();
+        assert_eq!(encoded.len(), 66);
+        assert_eq!(encoded.parse::<PublicKey>().unwrap(), public_key);
+
+        let uncompressed = public_key.serialize_uncompressed(&ctx).unwrap();
+        let mut uncompressed_hex = std::string::String::new();
+        for byte in uncompressed.iter() {
+            uncompressed_hex.push_str(&std::format!("{:02x}", byte));
+        }
+        assert_eq!(uncompressed_hex.parse::<PublicKey>().unwrap(), public_key);
+        assert!(encoded[..64].parse::<PublicKey>().is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Stealth addresses, letting a sender pay a fresh key that only the recipient can link to their
+//! address.
+//!
+//! The recipient publishes a scan key `Q = dG` and a spend key `R = fG`. The sender picks an
+//! ephemeral key `e`, puts `P = eG` in the output and pays to `R + H(eQ)G`. As `eQ == dP`,
+//! whoever holds `d` can recognize the output, but only the holder of `f` can spend it, with the
+//! key `f + H(dP)`. `H` is the hash of `SharedSecret::new`, as in veild.
+
+use core::ptr;
+
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::ecdh::SharedSecret;
+use crate::keys::{PrivateKey, PublicKey};
+
+/// The public half of a stealth address, all a sender needs to pay it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StealthAddress {
+    pub scan_public_key: PublicKey,
+    pub spend_public_key: PublicKey,
+}
+
+impl StealthAddress {
+    pub fn new(
+        ctx: &impl Signing,
+        scan_private_key: &PrivateKey,
+        spend_private_key: &PrivateKey,
+    ) -> Result<Self> {
+        Ok(Self {
+            scan_public_key: PublicKey::new(ctx, scan_private_key)?,
+            spend_public_key: PublicKey::new(ctx, spend_private_key)?,
+        })
+    }
+
+    /// The one-time key to pay to, for an output carrying the public key of
+    /// `ephemeral_private_key`. A new ephemeral key must be used for every output.
+    pub fn derive_output_key(
+        &self,
+        ctx: &impl Verification,
+        ephemeral_private_key: &PrivateKey,
+    ) -> Result<PublicKey> {
+        let shared = SharedSecret::new(&self.scan_public_key, ephemeral_private_key)?;
+        let mut output_key = self.spend_public_key;
+        output_key.add_assign(ctx, *shared.as_bytes())?;
+        Ok(output_key)
+    }
+}
+
+/// The private scan key with the public spend key, enough to find the outputs paying an address
+/// but not to spend them, as a view-only wallet holds.
+#[derive(Debug)]
+pub struct StealthScanKey {
+    scan_private_key: PrivateKey,
+    pub spend_public_key: PublicKey,
+}
+
+impl StealthScanKey {
+    pub fn new(scan_private_key: PrivateKey, spend_public_key: PublicKey) -> Self {
+        Self {
+            scan_private_key,
+            spend_public_key,
+        }
+    }
+
+    pub fn address(&self, ctx: &impl Signing) -> Result<StealthAddress> {
+        Ok(StealthAddress {
+            scan_public_key: PublicKey::new(ctx, &self.scan_private_key)?,
+            spend_public_key: self.spend_public_key,
+        })
+    }
+
+    /// Whether the output with `output_public_key`, carrying `ephemeral_public_key`, pays this
+    /// address.
+    pub fn is_mine(
+        &self,
+        ctx: &impl Verification,
+        ephemeral_public_key: &PublicKey,
+        output_public_key: &PublicKey,
+    ) -> Result<bool> {
+        Ok(self.output_key(ctx, ephemeral_public_key)? == *output_public_key)
+    }
+
+    /// The one-time key an output carrying `ephemeral_public_key` pays if it pays this address,
+    /// for outputs that only commit to their key, such as by its hash.
+    pub fn output_key(
+        &self,
+        ctx: &impl Verification,
+        ephemeral_public_key: &PublicKey,
+    ) -> Result<PublicKey> {
+        let shared = SharedSecret::new(ephemeral_public_key, &self.scan_private_key)?;
+        let mut output_key = self.spend_public_key;
+        output_key.add_assign(ctx, *shared.as_bytes())?;
+        Ok(output_key)
+    }
+
+    /// The private key of the output carrying `ephemeral_public_key`, which needs the private
+    /// spend key.
+    pub fn derive_spend_key(
+        &self,
+        ctx: &impl Context,
+        spend_private_key: &PrivateKey,
+        ephemeral_public_key: &PublicKey,
+    ) -> Result<PrivateKey> {
+        let shared = SharedSecret::new(ephemeral_public_key, &self.scan_private_key)?;
+        let mut bytes = spend_private_key.to_bytes();
+        let spend_key = PrivateKey::from_slice(ctx, &bytes);
+        unsafe { ptr::write_volatile(&mut bytes, [0; 32]) };
+
+        let mut spend_key = spend_key?;
+        spend_key.add_assign(ctx, *shared.as_bytes())?;
+        Ok(spend_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ContextAll;
+
+    #[test]
+    fn stealth() {
+        let ctx = ContextAll::new();
+        let scan = PrivateKey::new(&ctx);
+        let spend = PrivateKey::new(&ctx);
+        let address = StealthAddress::new(&ctx, &scan, &spend).unwrap();
+
+        // The sender only sees the address.
+        let ephemeral = PrivateKey::new(&ctx);
+        let ephemeral_public = PublicKey::new(&ctx, &ephemeral).unwrap();
+        let output = address.derive_output_key(&ctx, &ephemeral).unwrap();
+        assert_ne!(output, address.spend_public_key);
+
+        let scan_key = StealthScanKey::new(scan, address.spend_public_key);
+        assert_eq!(scan_key.address(&ctx).unwrap(), address);
+        assert!(scan_key.is_mine(&ctx, &ephemeral_public, &output).unwrap());
+        assert_eq!(
+            scan_key.output_key(&ctx, &ephemeral_public).unwrap(),
+            output
+        );
+
+        let spend_key = scan_key
+            .derive_spend_key(&ctx, &spend, &ephemeral_public)
+            .unwrap();
+        assert_eq!(PublicKey::new(&ctx, &spend_key).unwrap(), output);
+
+        // Another ephemeral key, or another address, gives an unrelated output.
+        let other_ephemeral = PublicKey::new(&ctx, &PrivateKey::new(&ctx)).unwrap();
+        assert!(!scan_key.is_mine(&ctx, &other_ephemeral, &output).unwrap());
+        let other = StealthAddress::new(&ctx, &PrivateKey::new(&ctx), &spend).unwrap();
+        let other_output = other.derive_output_key(&ctx, &ephemeral).unwrap();
+        assert!(
+            !scan_key
+                .is_mine(&ctx, &ephemeral_public, &other_output)
+                .unwrap()
+        );
+    }
+}
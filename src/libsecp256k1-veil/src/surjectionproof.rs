@@ -0,0 +1,219 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use secp256k1_veil_sys::{FixedAssetTag, c_types::*};
+
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::generator::Generator;
+use crate::pedersen::BlindingFactor;
+
+/// A proof that the asset of an output is the asset of one of the inputs, without showing
+/// which one.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct SurjectionProof(secp256k1_veil_sys::SurjectionProof);
+
+impl SurjectionProof {
+    /// Most inputs a proof can cover.
+    pub const MAX_INPUTS: usize = secp256k1_veil_sys::SURJECTIONPROOF_MAX_N_INPUTS;
+
+    /// Size of a serialized proof using all of `MAX_INPUTS`.
+    pub const MAX_SERIALIZED_SIZE: usize = 2 + Self::MAX_INPUTS / 8 + 32 * (1 + Self::MAX_INPUTS);
+
+    /// Picks `n_to_use` of `input_tags` to hide the output among, one of them being an input
+    /// with `output_tag`. Tries up to `max_iterations` random draws seeded from `seed`.
+    ///
+    /// Returns the proof, ready for `generate`, and the index of the input it proves against.
+    pub fn initialize(
+        ctx: &impl Context,
+        input_tags: &[[u8; 32]],
+        n_to_use: usize,
+        output_tag: &[u8; 32],
+        max_iterations: usize,
+        seed: &[u8; 32],
+    ) -> Result<(Self, usize)> {
+        let (proof, input_index) = unsafe {
+            secp256k1_veil_sys::SurjectionProof::initialize(
+                ctx.as_ptr(),
+                input_tags.as_ptr() as *const FixedAssetTag,
+                input_tags.len() as size_t,
+                n_to_use as size_t,
+                output_tag.as_ptr() as *const FixedAssetTag,
+                max_iterations as size_t,
+                seed.as_ptr(),
+            )?
+        };
+        Ok((Self(proof), input_index))
+    }
+
+    /// Signs the proof. `input_tags` are the blinded generators of every input, `input_index`
+    /// and `input_blind` those of the input returned by `initialize`.
+    pub fn generate(
+        &mut self,
+        ctx: &(impl Signing + Verification),
+        input_tags: &[Generator],
+        output_tag: &Generator,
+        input_index: usize,
+        input_blind: &BlindingFactor,
+        output_blind: &BlindingFactor,
+    ) -> Result<()> {
+        unsafe {
+            self.0.generate(
+                ctx.as_ptr(),
+                input_tags.as_ptr() as *const secp256k1_veil_sys::Generator,
+                input_tags.len() as size_t,
+                output_tag.as_ptr(),
+                input_index as size_t,
+                input_blind.as_ptr(),
+                output_blind.as_ptr(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        input_tags: &[Generator],
+        output_tag: &Generator,
+    ) -> Result<()> {
+        unsafe {
+            self.0.verify(
+                ctx.as_ptr(),
+                input_tags.as_ptr() as *const secp256k1_veil_sys::Generator,
+                input_tags.len() as size_t,
+                output_tag.as_ptr(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn parse(ctx: &impl Context, input: &[u8]) -> Result<Self> {
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::SurjectionProof::parse(
+                ctx.as_ptr(),
+                input.as_ptr(),
+                input.len() as size_t,
+            )?
+        }))
+    }
+
+    /// Writes the proof to `output`, returning the number of bytes written. See
+    /// `serialized_size` for the space needed.
+    pub fn serialize(&self, ctx: &impl Context, output: &mut [u8]) -> Result<usize> {
+        let mut len = output.len() as size_t;
+        unsafe {
+            self.0
+                .serialize(ctx.as_ptr(), output.as_mut_ptr(), &mut len)?;
+        }
+        Ok(len)
+    }
+
+    /// Number of inputs the proof was created for.
+    pub fn n_total_inputs(&self, ctx: &impl Context) -> usize {
+        unsafe { self.0.n_total_inputs(ctx.as_ptr()) }
+    }
+
+    /// Number of inputs the proof hides the output among.
+    pub fn n_used_inputs(&self, ctx: &impl Context) -> usize {
+        unsafe { self.0.n_used_inputs(ctx.as_ptr()) }
+    }
+
+    pub fn serialized_size(&self, ctx: &impl Context) -> usize {
+        unsafe { self.0.serialized_size(ctx.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextAll, ContextNone, ContextSign, ContextVerify};
+
+    const TAGS: [[u8; 32]; 4] = [[1; 32], [2; 32], [3; 32], [4; 32]];
+
+    fn blinded_inputs(sign: &ContextSign, blinds: &[BlindingFactor]) -> [Generator; 4] {
+        let mut gens = [Generator::new(sign, &[0xff; 32]).unwrap(); 4];
+        for (i, gen) in gens.iter_mut().enumerate() {
+            *gen = Generator::new_blinded(sign, &TAGS[i], &blinds[i]).unwrap();
+        }
+        gens
+    }
+
+    #[test]
+    fn generate_and_verify() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let all = ContextAll::new();
+        let verify = ContextVerify::new();
+
+        let blinds = [
+            BlindingFactor::random(&ctx),
+            BlindingFactor::random(&ctx),
+            BlindingFactor::random(&ctx),
+            BlindingFactor::random(&ctx),
+        ];
+        let inputs = blinded_inputs(&sign, &blinds);
+        let output_blind = BlindingFactor::random(&ctx);
+        let output = Generator::new_blinded(&sign, &TAGS[2], &output_blind).unwrap();
+
+        let (mut proof, index) =
+            SurjectionProof::initialize(&ctx, &TAGS, 3, &TAGS[2], 100, &[9; 32]).unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(proof.n_total_inputs(&ctx), 4);
+        assert_eq!(proof.n_used_inputs(&ctx), 3);
+
+        proof
+            .generate(&all, &inputs, &output, index, &blinds[index], &output_blind)
+            .unwrap();
+        assert!(proof.verify(&verify, &inputs, &output).is_ok());
+
+        let other = Generator::new_blinded(&sign, &TAGS[3], &output_blind).unwrap();
+        assert!(proof.verify(&verify, &inputs, &other).is_err());
+
+        let mut bytes = [0u8; SurjectionProof::MAX_SERIALIZED_SIZE];
+        let len = proof.serialize(&ctx, &mut bytes).unwrap();
+        assert_eq!(len, proof.serialized_size(&ctx));
+        assert_eq!(len, 2 + 1 + 32 * (1 + 3));
+
+        let parsed = SurjectionProof::parse(&ctx, &bytes[..len]).unwrap();
+        assert!(parsed.verify(&verify, &inputs, &output).is_ok());
+        assert!(proof.serialize(&ctx, &mut bytes[..len - 1]).is_err());
+    }
+
+    #[test]
+    fn initialize_without_matching_input() {
+        let ctx = ContextNone::new();
+        assert!(SurjectionProof::initialize(&ctx, &TAGS, 2, &[5; 32], 100, &[9; 32]).is_err());
+        assert!(SurjectionProof::initialize(&ctx, &TAGS, 5, &TAGS[0], 100, &[9; 32]).is_err());
+    }
+
+    #[test]
+    fn parse_truncated() {
+        // The truncated encodings from the C library's surjection proof tests.
+        let ctx = ContextNone::new();
+        assert!(SurjectionProof::parse(&ctx, &[0x00]).is_err());
+        assert!(SurjectionProof::parse(&ctx, &[0x01, 0x00]).is_err());
+        assert!(SurjectionProof::parse(&ctx, &[0; 33]).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+use core::ptr;
+use secp256k1_veil_sys::c_types::*;
+
+use crate::Result;
+use crate::context::{Context, Signing, Verification};
+use crate::error::{Error, ErrorKind};
+use crate::keys::{PrivateKey, PublicKey};
+
+/// A ring signature proving that a key was whitelisted by one of a set of online/offline key
+/// pairs, without showing which one.
+///
+/// The ring is made of the keys `online_i + H(offline_i + sub) * (offline_i + sub)` where `sub`
+/// is the whitelisted key. The signer needs the private keys of its online key and of the sum
+/// of its offline key and the whitelisted key.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct WhitelistSignature(secp256k1_veil_sys::WhitelistSignature);
+
+impl WhitelistSignature {
+    /// Most key pairs a signature can cover, the serialization stores the count in one byte.
+    pub const MAX_KEYS: usize = secp256k1_veil_sys::WHITELIST_MAX_N_KEYS - 1;
+
+    /// Size of a serialized signature covering `MAX_KEYS` pairs.
+    pub const MAX_SERIALIZED_SIZE: usize = 33 + 32 * Self::MAX_KEYS;
+
+    /// Signs for the key pair at `index`. `summed_key` is the private key of the whitelisted
+    /// key plus the offline key at `index`.
+    pub fn sign(
+        ctx: &(impl Signing + Verification),
+        online_keys: &[PublicKey],
+        offline_keys: &[PublicKey],
+        sub_key: &PublicKey,
+        online_private_key: &PrivateKey,
+        summed_private_key: &PrivateKey,
+        index: usize,
+    ) -> Result<Self> {
+        check_keys(online_keys, offline_keys)?;
+        if index >= online_keys.len() {
+            return Err(Error::new(ErrorKind::InvalidWhitelist));
+        }
+
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::WhitelistSignature::sign(
+                ctx.as_ptr(),
+                online_keys.as_ptr() as *const secp256k1_veil_sys::PublicKey,
+                offline_keys.as_ptr() as *const secp256k1_veil_sys::PublicKey,
+                online_keys.len() as size_t,
+                sub_key.as_ptr(),
+                online_private_key.as_ptr(),
+                summed_private_key.as_ptr(),
+                index as size_t,
+                ptr::null(),
+            )?
+        }))
+    }
+
+    /// Verifies that `sub_key` was whitelisted by one of the key pairs, which must be the same
+    /// keys, in the same order, as when signing.
+    pub fn verify(
+        &self,
+        ctx: &impl Verification,
+        online_keys: &[PublicKey],
+        offline_keys: &[PublicKey],
+        sub_key: &PublicKey,
+    ) -> Result<()> {
+        check_keys(online_keys, offline_keys)?;
+        if online_keys.len() != self.n_keys() {
+            return Err(Error::new(ErrorKind::InvalidWhitelist));
+        }
+
+        unsafe {
+            self.0.verify(
+                ctx.as_ptr(),
+                online_keys.as_ptr() as *const secp256k1_veil_sys::PublicKey,
+                offline_keys.as_ptr() as *const secp256k1_veil_sys::PublicKey,
+                sub_key.as_ptr(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Parses a signature of exactly `33 + 32 * n_keys` bytes, `n_keys` being the first byte.
+    pub fn parse(ctx: &impl Context, input: &[u8]) -> Result<Self> {
+        match input.first() {
+            Some(&n_keys) if input.len() == 33 + 32 * n_keys as usize => {}
+            _ => return Err(Error::new(ErrorKind::InvalidWhitelist)),
+        }
+
+        Ok(Self(unsafe {
+            secp256k1_veil_sys::WhitelistSignature::parse(ctx.as_ptr(), input.as_ptr())?
+        }))
+    }
+
+    /// Writes the signature to `output`, returning the number of bytes written. See
+    /// `serialized_size` for the space needed.
+    pub fn serialize(&self, ctx: &impl Context, output: &mut [u8]) -> Result<usize> {
+        let len = self.serialized_size();
+        if output.len() < len {
+            return Err(Error::new(ErrorKind::InvalidWhitelist));
+        }
+
+        unsafe { self.0.serialize(ctx.as_ptr(), output.as_mut_ptr()) };
+        Ok(len)
+    }
+
+    /// Number of key pairs the signature was made for.
+    pub fn n_keys(&self) -> usize {
+        unsafe { self.0.n_keys() }
+    }
+
+    pub fn serialized_size(&self) -> usize {
+        33 + 32 * self.n_keys()
+    }
+}
+
+fn check_keys(online_keys: &[PublicKey], offline_keys: &[PublicKey]) -> Result<()> {
+    if online_keys.is_empty()
+        || online_keys.len() > WhitelistSignature::MAX_KEYS
+        || online_keys.len() != offline_keys.len()
+    {
+        Err(Error::new(ErrorKind::InvalidWhitelist))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ContextAll, ContextNone, ContextSign, ContextVerify};
+    use std::vec::Vec;
+
+    struct Ring {
+        online: Vec<PublicKey>,
+        offline: Vec<PublicKey>,
+        sub: PublicKey,
+        online_private_key: PrivateKey,
+        summed_private_key: PrivateKey,
+    }
+
+    /// Builds a ring of `n` key pairs where the signer at `index` whitelisted a key. The
+    /// whitelisted key is derived as `summed - offline` so its private key is never needed.
+    fn ring(n: usize, index: usize) -> Ring {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let mut online = Vec::new();
+        let mut offline = Vec::new();
+        for _ in 0..n {
+            online.push(PublicKey::new(&sign, &PrivateKey::new(&ctx)).unwrap());
+            offline.push(PublicKey::new(&sign, &PrivateKey::new(&ctx)).unwrap());
+        }
+
+        let online_private_key = PrivateKey::new(&ctx);
+        online[index] = PublicKey::new(&sign, &online_private_key).unwrap();
+
+        let summed_private_key = PrivateKey::new(&ctx);
+        let mut neg_offline = offline[index];
+        neg_offline.neg(&ctx).unwrap();
+        let sub = PublicKey::new(&sign, &summed_private_key)
+            .unwrap()
+            .combine(&ctx, &neg_offline)
+            .unwrap();
+
+        Ring {
+            online,
+            offline,
+            sub,
+            online_private_key,
+            summed_private_key,
+        }
+    }
+
+    #[test]
+    fn sign_verify() {
+        let ctx = ContextNone::new();
+        let sign = ContextSign::new();
+        let all = ContextAll::new();
+        let verify = ContextVerify::new();
+        let ring = ring(4, 2);
+
+        let sig = WhitelistSignature::sign(
+            &all,
+            &ring.online,
+            &ring.offline,
+            &ring.sub,
+            &ring.online_private_key,
+            &ring.summed_private_key,
+            2,
+        )
+        .unwrap();
+        assert_eq!(sig.n_keys(), 4);
+        assert!(
+            sig.verify(&verify, &ring.online, &ring.offline, &ring.sub)
+                .is_ok()
+        );
+
+        let other = PublicKey::new(&sign, &PrivateKey::new(&ctx)).unwrap();
+        assert!(
+            sig.verify(&verify, &ring.online, &ring.offline, &other)
+                .is_err()
+        );
+        assert!(
+            sig.verify(&verify, &ring.online[..3], &ring.offline[..3], &ring.sub)
+                .is_err()
+        );
+
+        let mut swapped = ring.online.clone();
+        swapped.swap(0, 2);
+        assert!(
+            sig.verify(&verify, &swapped, &ring.offline, &ring.sub)
+                .is_err()
+        );
+
+        assert!(
+            WhitelistSignature::sign(
+                &all,
+                &ring.online,
+                &ring.offline,
+                &ring.sub,
+                &ring.online_private_key,
+                &ring.summed_private_key,
+                4,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let ctx = ContextNone::new();
+        let all = ContextAll::new();
+        let verify = ContextVerify::new();
+        let ring = ring(3, 0);
+
+        let sig = WhitelistSignature::sign(
+            &all,
+            &ring.online,
+            &ring.offline,
+            &ring.sub,
+            &ring.online_private_key,
+            &ring.summed_private_key,
+            0,
+        )
+        .unwrap();
+
+        let mut output = [0u8; WhitelistSignature::MAX_SERIALIZED_SIZE];
+        let len = sig.serialize(&ctx, &mut output).unwrap();
+        assert_eq!(len, 33 + 32 * 3);
+        assert_eq!(output[0], 3);
+
+        let parsed = WhitelistSignature::parse(&ctx, &output[..len]).unwrap();
+        assert!(
+            parsed
+                .verify(&verify, &ring.online, &ring.offline, &ring.sub)
+                .is_ok()
+        );
+
+        assert!(WhitelistSignature::parse(&ctx, &output[..len - 1]).is_err());
+        assert!(WhitelistSignature::parse(&ctx, &[]).is_err());
+        assert!(sig.serialize(&ctx, &mut [0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Base58 pay to public key hash addresses, as used to prove ownership with signed messages.
+
+use crate::crypto::hash;
+use crate::prelude::*;
+use crate::script::OpCodeKind;
+use core::{fmt, str};
+#[cfg(feature = "std")]
+use std::error;
+
+/// A network with its own address prefixes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Main,
+    Test,
+}
+
+impl Network {
+    pub fn pubkey_address_prefix(self) -> u8 {
+        match self {
+            Network::Main => 70,
+            Network::Test => 111,
+        }
+    }
+
+    pub fn secret_key_prefix(self) -> u8 {
+        match self {
+            Network::Main => 128,
+            Network::Test => 239,
+        }
+    }
+
+    /// Version bytes of a serialized extended public key.
+    pub fn ext_public_key_prefix(self) -> [u8; 4] {
+        match self {
+            Network::Main => [0x04, 0x88, 0xb2, 0x1e],
+            Network::Test => [0x04, 0x35, 0x87, 0xcf],
+        }
+    }
+
+    /// Version bytes of a serialized extended private key.
+    pub fn ext_secret_key_prefix(self) -> [u8; 4] {
+        match self {
+            Network::Main => [0x04, 0x88, 0xad, 0xe4],
+            Network::Test => [0x04, 0x35, 0x83, 0x94],
+        }
+    }
+
+    fn from_pubkey_address_prefix(prefix: u8) -> Option<Self> {
+        match prefix {
+            70 => Some(Network::Main),
+            111 => Some(Network::Test),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_secret_key_prefix(prefix: u8) -> Option<Self> {
+        match prefix {
+            128 => Some(Network::Main),
+            239 => Some(Network::Test),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_ext_public_key_prefix(prefix: [u8; 4]) -> Option<Self> {
+        match prefix {
+            [0x04, 0x88, 0xb2, 0x1e] => Some(Network::Main),
+            [0x04, 0x35, 0x87, 0xcf] => Some(Network::Test),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_ext_secret_key_prefix(prefix: [u8; 4]) -> Option<Self> {
+        match prefix {
+            [0x04, 0x88, 0xad, 0xe4] => Some(Network::Main),
+            [0x04, 0x35, 0x83, 0x94] => Some(Network::Test),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AddressError {
+    /// Not base58, or the checksum does not match.
+    InvalidBase58,
+    InvalidLength(usize),
+    UnknownPrefix(u8),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddressError::InvalidBase58 => f.write_str("invalid base58 or checksum"),
+            AddressError::InvalidLength(len) => write!(f, "invalid address length {}", len),
+            AddressError::UnknownPrefix(prefix) => write!(f, "unknown address prefix {}", prefix),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for AddressError {}
+
+/// An address paying to the hash160 of a public key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PubkeyHashAddress {
+    pub network: Network,
+    pub hash: [u8; 20],
+}
+
+impl PubkeyHashAddress {
+    /// The address of a serialized public key, compressed and uncompressed keys have different
+    /// addresses.
+    pub fn from_public_key(network: Network, public_key: &[u8]) -> Self {
+        Self {
+            network,
+            hash: hash::hash160(public_key),
+        }
+    }
+
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        let mut script = Vec::with_capacity(25);
+        script.push(OpCodeKind::OpDup as u8);
+        script.push(OpCodeKind::OpHash160 as u8);
+        script.push(20);
+        script.extend_from_slice(&self.hash);
+        script.push(OpCodeKind::OpEqualVerify as u8);
+        script.push(OpCodeKind::OpCheckSig as u8);
+        script
+    }
+}
+
+impl fmt::Display for PubkeyHashAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut data = [0u8; 21];
+        data[0] = self.network.pubkey_address_prefix();
+        data[1..].copy_from_slice(&self.hash);
+        f.write_str(&bs58::encode(&data[..]).with_check().into_string())
+    }
+}
+
+impl str::FromStr for PubkeyHashAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, AddressError> {
+        let data = bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(|_| AddressError::InvalidBase58)?;
+        if data.len() != 21 {
+            return Err(AddressError::InvalidLength(data.len()));
+        }
+        let network = Network::from_pubkey_address_prefix(data[0])
+            .ok_or(AddressError::UnknownPrefix(data[0]))?;
+
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&data[1..]);
+        Ok(Self { network, hash })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{self, ScriptKind};
+
+    #[test]
+    fn round_trip() {
+        let address = PubkeyHashAddress {
+            network: Network::Main,
+            hash: [7; 20],
+        };
+        let encoded = address.to_string();
+        assert!(encoded.starts_with('V'));
+        assert_eq!(encoded.parse::<PubkeyHashAddress>(), Ok(address));
+        assert_eq!(
+            script::solve(&address.script_pubkey()),
+            ScriptKind::PubKeyHash
+        );
+
+        let test = PubkeyHashAddress {
+            network: Network::Test,
+            ..address
+        };
+        assert_ne!(test.to_string(), encoded);
+        assert_eq!(test.to_string().parse::<PubkeyHashAddress>(), Ok(test));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut encoded = PubkeyHashAddress {
+            network: Network::Main,
+            hash: [7; 20],
+        }
+        .to_string();
+        encoded.pop();
+        encoded.push('1');
+        assert_eq!(
+            encoded.parse::<PubkeyHashAddress>(),
+            Err(AddressError::InvalidBase58)
+        );
+
+        let bitcoin = bs58::encode(&[0u8; 21][..]).with_check().into_string();
+        assert_eq!(
+            bitcoin.parse::<PubkeyHashAddress>(),
+            Err(AddressError::UnknownPrefix(0))
+        );
+    }
+}
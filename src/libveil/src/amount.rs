@@ -43,3 +43,59 @@ pub const MAX_MONEY: Amount = 300_000_000 * COIN;
 pub fn money_range(value: &Amount) -> bool {
     (value >= &0 && value <= &MAX_MONEY)
 }
+
+/// Fee rate in satoshis per 1000 virtual bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FeeRate(pub Amount);
+
+impl FeeRate {
+    pub fn per_kvb(amount: Amount) -> Self {
+        Self(amount)
+    }
+
+    /// Fee rate that results in paying `fee` for `vsize` virtual bytes.
+    pub fn from_fee(fee: Amount, vsize: usize) -> Self {
+        if vsize == 0 {
+            Self(0)
+        } else {
+            Self(mul_div(fee, 1000, vsize as Amount))
+        }
+    }
+
+    pub fn as_per_kvb(&self) -> Amount {
+        self.0
+    }
+
+    /// Fee for `vsize` virtual bytes.
+    ///
+    /// A non-zero rate never results in a zero fee, the same as veild rounds it.
+    pub fn fee(&self, vsize: usize) -> Amount {
+        let fee = mul_div(self.0, vsize as Amount, 1000);
+        if fee == 0 && vsize != 0 {
+            self.0.signum()
+        } else {
+            fee
+        }
+    }
+}
+
+/// `a * b / c` computed in 128 bits so large fees do not overflow, saturating the result.
+fn mul_div(a: Amount, b: Amount, c: Amount) -> Amount {
+    let result = i128::from(a) * i128::from(b) / i128::from(c);
+    result.clamp(i128::from(Amount::MIN), i128::from(Amount::MAX)) as Amount
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_rate() {
+        assert_eq!(FeeRate::from_fee(2260, 226), FeeRate(10_000));
+        assert_eq!(FeeRate::from_fee(MAX_MONEY, 1000), FeeRate(MAX_MONEY));
+        assert_eq!(FeeRate::from_fee(Amount::MAX, 1), FeeRate(Amount::MAX));
+        assert_eq!(FeeRate(10_000).fee(226), 2260);
+        assert_eq!(FeeRate(MAX_MONEY).fee(1000), MAX_MONEY);
+        assert_eq!(FeeRate(1).fee(10), 1);
+    }
+}
@@ -23,13 +23,8 @@
 // POSSIBILITY OF SUCH DAMAGE.
 
 use crate::util::MulOverflow;
-use crate::Result;
-use std::convert::TryFrom;
-use std::ops::{Div, Rem};
-use std::u32;
-use std::u64;
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 #[allow(non_camel_case_types)]
 pub struct u256(pub [u64; 4]);
 
@@ -145,6 +140,15 @@ impl u256 {
     pub fn from_u32(digit: u32) -> Self {
         Self::from(digit)
     }
+
+    /// Serializes to bytes, least significant byte first.
+    pub fn to_le_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, word) in bytes.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
 }
 
 impl From<u8> for u256 {
@@ -193,20 +197,20 @@ impl From<[u64; 4]> for u256 {
 
 #[cfg(test)]
 mod tests {
-    use crate::u256::u256;
+    use crate::u256;
 
     #[test]
     fn overflowing_arith() {
         let mut num1 = u256::from([0xFFFFFFFF_FFFFFFFF, 0x0, 0x0, 0x0]);
         let mut num2 = u256::from([0x1, 0x0, 0x0, 0x0]);
         let mut ans = num1.overflowing_add(num2);
-        println!("{:?}", ans);
+        std::println!("{:?}", ans);
 
         num1 = u256::from([0x0, 0x1, 0x0, 0x0]);
         num2 = u256::from([0xFFFFFFFF_FFFFFFFF, 0x0, 0x0, 0x0]);
         ans = num1.overflowing_sub(num2);
 
-        println!("{:?}", ans);
+        std::println!("{:?}", ans);
 
         num1 = u256::from([
             0xFFFFFFFF_FFFFFFFF,
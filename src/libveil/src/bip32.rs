@@ -0,0 +1,761 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! BIP32 hierarchical deterministic keys, deriving a tree of keys from a single seed.
+//!
+//! Veil uses the same `xprv`/`xpub` version bytes as Bitcoin, so keys exported here import into
+//! other BIP32 wallets.
+
+use crate::address::Network;
+use crate::crypto::hash::{self, Hasher, HmacSha512};
+use crate::prelude::*;
+use crate::util::zero;
+use libsecp256k1_veil::{Context, PrivateKey, PublicKey, Signing, Verification};
+use core::{fmt, str};
+#[cfg(feature = "std")]
+use std::error;
+
+/// Child numbers at or above this are hardened.
+const HARDENED_INDEX: u32 = 1 << 31;
+/// HMAC key used to turn a seed into the master key.
+const MASTER_KEY_SEED: &[u8] = b"Bitcoin seed";
+/// Length of a serialized extended key before base58 encoding.
+const EXTENDED_KEY_SIZE: usize = 78;
+
+#[derive(Debug, PartialEq)]
+pub enum Bip32Error {
+    /// Hardened children can only be derived from a private key.
+    CannotDeriveHardenedFromPublic,
+    /// The index is too large for a child number of its kind.
+    InvalidChildNumber(u32),
+    InvalidDerivationPath,
+    /// Not base58, or the checksum does not match.
+    InvalidBase58,
+    InvalidLength(usize),
+    UnknownVersion([u8; 4]),
+    /// The key is out of range, either when decoding or, with negligible probability, when
+    /// deriving.
+    InvalidKey,
+}
+
+impl fmt::Display for Bip32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bip32Error::CannotDeriveHardenedFromPublic => {
+                f.write_str("cannot derive a hardened child from a public key")
+            }
+            Bip32Error::InvalidChildNumber(index) => write!(f, "invalid child number {}", index),
+            Bip32Error::InvalidDerivationPath => f.write_str("invalid derivation path"),
+            Bip32Error::InvalidBase58 => f.write_str("invalid base58 or checksum"),
+            Bip32Error::InvalidLength(len) => write!(f, "invalid extended key length {}", len),
+            Bip32Error::UnknownVersion(version) => write!(
+                f,
+                "unknown extended key version {:02x}{:02x}{:02x}{:02x}",
+                version[0], version[1], version[2], version[3]
+            ),
+            Bip32Error::InvalidKey => f.write_str("invalid extended key"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Bip32Error {}
+
+/// The position of a key among its parent's children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChildNumber {
+    Normal(u32),
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    pub fn normal(index: u32) -> Result<Self, Bip32Error> {
+        if index < HARDENED_INDEX {
+            Ok(ChildNumber::Normal(index))
+        } else {
+            Err(Bip32Error::InvalidChildNumber(index))
+        }
+    }
+
+    pub fn hardened(index: u32) -> Result<Self, Bip32Error> {
+        if index < HARDENED_INDEX {
+            Ok(ChildNumber::Hardened(index))
+        } else {
+            Err(Bip32Error::InvalidChildNumber(index))
+        }
+    }
+
+    /// The child number serialized as `index`, hardened ones having the top bit set.
+    pub fn from_index(index: u32) -> Self {
+        if index < HARDENED_INDEX {
+            ChildNumber::Normal(index)
+        } else {
+            ChildNumber::Hardened(index - HARDENED_INDEX)
+        }
+    }
+
+    pub fn to_index(self) -> u32 {
+        match self {
+            ChildNumber::Normal(index) => index,
+            ChildNumber::Hardened(index) => index + HARDENED_INDEX,
+        }
+    }
+
+    pub fn is_hardened(self) -> bool {
+        match self {
+            ChildNumber::Normal(_) => false,
+            ChildNumber::Hardened(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChildNumber::Normal(index) => write!(f, "{}", index),
+            ChildNumber::Hardened(index) => write!(f, "{}'", index),
+        }
+    }
+}
+
+impl str::FromStr for ChildNumber {
+    type Err = Bip32Error;
+
+    /// Parses `44'` or `44h` as hardened and `44` as normal.
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        let (index, hardened) = match s.strip_suffix(|c| c == '\'' || c == 'h') {
+            Some(index) => (index, true),
+            None => (s, false),
+        };
+        let index = index
+            .parse()
+            .map_err(|_| Bip32Error::InvalidDerivationPath)?;
+        if hardened {
+            ChildNumber::hardened(index)
+        } else {
+            ChildNumber::normal(index)
+        }
+    }
+}
+
+/// A list of child numbers leading from a master key, written like `m/44'/0'/0'/0/1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    /// The path with `child` appended.
+    pub fn child(&self, child: ChildNumber) -> Self {
+        let mut path = self.0.clone();
+        path.push(child);
+        DerivationPath(path)
+    }
+}
+
+impl From<Vec<ChildNumber>> for DerivationPath {
+    fn from(path: Vec<ChildNumber>) -> Self {
+        DerivationPath(path)
+    }
+}
+
+impl AsRef<[ChildNumber]> for DerivationPath {
+    fn as_ref(&self) -> &[ChildNumber] {
+        &self.0
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for DerivationPath {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Bip32Error> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(Bip32Error::InvalidDerivationPath);
+        }
+        parts
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(DerivationPath)
+    }
+}
+
+/// A private key with the chain code needed to derive its children.
+#[derive(Debug, PartialEq)]
+pub struct ExtendedPrivKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    pub private_key: PrivateKey,
+}
+
+impl ExtendedPrivKey {
+    /// The root of the tree for `seed`, which BIP32 recommends be between 16 and 64 bytes.
+    pub fn new_master(
+        ctx: &impl Context,
+        network: Network,
+        seed: &[u8],
+    ) -> Result<Self, Bip32Error> {
+        let mut engine = HmacSha512::new(MASTER_KEY_SEED);
+        engine.update(seed);
+        let (mut key, chain_code) = split_hmac(engine);
+        let private_key = PrivateKey::from_slice(ctx, &key).map_err(|_| Bip32Error::InvalidKey);
+        zero(&mut key);
+
+        Ok(Self {
+            network,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: ChildNumber::Normal(0),
+            chain_code,
+            private_key: private_key?,
+        })
+    }
+
+    pub fn derive_child(
+        &self,
+        ctx: &impl Signing,
+        child_number: ChildNumber,
+    ) -> Result<Self, Bip32Error> {
+        let public_key = self.public_key(ctx)?;
+        let mut engine = HmacSha512::new(&self.chain_code);
+        if child_number.is_hardened() {
+            let mut key = self.private_key.to_bytes();
+            engine.update(&[0]);
+            engine.update(&key);
+            zero(&mut key);
+        } else {
+            engine.update(&public_key);
+        }
+        engine.update(&child_number.to_index().to_be_bytes());
+        let (mut tweak, chain_code) = split_hmac(engine);
+
+        let mut key = self.private_key.to_bytes();
+        let private_key = PrivateKey::from_slice(ctx, &key).and_then(|mut private_key| {
+            private_key.add_assign(ctx, tweak)?;
+            Ok(private_key)
+        });
+        zero(&mut key);
+        zero(&mut tweak);
+
+        Ok(Self {
+            network: self.network,
+            depth: self.depth.checked_add(1).ok_or(Bip32Error::InvalidKey)?,
+            parent_fingerprint: fingerprint(&public_key),
+            child_number,
+            chain_code,
+            private_key: private_key.map_err(|_| Bip32Error::InvalidKey)?,
+        })
+    }
+
+    /// Derives each child along `path` in turn. `path` is taken relative to this key.
+    pub fn derive_path(
+        &self,
+        ctx: &impl Signing,
+        path: &DerivationPath,
+    ) -> Result<Self, Bip32Error> {
+        let mut key: Option<Self> = None;
+        for &child_number in path.as_ref() {
+            key = Some(
+                key.as_ref()
+                    .unwrap_or(self)
+                    .derive_child(ctx, child_number)?,
+            );
+        }
+        match key {
+            Some(key) => Ok(key),
+            None => self.try_clone(ctx),
+        }
+    }
+
+    /// A copy of this key, `PrivateKey` is not `Clone` so copies of it are always explicit.
+    fn try_clone(&self, ctx: &impl Context) -> Result<Self, Bip32Error> {
+        let mut key = self.private_key.to_bytes();
+        let private_key = PrivateKey::from_slice(ctx, &key).map_err(|_| Bip32Error::InvalidKey);
+        zero(&mut key);
+
+        Ok(Self {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            private_key: private_key?,
+        })
+    }
+
+    /// Hash160 of the compressed public key, identifying this key.
+    pub fn identifier(&self, ctx: &impl Signing) -> Result<[u8; 20], Bip32Error> {
+        Ok(hash::hash160(&self.public_key(ctx)?))
+    }
+
+    /// The first four bytes of the identifier, which children record as their parent.
+    pub fn fingerprint(&self, ctx: &impl Signing) -> Result<[u8; 4], Bip32Error> {
+        Ok(fingerprint(&self.public_key(ctx)?))
+    }
+
+    fn public_key(&self, ctx: &impl Signing) -> Result<[u8; 33], Bip32Error> {
+        PublicKey::new(ctx, &self.private_key)
+            .and_then(|public_key| public_key.serialize_compressed(ctx))
+            .map_err(|_| Bip32Error::InvalidKey)
+    }
+
+    pub fn decode(ctx: &impl Context, s: &str) -> Result<Self, Bip32Error> {
+        let mut data = decode_base58(s)?;
+        let key = Self::from_data(ctx, &data);
+        zero(&mut data);
+        key
+    }
+
+    fn from_data(ctx: &impl Context, data: &[u8]) -> Result<Self, Bip32Error> {
+        let (version, depth, parent_fingerprint, child_number, chain_code) = split_data(data);
+        let network = Network::from_ext_secret_key_prefix(version)
+            .ok_or(Bip32Error::UnknownVersion(version))?;
+        if data[45] != 0 {
+            return Err(Bip32Error::InvalidKey);
+        }
+        let private_key =
+            PrivateKey::from_slice(ctx, &data[46..]).map_err(|_| Bip32Error::InvalidKey)?;
+
+        Ok(Self {
+            network,
+            depth,
+            parent_fingerprint,
+            child_number,
+            chain_code,
+            private_key,
+        })
+    }
+
+    /// The base58 `xprv` string.
+    pub fn encode(&self) -> String {
+        let mut data = [0u8; EXTENDED_KEY_SIZE];
+        write_data(
+            &mut data,
+            self.network.ext_secret_key_prefix(),
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            self.chain_code,
+        );
+        data[46..].copy_from_slice(&self.private_key.to_bytes());
+
+        let encoded = bs58::encode(&data[..]).with_check().into_string();
+        zero(&mut data);
+        encoded
+    }
+}
+
+/// A public key with the chain code needed to derive its normal children, for watching a wallet
+/// without being able to spend from it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtendedPubKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    pub public_key: PublicKey,
+}
+
+impl ExtendedPubKey {
+    pub fn from_private(ctx: &impl Signing, key: &ExtendedPrivKey) -> Result<Self, Bip32Error> {
+        Ok(Self {
+            network: key.network,
+            depth: key.depth,
+            parent_fingerprint: key.parent_fingerprint,
+            child_number: key.child_number,
+            chain_code: key.chain_code,
+            public_key: PublicKey::new(ctx, &key.private_key)
+                .map_err(|_| Bip32Error::InvalidKey)?,
+        })
+    }
+
+    /// Derives a normal child, hardened children need the private key.
+    pub fn derive_child(
+        &self,
+        ctx: &impl Verification,
+        child_number: ChildNumber,
+    ) -> Result<Self, Bip32Error> {
+        if child_number.is_hardened() {
+            return Err(Bip32Error::CannotDeriveHardenedFromPublic);
+        }
+        let serialized = self.serialize_compressed(ctx)?;
+        let mut engine = HmacSha512::new(&self.chain_code);
+        engine.update(&serialized);
+        engine.update(&child_number.to_index().to_be_bytes());
+        let (tweak, chain_code) = split_hmac(engine);
+
+        let mut public_key = self.public_key;
+        public_key
+            .add_assign(ctx, tweak)
+            .map_err(|_| Bip32Error::InvalidKey)?;
+
+        Ok(Self {
+            network: self.network,
+            depth: self.depth.checked_add(1).ok_or(Bip32Error::InvalidKey)?,
+            parent_fingerprint: fingerprint(&serialized),
+            child_number,
+            chain_code,
+            public_key,
+        })
+    }
+
+    /// Derives each child along `path` in turn, which must all be normal.
+    pub fn derive_path(
+        &self,
+        ctx: &impl Verification,
+        path: &DerivationPath,
+    ) -> Result<Self, Bip32Error> {
+        path.as_ref().iter().try_fold(*self, |key, &child_number| {
+            key.derive_child(ctx, child_number)
+        })
+    }
+
+    /// Hash160 of the compressed public key, identifying this key.
+    pub fn identifier(&self, ctx: &impl Context) -> Result<[u8; 20], Bip32Error> {
+        Ok(hash::hash160(&self.serialize_compressed(ctx)?))
+    }
+
+    /// The first four bytes of the identifier, which children record as their parent.
+    pub fn fingerprint(&self, ctx: &impl Context) -> Result<[u8; 4], Bip32Error> {
+        Ok(fingerprint(&self.serialize_compressed(ctx)?))
+    }
+
+    fn serialize_compressed(&self, ctx: &impl Context) -> Result<[u8; 33], Bip32Error> {
+        self.public_key
+            .serialize_compressed(ctx)
+            .map_err(|_| Bip32Error::InvalidKey)
+    }
+
+    pub fn decode(ctx: &impl Context, s: &str) -> Result<Self, Bip32Error> {
+        let data = decode_base58(s)?;
+        let (version, depth, parent_fingerprint, child_number, chain_code) = split_data(&data);
+        let network = Network::from_ext_public_key_prefix(version)
+            .ok_or(Bip32Error::UnknownVersion(version))?;
+        if data[45] != 2 && data[45] != 3 {
+            return Err(Bip32Error::InvalidKey);
+        }
+        let public_key = PublicKey::parse(ctx, &data[45..]).map_err(|_| Bip32Error::InvalidKey)?;
+
+        Ok(Self {
+            network,
+            depth,
+            parent_fingerprint,
+            child_number,
+            chain_code,
+            public_key,
+        })
+    }
+
+    /// The base58 `xpub` string.
+    pub fn encode(&self, ctx: &impl Context) -> Result<String, Bip32Error> {
+        let mut data = [0u8; EXTENDED_KEY_SIZE];
+        write_data(
+            &mut data,
+            self.network.ext_public_key_prefix(),
+            self.depth,
+            self.parent_fingerprint,
+            self.child_number,
+            self.chain_code,
+        );
+        data[45..].copy_from_slice(&self.serialize_compressed(ctx)?);
+        Ok(bs58::encode(&data[..]).with_check().into_string())
+    }
+}
+
+fn fingerprint(public_key: &[u8; 33]) -> [u8; 4] {
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&hash::hash160(public_key)[..4]);
+    fingerprint
+}
+
+/// Splits the HMAC output into the key or tweak, and the chain code.
+fn split_hmac(engine: HmacSha512) -> ([u8; 32], [u8; 32]) {
+    let mut hash = engine.finalize();
+    let mut key = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    key.copy_from_slice(&hash[..32]);
+    chain_code.copy_from_slice(&hash[32..]);
+    zero(&mut hash);
+    (key, chain_code)
+}
+
+fn decode_base58(s: &str) -> Result<Vec<u8>, Bip32Error> {
+    let mut data = bs58::decode(s)
+        .with_check(None)
+        .into_vec()
+        .map_err(|_| Bip32Error::InvalidBase58)?;
+    if data.len() != EXTENDED_KEY_SIZE {
+        let len = data.len();
+        zero(&mut data);
+        return Err(Bip32Error::InvalidLength(len));
+    }
+    Ok(data)
+}
+
+/// Splits the fields both kinds of extended key share, `data` must be `EXTENDED_KEY_SIZE` long.
+fn split_data(data: &[u8]) -> ([u8; 4], u8, [u8; 4], ChildNumber, [u8; 32]) {
+    let mut version = [0u8; 4];
+    let mut parent_fingerprint = [0u8; 4];
+    let mut index = [0u8; 4];
+    let mut chain_code = [0u8; 32];
+    version.copy_from_slice(&data[..4]);
+    parent_fingerprint.copy_from_slice(&data[5..9]);
+    index.copy_from_slice(&data[9..13]);
+    chain_code.copy_from_slice(&data[13..45]);
+    (
+        version,
+        data[4],
+        parent_fingerprint,
+        ChildNumber::from_index(u32::from_be_bytes(index)),
+        chain_code,
+    )
+}
+
+fn write_data(
+    data: &mut [u8; EXTENDED_KEY_SIZE],
+    version: [u8; 4],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: ChildNumber,
+    chain_code: [u8; 32],
+) {
+    data[..4].copy_from_slice(&version);
+    data[4] = depth;
+    data[5..9].copy_from_slice(&parent_fingerprint);
+    data[9..13].copy_from_slice(&child_number.to_index().to_be_bytes());
+    data[13..45].copy_from_slice(&chain_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libsecp256k1_veil::ContextAll;
+
+    // Test vectors 1 and 2 from BIP32, each a path with its expected xpub and xprv.
+    const VECTOR_1_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+    const VECTOR_1: &[(&str, &str, &str)] = &[
+        (
+            "m",
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+        ),
+        (
+            "m/0'",
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+            "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+        ),
+        (
+            "m/0'/1",
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+            "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+        ),
+        (
+            "m/0'/1/2'",
+            "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+            "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+        ),
+        (
+            "m/0'/1/2'/2",
+            "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+            "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+        ),
+        (
+            "m/0'/1/2'/2/1000000000",
+            "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+            "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+        ),
+    ];
+    const VECTOR_2_SEED: &str = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+    const VECTOR_2: &[(&str, &str, &str)] = &[
+        (
+            "m",
+            "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+            "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U",
+        ),
+        (
+            "m/0",
+            "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+            "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt",
+        ),
+        (
+            "m/0/2147483647'",
+            "xpub6ASAVgeehLbnwdqV6UKMHVzgqAG8Gr6riv3Fxxpj8ksbH9ebxaEyBLZ85ySDhKiLDBrQSARLq1uNRts8RuJiHjaDMBU4Zn9h8LZNnBC5y4a",
+            "xprv9wSp6B7kry3Vj9m1zSnLvN3xH8RdsPP1Mh7fAaR7aRLcQMKTR2vidYEeEg2mUCTAwCd6vnxVrcjfy2kRgVsFawNzmjuHc2YmYRmagcEPdU9",
+        ),
+        (
+            "m/0/2147483647'/1",
+            "xpub6DF8uhdarytz3FWdA8TvFSvvAh8dP3283MY7p2V4SeE2wyWmG5mg5EwVvmdMVCQcoNJxGoWaU9DCWh89LojfZ537wTfunKau47EL2dhHKon",
+            "xprv9zFnWC6h2cLgpmSA46vutJzBcfJ8yaJGg8cX1e5StJh45BBciYTRXSd25UEPVuesF9yog62tGAQtHjXajPPdbRCHuWS6T8XA2ECKADdw4Ef",
+        ),
+    ];
+
+    fn seed(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn check_vector(seed_hex: &str, vector: &[(&str, &str, &str)]) {
+        let ctx = ContextAll::new();
+        let master = ExtendedPrivKey::new_master(&ctx, Network::Main, &seed(seed_hex)).unwrap();
+
+        for &(path, xpub, xprv) in vector {
+            let path: DerivationPath = path.parse().unwrap();
+            let private = master.derive_path(&ctx, &path).unwrap();
+            let public = ExtendedPubKey::from_private(&ctx, &private).unwrap();
+            assert_eq!(private.encode(), xprv);
+            assert_eq!(public.encode(&ctx).unwrap(), xpub);
+            assert_eq!(ExtendedPrivKey::decode(&ctx, xprv).unwrap(), private);
+            assert_eq!(ExtendedPubKey::decode(&ctx, xpub).unwrap(), public);
+        }
+    }
+
+    #[test]
+    fn vector_1() {
+        check_vector(VECTOR_1_SEED, VECTOR_1);
+    }
+
+    #[test]
+    fn vector_2() {
+        check_vector(VECTOR_2_SEED, VECTOR_2);
+    }
+
+    #[test]
+    fn bip39_seed() {
+        // The seed of the `abandon ... about` mnemonic with the passphrase "TREZOR", the BIP39
+        // vectors list its master key too.
+        let seed = seed(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        );
+        let ctx = ContextAll::new();
+        assert_eq!(
+            ExtendedPrivKey::new_master(&ctx, Network::Main, &seed)
+                .unwrap()
+                .encode(),
+            "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF"
+        );
+    }
+
+    #[test]
+    fn public_derivation() {
+        let ctx = ContextAll::new();
+        let master =
+            ExtendedPrivKey::new_master(&ctx, Network::Main, &seed(VECTOR_1_SEED)).unwrap();
+        let account = master.derive_path(&ctx, &"m/0'".parse().unwrap()).unwrap();
+        let account_pub = ExtendedPubKey::from_private(&ctx, &account).unwrap();
+
+        let path: DerivationPath = "m/1/2".parse().unwrap();
+        let private = account.derive_path(&ctx, &path).unwrap();
+        let public = account_pub.derive_path(&ctx, &path).unwrap();
+        assert_eq!(
+            ExtendedPubKey::from_private(&ctx, &private).unwrap(),
+            public
+        );
+        assert_eq!(
+            public.parent_fingerprint,
+            account
+                .derive_child(&ctx, ChildNumber::Normal(1))
+                .unwrap()
+                .fingerprint(&ctx)
+                .unwrap()
+        );
+        assert_eq!(
+            account_pub.derive_child(&ctx, ChildNumber::Hardened(0)),
+            Err(Bip32Error::CannotDeriveHardenedFromPublic)
+        );
+    }
+
+    #[test]
+    fn derivation_path() {
+        let path: DerivationPath = "m/44'/0h/0'/0/1".parse().unwrap();
+        assert_eq!(
+            path.as_ref(),
+            &[
+                ChildNumber::Hardened(44),
+                ChildNumber::Hardened(0),
+                ChildNumber::Hardened(0),
+                ChildNumber::Normal(0),
+                ChildNumber::Normal(1),
+            ][..]
+        );
+        assert_eq!(path.to_string(), "m/44'/0'/0'/0/1");
+        assert_eq!("m".parse(), Ok(DerivationPath::default()));
+        assert_eq!(
+            "m/2147483648".parse::<DerivationPath>(),
+            Err(Bip32Error::InvalidChildNumber(1 << 31))
+        );
+        for invalid in &["", "44'", "m/", "m/x", "m/0''", "n/0"] {
+            assert_eq!(
+                invalid.parse::<DerivationPath>(),
+                Err(Bip32Error::InvalidDerivationPath)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let ctx = ContextAll::new();
+        let encode = |data: &[u8]| bs58::encode(data).with_check().into_string();
+        let mut data = bs58::decode(VECTOR_1[0].2)
+            .with_check(None)
+            .into_vec()
+            .unwrap();
+
+        assert_eq!(
+            ExtendedPrivKey::decode(&ctx, &encode(&data[..77])),
+            Err(Bip32Error::InvalidLength(77))
+        );
+        assert_eq!(
+            ExtendedPubKey::decode(&ctx, VECTOR_1[0].2),
+            Err(Bip32Error::UnknownVersion([0x04, 0x88, 0xad, 0xe4]))
+        );
+        data[45] = 1;
+        assert_eq!(
+            ExtendedPrivKey::decode(&ctx, &encode(&data)),
+            Err(Bip32Error::InvalidKey)
+        );
+        data[45] = 0;
+        data[46..].copy_from_slice(&[0; 32]);
+        assert_eq!(
+            ExtendedPrivKey::decode(&ctx, &encode(&data)),
+            Err(Bip32Error::InvalidKey)
+        );
+        assert_eq!(
+            ExtendedPrivKey::decode(&ctx, "xprv"),
+            Err(Bip32Error::InvalidBase58)
+        );
+    }
+}
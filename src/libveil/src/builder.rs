@@ -0,0 +1,481 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Builds unsigned transactions from spendable inputs and recipients.
+//!
+//! The builder balances the transaction, pays the fee for its signed weight and sends what is
+//! left back to a change script, unless the change would be dust.
+
+use crate::address::PubkeyHashAddress;
+use crate::amount::{self, Amount, FeeRate};
+use crate::constants::WITNESS_SCALE_FACTOR;
+use crate::policy::{self, DEFAULT_MIN_RELAY_TX_FEE, DUST_RELAY_TX_FEE};
+use crate::prelude::*;
+use crate::primitives::transaction::{OutPoint, StandardOut, Transaction, TransactionIn};
+use crate::script::{self, ScriptKind};
+use crate::serialize;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+/// Size of a DER encoded signature with its sighash byte, at most.
+const MAX_SIGNATURE_SIZE: usize = 72;
+const PUBLIC_KEY_SIZE: usize = 33;
+
+#[derive(Debug, PartialEq)]
+pub enum BuildError {
+    NoInputs,
+    NoOutputs,
+    AmountOutOfRange,
+    /// The recipient at this index would receive a dust amount.
+    DustOutput(usize),
+    InsufficientFunds {
+        available: Amount,
+        required: Amount,
+    },
+    /// The input script is not a template the signature size can be estimated for.
+    UnknownInputScript,
+    MissingChangeScript,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::NoInputs => f.write_str("transaction has no inputs"),
+            BuildError::NoOutputs => f.write_str("transaction has no outputs"),
+            BuildError::AmountOutOfRange => f.write_str("amount out of range"),
+            BuildError::DustOutput(i) => write!(f, "output {} is dust", i),
+            BuildError::InsufficientFunds {
+                available,
+                required,
+            } => write!(
+                f,
+                "insufficient funds, {} available, {} required",
+                available, required
+            ),
+            BuildError::UnknownInputScript => f.write_str("unable to estimate input size"),
+            BuildError::MissingChangeScript => {
+                f.write_str("change needed but no change script set")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for BuildError {}
+
+/// A spendable output, along with what it takes to spend it.
+#[derive(Clone, Debug)]
+pub struct Input {
+    prev_out: OutPoint,
+    amount: Amount,
+    script_pubkey: Vec<u8>,
+    sequence: Option<u32>,
+    script_sig_len: usize,
+    witness: Vec<usize>,
+}
+
+impl Input {
+    /// Spends a pay to public key, public key hash, bare multisig or witness key hash output.
+    pub fn new(
+        prev_out: OutPoint,
+        amount: Amount,
+        script_pubkey: Vec<u8>,
+    ) -> Result<Self, BuildError> {
+        let (script_sig_len, witness) = match script::solve(&script_pubkey) {
+            ScriptKind::PubKey => (1 + MAX_SIGNATURE_SIZE, Vec::new()),
+            ScriptKind::PubKeyHash => (1 + MAX_SIGNATURE_SIZE + 1 + PUBLIC_KEY_SIZE, Vec::new()),
+            ScriptKind::Multisig { required, .. } => {
+                (1 + required as usize * (1 + MAX_SIGNATURE_SIZE), Vec::new())
+            }
+            ScriptKind::WitnessV0KeyHash => (0, vec![MAX_SIGNATURE_SIZE, PUBLIC_KEY_SIZE]),
+            _ => return Err(BuildError::UnknownInputScript),
+        };
+        Ok(Self::with_satisfaction(
+            prev_out,
+            amount,
+            script_pubkey,
+            script_sig_len,
+            witness,
+        ))
+    }
+
+    /// Spends any output, given the size of its signature script and witness stack items.
+    pub fn with_satisfaction(
+        prev_out: OutPoint,
+        amount: Amount,
+        script_pubkey: Vec<u8>,
+        script_sig_len: usize,
+        witness: Vec<usize>,
+    ) -> Self {
+        Self {
+            prev_out,
+            amount,
+            script_pubkey,
+            sequence: None,
+            script_sig_len,
+            witness,
+        }
+    }
+
+    /// Sets an explicit sequence, for instance a relative lock-time.
+    pub fn set_sequence(&mut self, sequence: u32) {
+        self.sequence = Some(sequence);
+    }
+
+    pub fn prev_out(&self) -> &OutPoint {
+        &self.prev_out
+    }
+
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    pub fn script_pubkey(&self) -> &[u8] {
+        &self.script_pubkey
+    }
+
+    /// Weight the input adds to a transaction once it is signed.
+    pub fn weight(&self) -> usize {
+        let script_sig =
+            serialize::compact_size_len(self.script_sig_len as u64) + self.script_sig_len;
+        let witness = serialize::compact_size_len(self.witness.len() as u64)
+            + self
+                .witness
+                .iter()
+                .map(|len| serialize::compact_size_len(*len as u64) + len)
+                .sum::<usize>();
+        (32 + 4 + script_sig + 4) * WITNESS_SCALE_FACTOR + witness
+    }
+
+    fn to_transaction_in(&self, lock_time: u32, signed: bool) -> TransactionIn {
+        let sequence = self.sequence.unwrap_or(if lock_time == 0 {
+            TransactionIn::SEQUENCE_FINAL
+        } else {
+            // Leaves the lock time enabled.
+            TransactionIn::SEQUENCE_FINAL - 1
+        });
+
+        let mut tx_in = TransactionIn::new(self.prev_out.clone(), Vec::new(), sequence);
+        if signed {
+            tx_in.set_script(vec![0; self.script_sig_len]);
+            tx_in.set_script_witness(self.witness.iter().map(|len| vec![0; *len]).collect());
+        }
+        tx_in
+    }
+}
+
+/// An unsigned transaction and how it was balanced.
+pub struct UnsignedTransaction {
+    pub transaction: Transaction,
+    pub fee: Amount,
+    /// Index of the change output. The builder only makes standard outputs, so this is also its
+    /// index in `Transaction::standard_out`.
+    pub change_index: Option<usize>,
+}
+
+pub struct TransactionBuilder {
+    inputs: Vec<Input>,
+    outputs: Vec<StandardOut>,
+    change_script: Option<Vec<u8>>,
+    fee_rate: FeeRate,
+    dust_relay_fee: FeeRate,
+    version: u32,
+    lock_time: u32,
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            change_script: None,
+            fee_rate: FeeRate::per_kvb(DEFAULT_MIN_RELAY_TX_FEE),
+            dust_relay_fee: FeeRate::per_kvb(DUST_RELAY_TX_FEE),
+            version: Transaction::CURRENT_VERSION,
+            lock_time: 0,
+        }
+    }
+
+    pub fn add_input(&mut self, input: Input) -> &mut Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Pays `amount` to `script`, which can be any output script including null data.
+    pub fn add_recipient(&mut self, script: Vec<u8>, amount: Amount) -> &mut Self {
+        self.outputs.push(StandardOut::new(amount, script));
+        self
+    }
+
+    /// Pays `amount` to `address`.
+    pub fn add_recipient_address(
+        &mut self,
+        address: &PubkeyHashAddress,
+        amount: Amount,
+    ) -> &mut Self {
+        self.add_recipient(address.script_pubkey(), amount)
+    }
+
+    pub fn change_script(&mut self, script: Vec<u8>) -> &mut Self {
+        self.change_script = Some(script);
+        self
+    }
+
+    /// Fee rate to pay, defaults to the minimum relay fee.
+    pub fn fee_rate(&mut self, fee_rate: FeeRate) -> &mut Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Fee rate used to decide whether an output is dust.
+    pub fn dust_relay_fee(&mut self, dust_relay_fee: FeeRate) -> &mut Self {
+        self.dust_relay_fee = dust_relay_fee;
+        self
+    }
+
+    pub fn version(&mut self, version: u32) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the lock time. Inputs without an explicit sequence then get one that enables it.
+    pub fn lock_time(&mut self, lock_time: u32) -> &mut Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Balances the transaction and returns it without signatures.
+    pub fn build(&self) -> Result<UnsignedTransaction, BuildError> {
+        if self.inputs.is_empty() {
+            return Err(BuildError::NoInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(BuildError::NoOutputs);
+        }
+
+        let available = sum(self.inputs.iter().map(|input| input.amount))?;
+        let sent = sum(self.outputs.iter().map(|out| *out.amount()))?;
+        for (i, out) in self.outputs.iter().enumerate() {
+            if !script::is_unspendable(out.script()) && policy::is_dust(out, &self.dust_relay_fee) {
+                return Err(BuildError::DustOutput(i));
+            }
+        }
+
+        let fee = self.fee_rate.fee(self.transaction(None, true).vsize());
+        let required = sent + fee;
+        if available < required {
+            return Err(BuildError::InsufficientFunds {
+                available,
+                required,
+            });
+        }
+
+        let change = StandardOut::new(0, self.change_script.clone().unwrap_or_default());
+        let fee_with_change = self
+            .fee_rate
+            .fee(self.transaction(Some(&change), true).vsize());
+        let change_amount = available - sent - fee_with_change;
+        let change = StandardOut::new(change_amount, change.script().to_vec());
+        if change_amount <= 0 || policy::is_dust(&change, &self.dust_relay_fee) {
+            // Not worth an output, the excess goes to the fee.
+            return Ok(UnsignedTransaction {
+                transaction: self.transaction(None, false),
+                fee: available - sent,
+                change_index: None,
+            });
+        }
+        if self.change_script.is_none() {
+            return Err(BuildError::MissingChangeScript);
+        }
+
+        Ok(UnsignedTransaction {
+            transaction: self.transaction(Some(&change), false),
+            fee: fee_with_change,
+            change_index: Some(self.outputs.len()),
+        })
+    }
+
+    /// Assembles the transaction, with placeholder signatures when `signed` is set.
+    fn transaction(&self, change: Option<&StandardOut>, signed: bool) -> Transaction {
+        let mut tx = Transaction::new();
+        tx.set_version(self.version);
+        tx.set_lock_time(self.lock_time);
+        for input in &self.inputs {
+            tx.push_transaction_in(input.to_transaction_in(self.lock_time, signed));
+        }
+        for out in self.outputs.iter().chain(change) {
+            tx.push_standard_out(StandardOut::new(*out.amount(), out.script().to_vec()));
+        }
+        tx
+    }
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn sum(amounts: impl Iterator<Item = Amount>) -> Result<Amount, BuildError> {
+    let mut total: Amount = 0;
+    for value in amounts {
+        total += value;
+        if !amount::money_range(&value) || !amount::money_range(&total) {
+            return Err(BuildError::AmountOutOfRange);
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use crate::amount::COIN;
+    use crate::u256;
+
+    fn p2pkh(n: u8) -> Vec<u8> {
+        let mut script = vec![0x76, 0xa9, 20];
+        script.extend_from_slice(&[n; 20]);
+        script.extend_from_slice(&[0x88, 0xac]);
+        script
+    }
+
+    fn p2wpkh(n: u8) -> Vec<u8> {
+        let mut script = vec![0x00, 20];
+        script.extend_from_slice(&[n; 20]);
+        script
+    }
+
+    fn input(n: u64, amount: Amount, script: Vec<u8>) -> Input {
+        Input::new(OutPoint::new(u256([n, 0, 0, 0]), 0), amount, script).unwrap()
+    }
+
+    #[test]
+    fn change() {
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2pkh(1)))
+            .add_input(input(2, COIN, p2wpkh(2)))
+            .add_recipient(p2pkh(3), COIN + COIN / 2)
+            .change_script(p2wpkh(4))
+            .fee_rate(FeeRate::per_kvb(10_000));
+        let unsigned = builder.build().unwrap();
+
+        let tx = &unsigned.transaction;
+        let change = &tx.standard_out().nth(unsigned.change_index.unwrap()).unwrap();
+        assert_eq!(*change.amount() + unsigned.fee + COIN + COIN / 2, 2 * COIN);
+        assert!(
+            tx.transaction_in()
+                .iter()
+                .all(|tx_in| tx_in.script().is_empty())
+        );
+
+        let signed = builder.transaction(Some(change), true);
+        assert_eq!(unsigned.fee, FeeRate::per_kvb(10_000).fee(signed.vsize()));
+    }
+
+    #[test]
+    fn input_weight() {
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2wpkh(1)))
+            .add_recipient(p2pkh(2), COIN);
+        let unsigned_input = (32 + 4 + 1 + 4) * WITNESS_SCALE_FACTOR + 1;
+        assert_eq!(
+            builder.transaction(None, true).weight() - builder.transaction(None, false).weight(),
+            builder.inputs[0].weight() - unsigned_input
+        );
+    }
+
+    #[test]
+    fn recipient_address() {
+        let address = PubkeyHashAddress {
+            network: Network::Main,
+            hash: [3; 20],
+        };
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2pkh(1)))
+            .add_recipient_address(&address, COIN / 2)
+            .change_script(p2wpkh(4));
+        let tx = builder.build().unwrap().transaction;
+        assert_eq!(tx.standard_out().next().unwrap().script(), &p2pkh(3)[..]);
+    }
+
+    #[test]
+    fn dust_change_goes_to_fee() {
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2pkh(1)))
+            .add_recipient(p2pkh(2), COIN - 500);
+        let unsigned = builder.build().unwrap();
+        assert_eq!(unsigned.change_index, None);
+        assert_eq!(unsigned.fee, 500);
+    }
+
+    #[test]
+    fn errors() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(input(1, COIN, p2pkh(1)));
+        assert_eq!(builder.build().err(), Some(BuildError::NoOutputs));
+
+        builder.add_recipient(p2pkh(2), COIN);
+        assert!(matches!(
+            builder.build(),
+            Err(BuildError::InsufficientFunds { .. })
+        ));
+
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2pkh(1)))
+            .add_recipient(p2pkh(2), COIN / 2);
+        assert_eq!(builder.build().err(), Some(BuildError::MissingChangeScript));
+
+        builder.add_recipient(p2pkh(2), 100);
+        assert_eq!(builder.build().err(), Some(BuildError::DustOutput(1)));
+    }
+
+    #[test]
+    fn lock_time_sequence() {
+        let mut relative = input(2, COIN, p2pkh(1));
+        relative.set_sequence(10);
+
+        let mut builder = TransactionBuilder::new();
+        builder
+            .add_input(input(1, COIN, p2pkh(1)))
+            .add_input(relative)
+            .add_recipient(p2pkh(2), COIN)
+            .change_script(p2pkh(3))
+            .lock_time(500);
+        let tx = builder.build().unwrap().transaction;
+        assert_eq!(*tx.lock_time(), 500);
+        assert_eq!(
+            tx.transaction_in()[0].sequence(),
+            TransactionIn::SEQUENCE_FINAL - 1
+        );
+        assert_eq!(tx.transaction_in()[1].sequence(), 10);
+    }
+}
@@ -0,0 +1,570 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Coin selection.
+//!
+//! Selects which of the candidate outputs fund a transaction. Every strategy works on effective
+//! values, the value of an output minus the fee to spend it, and covers a target which is the
+//! amount sent plus the fee for the rest of the transaction.
+
+use crate::amount::{Amount, CENT, FeeRate};
+use crate::builder::Input;
+use crate::constants::WITNESS_SCALE_FACTOR;
+use crate::policy::DEFAULT_MIN_RELAY_TX_FEE;
+use crate::prelude::*;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Number of branches branch and bound explores before giving up.
+const BNB_TOTAL_TRIES: usize = 100_000;
+
+/// Number of random subsets the knapsack solver tries.
+const KNAPSACK_ITERATIONS: usize = 1000;
+
+/// Change the knapsack solver aims for when it can not match the target exactly.
+const MIN_CHANGE: Amount = CENT;
+
+/// An output that can be selected, transparent or CT.
+///
+/// For CT outputs, the value is the amount the wallet unblinded.
+pub trait Candidate {
+    fn value(&self) -> Amount;
+
+    /// Weight the output adds to a transaction when spent.
+    fn weight(&self) -> usize;
+}
+
+impl Candidate for Input {
+    fn value(&self) -> Amount {
+        self.amount()
+    }
+
+    fn weight(&self) -> usize {
+        Input::weight(self)
+    }
+}
+
+pub struct Params {
+    pub fee_rate: FeeRate,
+    /// Fee rate expected to be paid in the long run, used to decide whether it is cheaper to
+    /// spend outputs now or later.
+    pub long_term_fee_rate: FeeRate,
+    /// Change below this amount is added to the fee instead.
+    pub dust_limit: Amount,
+    /// Weight of the change output.
+    pub change_output_weight: usize,
+    /// Weight of the input that spends the change later on.
+    pub change_spend_weight: usize,
+}
+
+impl Default for Params {
+    /// Minimum relay fee rates, with witness key hash change.
+    fn default() -> Self {
+        Self {
+            fee_rate: FeeRate::per_kvb(DEFAULT_MIN_RELAY_TX_FEE),
+            long_term_fee_rate: FeeRate::per_kvb(DEFAULT_MIN_RELAY_TX_FEE),
+            dust_limit: 546,
+            change_output_weight: (1 + 8 + 1 + 22) * WITNESS_SCALE_FACTOR,
+            change_spend_weight: (32 + 4 + 1 + 4) * WITNESS_SCALE_FACTOR + 1 + 1 + 72 + 1 + 33,
+        }
+    }
+}
+
+impl Params {
+    /// Fee for adding a change output now and spending it later.
+    pub fn cost_of_change(&self) -> Amount {
+        fee(&self.fee_rate, self.change_output_weight)
+            + fee(&self.long_term_fee_rate, self.change_spend_weight)
+    }
+}
+
+/// The outcome of a coin selection.
+#[derive(Debug, PartialEq)]
+pub struct Selection {
+    /// Indices of the selected candidates.
+    pub selected: Vec<usize>,
+    /// Change amount, zero if there is no change output.
+    pub change: Amount,
+    /// Fee paid for the inputs and the change output, including any excess dropped to fees.
+    pub fee: Amount,
+    /// How much more this selection costs than spending the same outputs at the long term fee
+    /// rate and without excess. Lower is better.
+    pub waste: Amount,
+}
+
+/// Picks the selection with the lowest waste out of branch and bound, knapsack and single
+/// random draw.
+pub fn select_coins<C: Candidate, R: Rng + ?Sized>(
+    pool: &[C],
+    target: Amount,
+    params: &Params,
+    rng: &mut R,
+) -> Option<Selection> {
+    let mut best: Option<Selection> = None;
+    let results = vec![
+        branch_and_bound(pool, target, params),
+        knapsack(pool, target, params, rng),
+        single_random_draw(pool, target, params, rng),
+    ];
+    for selection in results.into_iter().flatten() {
+        let better = match &best {
+            Some(best) => {
+                selection.waste < best.waste
+                    || (selection.waste == best.waste
+                        && selection.selected.len() > best.selected.len())
+            }
+            None => true,
+        };
+        if better {
+            best = Some(selection);
+        }
+    }
+    best
+}
+
+/// Searches for a selection that needs no change output, within the cost of change of the
+/// target, with the least waste.
+pub fn branch_and_bound<C: Candidate>(
+    pool: &[C],
+    target: Amount,
+    params: &Params,
+) -> Option<Selection> {
+    let mut utxos: Vec<usize> = (0..pool.len())
+        .filter(|i| effective_value(&pool[*i], params) > 0)
+        .collect();
+    utxos.sort_by_key(|i| core::cmp::Reverse(effective_value(&pool[*i], params)));
+    let values: Vec<Amount> = utxos
+        .iter()
+        .map(|i| effective_value(&pool[*i], params))
+        .collect();
+    let waste: Vec<Amount> = utxos
+        .iter()
+        .map(|i| input_waste(&pool[*i], params))
+        .collect();
+
+    let mut available: Amount = values.iter().sum();
+    if available < target {
+        return None;
+    }
+
+    let cost_of_change = params.cost_of_change();
+    let fee_rate_high = params.fee_rate > params.long_term_fee_rate;
+    let mut value: Amount = 0;
+    let mut current_waste: Amount = 0;
+    let mut selection: Vec<usize> = Vec::new();
+    let mut best: Option<Vec<usize>> = None;
+    let mut best_waste = Amount::MAX;
+
+    let mut index = 0;
+    for _ in 0..BNB_TOTAL_TRIES {
+        let mut backtrack = false;
+        if value + available < target
+            || value > target + cost_of_change
+            || (current_waste > best_waste && fee_rate_high)
+        {
+            backtrack = true;
+        } else if value >= target {
+            // Whatever exceeds the target goes to the fee.
+            let total_waste = current_waste + value - target;
+            if total_waste <= best_waste {
+                best = Some(selection.clone());
+                best_waste = total_waste;
+            }
+            backtrack = true;
+        }
+
+        if backtrack {
+            let last = match selection.pop() {
+                Some(last) => last,
+                None => break,
+            };
+            // Puts the skipped outputs back before trying the branch without `last`.
+            while index > last + 1 {
+                index -= 1;
+                available += values[index];
+            }
+            index = last;
+            value -= values[last];
+            current_waste -= waste[last];
+        } else {
+            available -= values[index];
+            // Including this output would repeat the branch where an equivalent previous output
+            // was excluded.
+            let duplicate = index > 0
+                && selection.last() != Some(&(index - 1))
+                && values[index] == values[index - 1]
+                && waste[index] == waste[index - 1];
+            if !duplicate {
+                selection.push(index);
+                value += values[index];
+                current_waste += waste[index];
+            }
+        }
+        index += 1;
+    }
+
+    let selected = best?.into_iter().map(|i| utxos[i]).collect();
+    Some(finish(pool, selected, target, params, false))
+}
+
+/// Bitcoin Core's knapsack solver, aiming for an exact match or else for at least
+/// `MIN_CHANGE` of change.
+pub fn knapsack<C: Candidate, R: Rng + ?Sized>(
+    pool: &[C],
+    target: Amount,
+    params: &Params,
+    rng: &mut R,
+) -> Option<Selection> {
+    let mut order: Vec<usize> = (0..pool.len()).collect();
+    order.shuffle(rng);
+
+    let mut applicable = Vec::new();
+    let mut total_lower: Amount = 0;
+    let mut lowest_larger: Option<(usize, Amount)> = None;
+    for i in order {
+        let value = effective_value(&pool[i], params);
+        if value <= 0 {
+            continue;
+        }
+        if value == target {
+            return Some(finish(pool, vec![i], target, params, true));
+        } else if value < target + MIN_CHANGE {
+            applicable.push((i, value));
+            total_lower += value;
+        } else if lowest_larger.map_or(true, |(_, larger)| value < larger) {
+            lowest_larger = Some((i, value));
+        }
+    }
+
+    if total_lower == target {
+        let selected = applicable.iter().map(|(i, _)| *i).collect();
+        return Some(finish(pool, selected, target, params, true));
+    }
+    if total_lower < target {
+        let (i, _) = lowest_larger?;
+        return Some(finish(pool, vec![i], target, params, true));
+    }
+
+    applicable.sort_by_key(|(_, value)| core::cmp::Reverse(*value));
+    let values: Vec<Amount> = applicable.iter().map(|(_, value)| *value).collect();
+    let (mut best, mut best_value) = approximate_best_subset(&values, total_lower, target, rng);
+    if best_value != target && total_lower >= target + MIN_CHANGE {
+        let (subset, subset_value) =
+            approximate_best_subset(&values, total_lower, target + MIN_CHANGE, rng);
+        best = subset;
+        best_value = subset_value;
+    }
+
+    if let Some((i, larger)) = lowest_larger {
+        if (best_value != target && best_value < target + MIN_CHANGE) || larger <= best_value {
+            return Some(finish(pool, vec![i], target, params, true));
+        }
+    }
+
+    let selected = applicable
+        .iter()
+        .zip(best)
+        .filter(|(_, included)| *included)
+        .map(|((i, _), _)| *i)
+        .collect();
+    Some(finish(pool, selected, target, params, true))
+}
+
+/// Selects the outputs with the largest effective values until the target is covered.
+pub fn largest_first<C: Candidate>(
+    pool: &[C],
+    target: Amount,
+    params: &Params,
+) -> Option<Selection> {
+    let mut order: Vec<usize> = (0..pool.len()).collect();
+    order.sort_by_key(|i| core::cmp::Reverse(effective_value(&pool[*i], params)));
+    select_in_order(pool, order, target, target, params)
+}
+
+/// Selects random outputs until the target is covered with enough left for change.
+pub fn single_random_draw<C: Candidate, R: Rng + ?Sized>(
+    pool: &[C],
+    target: Amount,
+    params: &Params,
+    rng: &mut R,
+) -> Option<Selection> {
+    let mut order: Vec<usize> = (0..pool.len()).collect();
+    order.shuffle(rng);
+    let required = target + fee(&params.fee_rate, params.change_output_weight) + params.dust_limit;
+    select_in_order(pool, order, target, required, params)
+}
+
+fn select_in_order<C: Candidate>(
+    pool: &[C],
+    order: Vec<usize>,
+    target: Amount,
+    required: Amount,
+    params: &Params,
+) -> Option<Selection> {
+    let mut selected = Vec::new();
+    let mut total: Amount = 0;
+    for i in order {
+        let value = effective_value(&pool[i], params);
+        if value <= 0 {
+            continue;
+        }
+        selected.push(i);
+        total += value;
+        if total >= required {
+            return Some(finish(pool, selected, target, params, true));
+        }
+    }
+    None
+}
+
+/// Returns the subset of `values` closest to, but not below, `target`.
+fn approximate_best_subset<R: Rng + ?Sized>(
+    values: &[Amount],
+    total_lower: Amount,
+    target: Amount,
+    rng: &mut R,
+) -> (Vec<bool>, Amount) {
+    let mut best = vec![true; values.len()];
+    let mut best_value = total_lower;
+
+    for _ in 0..KNAPSACK_ITERATIONS {
+        if best_value == target {
+            break;
+        }
+        let mut included = vec![false; values.len()];
+        let mut total: Amount = 0;
+        let mut reached = false;
+        for pass in 0..2 {
+            if reached {
+                break;
+            }
+            for (i, value) in values.iter().enumerate() {
+                // The first pass picks randomly, the second adds whatever was left out.
+                let pick = if pass == 0 {
+                    rng.gen_bool(0.5)
+                } else {
+                    !included[i]
+                };
+                if !pick {
+                    continue;
+                }
+                total += value;
+                included[i] = true;
+                if total >= target {
+                    reached = true;
+                    if total < best_value {
+                        best_value = total;
+                        best = included.clone();
+                    }
+                    total -= value;
+                    included[i] = false;
+                }
+            }
+        }
+    }
+
+    (best, best_value)
+}
+
+fn finish<C: Candidate>(
+    pool: &[C],
+    selected: Vec<usize>,
+    target: Amount,
+    params: &Params,
+    allow_change: bool,
+) -> Selection {
+    let value: Amount = selected
+        .iter()
+        .map(|i| effective_value(&pool[*i], params))
+        .sum();
+    let input_fee: Amount = selected
+        .iter()
+        .map(|i| fee(&params.fee_rate, pool[*i].weight()))
+        .sum();
+    let input_waste: Amount = selected
+        .iter()
+        .map(|i| input_waste(&pool[*i], params))
+        .sum();
+
+    let excess = value - target;
+    let change = excess - fee(&params.fee_rate, params.change_output_weight);
+    if allow_change && change >= params.dust_limit {
+        Selection {
+            selected,
+            change,
+            fee: input_fee + excess - change,
+            waste: input_waste + params.cost_of_change(),
+        }
+    } else {
+        Selection {
+            selected,
+            change: 0,
+            fee: input_fee + excess,
+            waste: input_waste + excess,
+        }
+    }
+}
+
+fn fee(fee_rate: &FeeRate, weight: usize) -> Amount {
+    fee_rate.fee(weight.div_ceil(WITNESS_SCALE_FACTOR))
+}
+
+fn effective_value<C: Candidate>(candidate: &C, params: &Params) -> Amount {
+    candidate.value() - fee(&params.fee_rate, candidate.weight())
+}
+
+fn input_waste<C: Candidate>(candidate: &C, params: &Params) -> Amount {
+    fee(&params.fee_rate, candidate.weight()) - fee(&params.long_term_fee_rate, candidate.weight())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::COIN;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    struct Coin(Amount);
+
+    impl Candidate for Coin {
+        fn value(&self) -> Amount {
+            self.0
+        }
+
+        fn weight(&self) -> usize {
+            272
+        }
+    }
+
+    fn pool(values: &[Amount]) -> Vec<Coin> {
+        values.iter().map(|value| Coin(*value)).collect()
+    }
+
+    fn free() -> Params {
+        Params {
+            fee_rate: FeeRate(0),
+            long_term_fee_rate: FeeRate(0),
+            ..Params::default()
+        }
+    }
+
+    fn sorted(mut selected: Vec<usize>) -> Vec<usize> {
+        selected.sort();
+        selected
+    }
+
+    #[test]
+    fn branch_and_bound_exact() {
+        let pool = pool(&[COIN, 2 * COIN, 3 * COIN, 4 * COIN, 5 * COIN]);
+        let selection = branch_and_bound(&pool, 10 * COIN, &free()).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, 0);
+        assert_eq!(selection.waste, 0);
+        let total: Amount = selection.selected.iter().map(|i| pool[*i].0).sum();
+        assert_eq!(total, 10 * COIN);
+
+        assert_eq!(branch_and_bound(&pool, 16 * COIN, &free()), None);
+        assert_eq!(branch_and_bound(&pool, COIN / 2, &free()), None);
+    }
+
+    #[test]
+    fn branch_and_bound_fees() {
+        let params = Params {
+            fee_rate: FeeRate(10_000),
+            long_term_fee_rate: FeeRate(5_000),
+            ..Params::default()
+        };
+        // Each input costs 680 at the current rate and wastes 340.
+        let pool = pool(&[COIN + 680, COIN + 680, 2 * COIN + 680]);
+        let selection = branch_and_bound(&pool, 2 * COIN, &params).unwrap();
+        assert_eq!(selection.selected, vec![2]);
+        assert_eq!(selection.fee, 680);
+        assert_eq!(selection.waste, 340);
+    }
+
+    #[test]
+    fn knapsack_deterministic() {
+        let values: Vec<Amount> = (1..30).map(|n| n * 7_919_000).collect();
+        let pool = pool(&values);
+        let params = Params::default();
+
+        let a = knapsack(
+            &pool,
+            50 * 7_919_000 + 1,
+            &params,
+            &mut StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+        let b = knapsack(
+            &pool,
+            50 * 7_919_000 + 1,
+            &params,
+            &mut StdRng::seed_from_u64(7),
+        )
+        .unwrap();
+        assert_eq!(a, b);
+        let total: Amount = a.selected.iter().map(|i| pool[*i].0).sum();
+        assert_eq!(total, 50 * 7_919_000 + 1 + a.fee + a.change);
+
+        let exact = knapsack(&pool, 3 * 7_919_000, &free(), &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(exact.change, 0);
+        assert_eq!(exact.selected.len(), 1);
+    }
+
+    #[test]
+    fn largest_first_selects_largest() {
+        let pool = pool(&[COIN, 5 * COIN, 3 * COIN]);
+        let selection = largest_first(&pool, 7 * COIN, &free()).unwrap();
+        assert_eq!(sorted(selection.selected), vec![1, 2]);
+        assert_eq!(selection.change, COIN);
+        assert_eq!(largest_first(&pool, 10 * COIN, &free()), None);
+    }
+
+    #[test]
+    fn single_random_draw_change() {
+        let pool = pool(&[COIN, 2 * COIN, 3 * COIN, 4 * COIN]);
+        let params = Params::default();
+        let selection =
+            single_random_draw(&pool, 5 * COIN, &params, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(selection.change >= params.dust_limit);
+        assert_eq!(
+            selection,
+            single_random_draw(&pool, 5 * COIN, &params, &mut StdRng::seed_from_u64(1)).unwrap()
+        );
+    }
+
+    #[test]
+    fn select_coins_prefers_changeless() {
+        let pool = pool(&[COIN, 2 * COIN, 3 * COIN, 4 * COIN, 5 * COIN]);
+        let params = Params {
+            long_term_fee_rate: FeeRate(10_000),
+            ..Params::default()
+        };
+        let target = 6 * COIN - 2 * 68;
+        let selection =
+            select_coins(&pool, target, &params, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(selection.change, 0);
+        assert!(selection.waste < 0);
+    }
+}
@@ -24,3 +24,6 @@
 
 pub const TRANSACTION_CURRENT_VERSION: i32 = 2;
 pub const TRANSACTION_MAX_VERSION: i32 = 2;
+
+/// Witness data is discounted by this factor when computing transaction weight.
+pub const WITNESS_SCALE_FACTOR: usize = 4;
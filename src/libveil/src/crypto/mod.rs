@@ -22,4 +22,7 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-pub mod sha256;
+//! Cryptographic primitives from the `crypto` crate, so users of this crate need not depend on it
+//! directly.
+
+pub use ::crypto::hash;
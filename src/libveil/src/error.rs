@@ -22,13 +22,19 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-use std::{error, fmt, io, net, num};
+use core::{fmt, num};
+#[cfg(feature = "std")]
+use std::{error, io, net};
+
+use crate::prelude::*;
 
 #[derive(Debug)]
 pub enum ErrorKind {
+    #[cfg(feature = "std")]
     Io(io::Error),
     ParseInt(num::ParseIntError),
     ParseFloat(num::ParseFloatError),
+    #[cfg(feature = "std")]
     AddrParse(net::AddrParseError),
 }
 
@@ -48,14 +54,17 @@ impl Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self.0 {
+            #[cfg(feature = "std")]
             ErrorKind::Io(ref e) => e.fmt(f),
             ErrorKind::ParseInt(ref e) => e.fmt(f),
             ErrorKind::ParseFloat(ref e) => e.fmt(f),
+            #[cfg(feature = "std")]
             ErrorKind::AddrParse(ref e) => e.fmt(f),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self.0 {
@@ -67,6 +76,7 @@ impl error::Error for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         Error::new(ErrorKind::Io(e))
@@ -85,7 +95,8 @@ impl From<num::ParseFloatError> for Error {
     }
 }
 
-impl From<std::net::AddrParseError> for Error {
+#[cfg(feature = "std")]
+impl From<net::AddrParseError> for Error {
     fn from(e: net::AddrParseError) -> Self {
         Error::new(ErrorKind::AddrParse(e))
     }
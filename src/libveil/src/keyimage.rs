@@ -22,14 +22,35 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-use std::borrow;
-use std::cmp;
-use std::convert;
-use std::fmt;
-use std::slice;
+use core::borrow;
+use core::cmp;
+use core::fmt;
+use core::slice;
+use core::str;
+#[cfg(feature = "serde")]
+use libsecp256k1_veil::serde_util::{BytesVisitor, FromStrVisitor};
+use libsecp256k1_veil::{Context, ContextNone, Error, ErrorKind, PublicKey, hex};
 
 pub struct KeyImage(pub [u8; 33]);
 
+impl KeyImage {
+    /// Parses a key image, checking it is a compressed point on the curve.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != 33 {
+            return Err(Error::new(ErrorKind::InvalidPublicKey));
+        }
+
+        let mut ctx = ContextNone::new();
+        let point = PublicKey::parse(&ctx, data);
+        ctx.destroy();
+        point?;
+
+        let mut bytes = [0u8; 33];
+        bytes.copy_from_slice(data);
+        Ok(Self(bytes))
+    }
+}
+
 impl AsRef<[u8]> for KeyImage {
     fn as_ref(&self) -> &[u8] {
         &self.0[..]
@@ -54,6 +75,12 @@ impl borrow::BorrowMut<[u8]> for KeyImage {
     }
 }
 
+impl From<[u8; 33]> for KeyImage {
+    fn from(bytes: [u8; 33]) -> Self {
+        Self(bytes)
+    }
+}
+
 // TODO: TryFrom
 //impl convert::TryFrom<&[u8]> for KeyImage {
 //    type Error = ();
@@ -70,6 +97,48 @@ impl fmt::Debug for KeyImage {
     }
 }
 
+impl fmt::Display for KeyImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl str::FromStr for KeyImage {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut buf = [0u8; 33];
+        match hex::decode(s, &mut buf) {
+            Some(bytes) => Self::from_slice(bytes),
+            None => Err(Error::new(ErrorKind::InvalidPublicKey)),
+        }
+    }
+}
+
+/// Hex in human readable formats, otherwise the 33 bytes.
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyImage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+/// Rejects key images that are not points on the curve.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyImage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FromStrVisitor::new("a hex encoded key image"))
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor::new("a key image", KeyImage::from_slice))
+        }
+    }
+}
+
 impl<'a> IntoIterator for &'a KeyImage {
     type Item = &'a u8;
     type IntoIter = slice::Iter<'a, u8>;
@@ -154,3 +223,55 @@ impl Ord for KeyImage {
         Ord::cmp(&&self.0[..], &&other.0[..])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use libsecp256k1_veil::{ContextSign, PrivateKey};
+
+    fn key_image() -> KeyImage {
+        let ctx = ContextSign::new();
+        let private_key = PrivateKey::from_slice(&ctx, &[1; 32]).unwrap();
+        let point = PublicKey::new(&ctx, &private_key).unwrap();
+        KeyImage(point.serialize_compressed(&ctx).unwrap())
+    }
+
+    #[test]
+    fn parse() {
+        let key_image = key_image();
+        let hex = key_image.to_string();
+        assert_eq!(hex.len(), 66);
+        assert_eq!(hex.parse::<KeyImage>().unwrap(), key_image);
+        assert_eq!(KeyImage::from_slice(&key_image.0).unwrap(), key_image);
+
+        let mut off_curve = key_image.0;
+        off_curve[1..].copy_from_slice(&[0; 32]);
+        assert!(KeyImage::from_slice(&off_curve).is_err());
+        assert!(KeyImage::from_slice(&key_image.0[..32]).is_err());
+        assert!(hex[..64].parse::<KeyImage>().is_err());
+        assert!(hex.replace(&hex[..2], "zz").parse::<KeyImage>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let key_image = key_image();
+        let json = serde_json::to_string(&key_image).unwrap();
+        assert_eq!(json, format!("\"{}\"", key_image));
+        assert_eq!(serde_json::from_str::<KeyImage>(&json).unwrap(), key_image);
+
+        let binary = bincode::serialize(&key_image).unwrap();
+        assert_eq!(&binary[8..], &key_image.0[..]);
+        assert_eq!(
+            bincode::deserialize::<KeyImage>(&binary).unwrap(),
+            key_image
+        );
+
+        let off_curve = format!("\"02{}\"", "00".repeat(32));
+        assert!(serde_json::from_str::<KeyImage>(&off_curve).is_err());
+        let mut binary = binary;
+        binary[9..].copy_from_slice(&[0; 32]);
+        assert!(bincode::deserialize::<KeyImage>(&binary).is_err());
+    }
+}
@@ -22,20 +22,55 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
+//! Veil primitives, script, amounts and serialization.
+//!
+//! The crate is `no_std` with the `alloc` feature, for signing inside hardware wallets. The
+//! default `std` feature adds the standard error impls and the data directory `paths`.
+
+#![no_std]
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("veil requires either the `std` or the `alloc` feature");
+
+#[macro_use]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+pub mod address;
 pub mod amount;
 mod bigint_u256;
+pub mod bip32;
+pub mod builder;
+pub mod coin_selection;
 pub mod constants;
 pub mod crypto;
 pub mod error;
 pub mod keyimage;
+pub mod message;
+#[cfg(feature = "std")]
 pub mod paths;
+pub mod policy;
 pub mod primitives;
 pub mod script;
+pub mod serialize;
+pub mod stealth;
+pub mod subsidy;
 pub mod types;
 pub mod util;
+pub mod wif;
+
+/// The `alloc` types the std prelude would otherwise bring into scope.
+mod prelude {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::String;
+    #[cfg(test)]
+    pub use alloc::string::ToString;
+    pub use alloc::vec::Vec;
+}
 
 pub use amount::Amount;
 pub use bigint_u256::u256;
 pub use keyimage::KeyImage;
 
-pub type Result<T, E = error::Error> = std::result::Result<T, E>;
+pub type Result<T, E = error::Error> = core::result::Result<T, E>;
@@ -0,0 +1,244 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Signed messages compatible with veild's `signmessage` and `verifymessage`.
+//!
+//! The message is hashed behind a magic prefix and signed with a recoverable signature, so the
+//! signature alone shows which address signed it.
+
+use crate::address::{AddressError, PubkeyHashAddress};
+use crate::crypto::hash;
+use crate::prelude::*;
+use crate::serialize;
+use core::fmt;
+use libsecp256k1_veil::{Message, PrivateKey, RecoverableSignature, Signing, Verification};
+#[cfg(feature = "std")]
+use std::error;
+
+/// Prefix hashed in front of every message, so a signed message can not pass for a transaction.
+pub const MESSAGE_MAGIC: &str = "Veil Signed Message:\n";
+
+/// Length of a compact signature with its header byte.
+const SIGNATURE_SIZE: usize = 65;
+
+#[derive(Debug)]
+pub enum MessageError {
+    InvalidBase64,
+    InvalidSignatureLength(usize),
+    /// The first byte of the signature is not a recovery header.
+    InvalidHeader(u8),
+    InvalidAddress(AddressError),
+    Secp256k1(libsecp256k1_veil::Error),
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageError::InvalidBase64 => f.write_str("malformed base64 encoding"),
+            MessageError::InvalidSignatureLength(len) => {
+                write!(f, "invalid signature length {}", len)
+            }
+            MessageError::InvalidHeader(header) => {
+                write!(f, "invalid signature header {}", header)
+            }
+            MessageError::InvalidAddress(e) => e.fmt(f),
+            MessageError::Secp256k1(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for MessageError {}
+
+impl From<AddressError> for MessageError {
+    fn from(e: AddressError) -> Self {
+        MessageError::InvalidAddress(e)
+    }
+}
+
+impl From<libsecp256k1_veil::Error> for MessageError {
+    fn from(e: libsecp256k1_veil::Error) -> Self {
+        MessageError::Secp256k1(e)
+    }
+}
+
+/// The hash veild signs for `message`.
+pub fn message_hash(message: &str) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(MESSAGE_MAGIC.len() + message.len() + 10);
+    serialize::write_var_bytes(&mut buf, MESSAGE_MAGIC.as_bytes());
+    serialize::write_var_bytes(&mut buf, message.as_bytes());
+    hash::sha256d(&buf)
+}
+
+/// Signs `message`, returning the base64 signature `signmessage` gives. `compressed` picks
+/// which of the key's two addresses the signature verifies against.
+pub fn sign_message(
+    ctx: &impl Signing,
+    private_key: &PrivateKey,
+    compressed: bool,
+    message: &str,
+) -> Result<String, MessageError> {
+    let msg = Message::new(&message_hash(message));
+    let sig = RecoverableSignature::sign(ctx, &msg, private_key)?;
+    let (recovery_id, compact) = sig.serialize_compact(ctx);
+
+    let mut bytes = [0u8; SIGNATURE_SIZE];
+    bytes[0] = 27 + recovery_id as u8 + if compressed { 4 } else { 0 };
+    bytes[1..].copy_from_slice(&compact);
+    Ok(base64::encode(&bytes[..]))
+}
+
+/// Checks `signature` was made over `message` by the key of `address`, like `verifymessage`.
+///
+/// Returns `false` when the signature is well formed but made by another key or over another
+/// message.
+pub fn verify_message(
+    ctx: &impl Verification,
+    address: &str,
+    signature: &str,
+    message: &str,
+) -> Result<bool, MessageError> {
+    let address: PubkeyHashAddress = address.parse()?;
+    let bytes = base64::decode(signature).map_err(|_| MessageError::InvalidBase64)?;
+    if bytes.len() != SIGNATURE_SIZE {
+        return Err(MessageError::InvalidSignatureLength(bytes.len()));
+    }
+    let header = bytes[0];
+    if !(27..35).contains(&header) {
+        return Err(MessageError::InvalidHeader(header));
+    }
+    let recovery_id = i32::from((header - 27) & 3);
+    let compressed = (header - 27) & 4 != 0;
+
+    let mut compact = [0u8; 64];
+    compact.copy_from_slice(&bytes[1..]);
+    let sig = match RecoverableSignature::parse_compact(ctx, &compact, recovery_id) {
+        Ok(sig) => sig,
+        Err(_) => return Ok(false),
+    };
+    let public_key = match sig.recover(ctx, &Message::new(&message_hash(message))) {
+        Ok(public_key) => public_key,
+        Err(_) => return Ok(false),
+    };
+
+    let signer = if compressed {
+        PubkeyHashAddress::from_public_key(address.network, &public_key.serialize_compressed(ctx)?)
+    } else {
+        PubkeyHashAddress::from_public_key(
+            address.network,
+            &public_key.serialize_uncompressed(ctx)?,
+        )
+    };
+    Ok(signer == address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Network;
+    use libsecp256k1_veil::{Context, ContextAll, ContextVerify, PublicKey};
+
+    #[test]
+    fn hash_layout() {
+        let mut expected = vec![MESSAGE_MAGIC.len() as u8];
+        expected.extend_from_slice(MESSAGE_MAGIC.as_bytes());
+        expected.push(5);
+        expected.extend_from_slice(b"hello");
+        assert_eq!(message_hash("hello"), hash::sha256d(&expected));
+    }
+
+    #[test]
+    fn known_answer() {
+        // `signmessage` with the key 0x0101..01 and its compressed address. veild's libsecp256k1
+        // signs with RFC 6979 nonces, so the signature is deterministic; this one was computed
+        // with an independent RFC 6979 implementation.
+        let ctx = ContextAll::new();
+        let private_key = PrivateKey::from_slice(&ctx, &[1; 32]).unwrap();
+        let address = "VMkeXeoBn7jEsPPPvx6DqMtjNpsjcT2a8s";
+        let signature = "INxDRdmi3IrvvhNrO39b2JLFpJxK4HgQUrWXAkmkBHWoXh3wWQYWe6Xw/HsfXTnt7ZikIC2QTBzxM2RXwyuFr38=";
+
+        assert_eq!(
+            sign_message(&ctx, &private_key, true, "Veil").unwrap(),
+            signature
+        );
+        assert!(verify_message(&ctx, address, signature, "Veil").unwrap());
+        assert!(!verify_message(&ctx, address, signature, "veil").unwrap());
+    }
+
+    #[test]
+    fn sign_verify() {
+        let ctx = ContextAll::new();
+        let private_key = PrivateKey::new(&ctx);
+        let public_key = PublicKey::new(&ctx, &private_key).unwrap();
+
+        let compressed = PubkeyHashAddress::from_public_key(
+            Network::Main,
+            &public_key.serialize_compressed(&ctx).unwrap(),
+        )
+        .to_string();
+        let uncompressed = PubkeyHashAddress::from_public_key(
+            Network::Main,
+            &public_key.serialize_uncompressed(&ctx).unwrap(),
+        )
+        .to_string();
+
+        let sig = sign_message(&ctx, &private_key, true, "hello").unwrap();
+        assert_ne!((base64::decode(&sig).unwrap()[0] - 27) & 4, 0);
+        assert!(verify_message(&ctx, &compressed, &sig, "hello").unwrap());
+        assert!(!verify_message(&ctx, &compressed, &sig, "hello!").unwrap());
+        assert!(!verify_message(&ctx, &uncompressed, &sig, "hello").unwrap());
+
+        let sig = sign_message(&ctx, &private_key, false, "hello").unwrap();
+        assert!(verify_message(&ctx, &uncompressed, &sig, "hello").unwrap());
+        assert!(!verify_message(&ctx, &compressed, &sig, "hello").unwrap());
+    }
+
+    #[test]
+    fn malformed() {
+        let ctx = ContextVerify::new();
+        let address = PubkeyHashAddress {
+            network: Network::Main,
+            hash: [0; 20],
+        }
+        .to_string();
+
+        let short = base64::encode(&[31u8; 64][..]);
+        let bad_header = base64::encode(&[26u8; 65][..]);
+        assert!(matches!(
+            verify_message(&ctx, &address, "not base64!", "hello"),
+            Err(MessageError::InvalidBase64)
+        ));
+        assert!(matches!(
+            verify_message(&ctx, &address, &short, "hello"),
+            Err(MessageError::InvalidSignatureLength(64))
+        ));
+        assert!(matches!(
+            verify_message(&ctx, &address, &bad_header, "hello"),
+            Err(MessageError::InvalidHeader(26))
+        ));
+        assert!(matches!(
+            verify_message(&ctx, "V", &short, "hello"),
+            Err(MessageError::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Mempool standardness rules.
+//!
+//! These mirror the checks veild runs before relaying a transaction, so a wallet can find out
+//! offline whether `testmempoolaccept` would reject it. The reject reasons are the strings veild
+//! reports.
+
+use crate::amount::{self, Amount, FeeRate};
+use crate::primitives::transaction::{CtOut, RingCtOut, StandardOut, Transaction};
+use crate::script::{self, ScriptKind};
+use core::fmt;
+
+/// The maximum weight of a transaction veild will relay or mine.
+pub const MAX_STANDARD_TX_WEIGHT: usize = 400_000;
+
+/// The maximum size of an input script, large enough for a 15-of-15 P2SH multisig spend.
+pub const MAX_STANDARD_SCRIPTSIG_SIZE: usize = 1650;
+
+/// The maximum size of a null data output script, including the `OP_RETURN`.
+pub const MAX_OP_RETURN_RELAY: usize = 83;
+
+/// The maximum number of keys in a standard bare multisig output.
+pub const MAX_STANDARD_MULTISIG_KEYS: u8 = 3;
+
+/// The maximum size of a data output, enough for two stealth prefixes and a fee.
+pub const MAX_DATA_OUTPUT_SIZE: usize = 34 + 5 + 34;
+
+/// Size of a serialized Pedersen commitment and of a compressed public key.
+pub const COMMITMENT_SIZE: usize = 33;
+
+/// The maximum size of the data of a blinded output, the ephemeral key plus a prefix and
+/// narration key.
+pub const MAX_BLINDED_DATA_SIZE: usize = 33 + 5 + 33;
+
+/// The maximum size of a range proof.
+pub const MAX_RANGE_PROOF_SIZE: usize = 5134;
+
+/// The smallest ring an anon input may use.
+pub const MIN_RING_SIZE: u32 = 3;
+
+/// The largest ring an anon input may use.
+pub const MAX_RING_SIZE: u32 = 32;
+
+/// The maximum number of inputs signed by a single anon input.
+pub const MAX_ANON_INPUTS: u32 = 32;
+
+/// Default for the fee rate below which a transaction is not relayed.
+pub const DEFAULT_MIN_RELAY_TX_FEE: Amount = 1000;
+
+/// Default for the fee rate used to decide whether an output is dust.
+pub const DUST_RELAY_TX_FEE: Amount = 3000;
+
+#[derive(Debug, PartialEq)]
+pub enum RejectReason {
+    Coinbase,
+    Version,
+    TxSize,
+    ScriptSigSize,
+    ScriptSigNotPushOnly,
+    ScriptPubKey,
+    BareMultisig,
+    Dust,
+    MultiOpReturn,
+    OutputNegative,
+    OutputTooLarge,
+    OutputDataSize,
+    CtOutCommitmentSize,
+    CtOutEphemeralSize,
+    CtOutRangeProofSize,
+    RingCtOutPublicKeySize,
+    RingCtOutCommitmentSize,
+    RingCtOutEphemeralSize,
+    RingCtOutRangeProofSize,
+    AnonRingSize,
+    AnonNumInputs,
+    MinRelayFee,
+}
+
+impl RejectReason {
+    /// The reject reason veild reports.
+    pub fn as_str(&self) -> &'static str {
+        use RejectReason::*;
+        match self {
+            Coinbase => "coinbase",
+            Version => "version",
+            TxSize => "tx-size",
+            ScriptSigSize => "scriptsig-size",
+            ScriptSigNotPushOnly => "scriptsig-not-pushonly",
+            ScriptPubKey => "scriptpubkey",
+            BareMultisig => "bare-multisig",
+            Dust => "dust",
+            MultiOpReturn => "multi-op-return",
+            OutputNegative => "bad-txns-vout-negative",
+            OutputTooLarge => "bad-txns-vout-toolarge",
+            OutputDataSize => "bad-output-data-size",
+            CtOutCommitmentSize => "bad-ctout-commitment-size",
+            CtOutEphemeralSize => "bad-ctout-ephem-size",
+            CtOutRangeProofSize => "bad-ctout-rangeproof-size",
+            RingCtOutPublicKeySize => "bad-rctout-pk-size",
+            RingCtOutCommitmentSize => "bad-rctout-commitment-size",
+            RingCtOutEphemeralSize => "bad-rctout-ephem-size",
+            RingCtOutRangeProofSize => "bad-rctout-rangeproof-size",
+            AnonRingSize => "bad-anon-ringsize",
+            AnonNumInputs => "bad-anon-num-inputs",
+            MinRelayFee => "min relay fee not met",
+        }
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Relay policy settings, the defaults match veild's.
+pub struct Config {
+    pub permit_bare_multisig: bool,
+    /// Maximum null data script size, `None` rejects null data outputs.
+    pub max_datacarrier_bytes: Option<usize>,
+    pub dust_relay_fee: FeeRate,
+    pub min_relay_fee: FeeRate,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            permit_bare_multisig: true,
+            max_datacarrier_bytes: Some(MAX_OP_RETURN_RELAY),
+            dust_relay_fee: FeeRate::per_kvb(DUST_RELAY_TX_FEE),
+            min_relay_fee: FeeRate::per_kvb(DEFAULT_MIN_RELAY_TX_FEE),
+        }
+    }
+}
+
+/// Runs every policy check on a transaction paying `fee`.
+pub fn check_transaction(tx: &Transaction, fee: Amount, cfg: &Config) -> Result<(), RejectReason> {
+    if tx.is_coinbase() {
+        return Err(RejectReason::Coinbase);
+    }
+    check_standard(tx, cfg)?;
+    check_fee(tx, fee, cfg)
+}
+
+/// Checks the transaction is standard, without looking at its fee.
+pub fn check_standard(tx: &Transaction, cfg: &Config) -> Result<(), RejectReason> {
+    let version = tx.version() & 0xff;
+    if !(1..=Transaction::MAX_VERSION).contains(&version) {
+        return Err(RejectReason::Version);
+    }
+
+    if tx.weight() > MAX_STANDARD_TX_WEIGHT {
+        return Err(RejectReason::TxSize);
+    }
+
+    for tx_in in tx.transaction_in() {
+        if tx_in.is_anon() {
+            let (inputs, ring_size) = tx_in.anon();
+            if !(1..=MAX_ANON_INPUTS).contains(&inputs) {
+                return Err(RejectReason::AnonNumInputs);
+            }
+            if !(MIN_RING_SIZE..=MAX_RING_SIZE).contains(&ring_size) {
+                return Err(RejectReason::AnonRingSize);
+            }
+        }
+        if tx_in.script().len() > MAX_STANDARD_SCRIPTSIG_SIZE {
+            return Err(RejectReason::ScriptSigSize);
+        }
+        if !script::is_push_only(tx_in.script()) {
+            return Err(RejectReason::ScriptSigNotPushOnly);
+        }
+    }
+
+    let mut null_data_count = 0;
+    for out in tx.standard_out() {
+        match check_standard_out(out, cfg)? {
+            ScriptKind::NullData => null_data_count += 1,
+            _ if is_dust(out, &cfg.dust_relay_fee) => return Err(RejectReason::Dust),
+            _ => {}
+        }
+    }
+    if null_data_count > 1 {
+        return Err(RejectReason::MultiOpReturn);
+    }
+
+    for out in tx.ct_out() {
+        check_ct_out(out)?;
+    }
+    for out in tx.ring_ct_out() {
+        check_ring_ct_out(out)?;
+    }
+    for out in tx.data_out() {
+        let len = out.data().len();
+        if !(1..=MAX_DATA_OUTPUT_SIZE).contains(&len) {
+            return Err(RejectReason::OutputDataSize);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks `fee` meets the minimum relay fee for the size of the transaction.
+pub fn check_fee(tx: &Transaction, fee: Amount, cfg: &Config) -> Result<(), RejectReason> {
+    if fee < cfg.min_relay_fee.fee(tx.vsize()) {
+        Err(RejectReason::MinRelayFee)
+    } else {
+        Ok(())
+    }
+}
+
+/// The smallest value an output can have before spending it costs more than it is worth.
+pub fn dust_threshold(out: &StandardOut, dust_relay_fee: &FeeRate) -> Amount {
+    if script::is_unspendable(out.script()) {
+        return 0;
+    }
+
+    // Output type, value and script, plus the input needed to spend it: the outpoint,
+    // sequence and a typical signature and public key.
+    let mut size = 1 + 8 + serialized_script_len(out.script()) + 32 + 4 + 4;
+    if script::witness_program(out.script()).is_some() {
+        size += 1 + 107 / 4;
+    } else {
+        size += 1 + 107;
+    }
+
+    dust_relay_fee.fee(size)
+}
+
+pub fn is_dust(out: &StandardOut, dust_relay_fee: &FeeRate) -> bool {
+    *out.amount() < dust_threshold(out, dust_relay_fee)
+}
+
+fn serialized_script_len(script: &[u8]) -> usize {
+    crate::serialize::compact_size_len(script.len() as u64) + script.len()
+}
+
+fn check_standard_out(out: &StandardOut, cfg: &Config) -> Result<ScriptKind, RejectReason> {
+    if *out.amount() < 0 {
+        return Err(RejectReason::OutputNegative);
+    }
+    if !amount::money_range(out.amount()) {
+        return Err(RejectReason::OutputTooLarge);
+    }
+
+    let kind = script::solve(out.script());
+    match kind {
+        ScriptKind::NonStandard => return Err(RejectReason::ScriptPubKey),
+        ScriptKind::Multisig { required, keys } => {
+            if !(1..=MAX_STANDARD_MULTISIG_KEYS).contains(&keys) || required > keys {
+                return Err(RejectReason::ScriptPubKey);
+            }
+            if !cfg.permit_bare_multisig {
+                return Err(RejectReason::BareMultisig);
+            }
+        }
+        ScriptKind::NullData => match cfg.max_datacarrier_bytes {
+            Some(max) if out.script().len() <= max => {}
+            _ => return Err(RejectReason::ScriptPubKey),
+        },
+        _ => {}
+    }
+
+    Ok(kind)
+}
+
+fn check_ct_out(out: &CtOut) -> Result<(), RejectReason> {
+    if out.commitment().len() != COMMITMENT_SIZE {
+        return Err(RejectReason::CtOutCommitmentSize);
+    }
+    let data_len = out.ephemeral_public_key().len() + out.data().len();
+    if out.ephemeral_public_key().len() != COMMITMENT_SIZE || data_len > MAX_BLINDED_DATA_SIZE {
+        return Err(RejectReason::CtOutEphemeralSize);
+    }
+    if out.range_proof().len() > MAX_RANGE_PROOF_SIZE {
+        return Err(RejectReason::CtOutRangeProofSize);
+    }
+    if script::solve(out.script()) == ScriptKind::NonStandard {
+        return Err(RejectReason::ScriptPubKey);
+    }
+    Ok(())
+}
+
+fn check_ring_ct_out(out: &RingCtOut) -> Result<(), RejectReason> {
+    if out.public_key().len() != COMMITMENT_SIZE {
+        return Err(RejectReason::RingCtOutPublicKeySize);
+    }
+    if out.commitment().len() != COMMITMENT_SIZE {
+        return Err(RejectReason::RingCtOutCommitmentSize);
+    }
+    let data_len = out.ephemeral_public_key().len() + out.data().len();
+    if out.ephemeral_public_key().len() != COMMITMENT_SIZE || data_len > MAX_BLINDED_DATA_SIZE {
+        return Err(RejectReason::RingCtOutEphemeralSize);
+    }
+    if out.range_proof().len() > MAX_RANGE_PROOF_SIZE {
+        return Err(RejectReason::RingCtOutRangeProofSize);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::primitives::transaction::{OutPoint, TransactionIn};
+    use crate::u256;
+
+    fn p2pkh() -> Vec<u8> {
+        let mut script = vec![0x76, 0xa9, 20];
+        script.extend_from_slice(&[7; 20]);
+        script.extend_from_slice(&[0x88, 0xac]);
+        script
+    }
+
+    fn transaction(value: Amount) -> Transaction {
+        let mut tx = Transaction::new();
+        tx.push_transaction_in(TransactionIn::new(
+            OutPoint::new(u256::from(1u32), 0),
+            vec![0x01, 0x00],
+            TransactionIn::SEQUENCE_FINAL,
+        ));
+        tx.push_standard_out(StandardOut::new(value, p2pkh()));
+        tx
+    }
+
+    #[test]
+    fn standard_transaction() {
+        let cfg = Config::default();
+        let tx = transaction(amount::COIN);
+        assert_eq!(check_transaction(&tx, 1000, &cfg), Ok(()));
+        assert_eq!(
+            check_transaction(&tx, 0, &cfg),
+            Err(RejectReason::MinRelayFee)
+        );
+    }
+
+    #[test]
+    fn dust() {
+        let cfg = Config::default();
+        let out = StandardOut::new(0, p2pkh());
+        // 1 + 8 + 26 + 148 bytes at 3 sat/vB.
+        assert_eq!(dust_threshold(&out, &cfg.dust_relay_fee), 549);
+        assert_eq!(
+            check_standard(&transaction(548), &cfg),
+            Err(RejectReason::Dust)
+        );
+        assert_eq!(check_standard(&transaction(549), &cfg), Ok(()));
+    }
+
+    #[test]
+    fn null_data() {
+        let cfg = Config::default();
+        let mut tx = transaction(amount::COIN);
+        tx.push_standard_out(StandardOut::new(0, vec![0x6a, 0x01, 0x01]));
+        assert_eq!(check_standard(&tx, &cfg), Ok(()));
+        tx.push_standard_out(StandardOut::new(0, vec![0x6a]));
+        assert_eq!(check_standard(&tx, &cfg), Err(RejectReason::MultiOpReturn));
+    }
+
+    #[test]
+    fn range_proof_is_witness() {
+        let plain = transaction(amount::COIN);
+        let mut tx = transaction(amount::COIN);
+        let mut out = CtOut::new();
+        out.set_commitment(vec![8; 33]);
+        out.set_ephemeral_public_key(vec![3; 33]);
+        out.set_script(p2pkh());
+        out.set_range_proof(vec![0; 5000]);
+        tx.push_ct_out(out);
+
+        // Kind, commitment, data, script and an empty proof.
+        assert_eq!(tx.base_size() - plain.base_size(), 1 + 33 + 34 + 26 + 1);
+        // The proof and its three byte length only count in the witness, at a quarter weight.
+        let witness = |tx: &Transaction| tx.total_size() - tx.base_size();
+        assert_eq!(witness(&tx) - witness(&plain), 5000 + 3 - 1);
+        assert_eq!(tx.weight() - plain.weight(), 95 * 4 + 5002);
+    }
+
+    #[test]
+    fn ring_ct_limits() {
+        let cfg = Config::default();
+        let mut tx = transaction(amount::COIN);
+        let mut tx_in = TransactionIn::new(
+            OutPoint::new(u256::from(0u32), OutPoint::ANON_MARKER),
+            Vec::new(),
+            TransactionIn::SEQUENCE_FINAL,
+        );
+        tx_in.set_anon(1, 2);
+        tx.push_transaction_in(tx_in);
+        assert_eq!(check_standard(&tx, &cfg), Err(RejectReason::AnonRingSize));
+
+        let mut tx = transaction(amount::COIN);
+        let mut out = RingCtOut::new();
+        out.set_public_key(vec![2; 33]);
+        out.set_commitment(vec![8; 33]);
+        out.set_ephemeral_public_key(vec![3; 33]);
+        out.set_range_proof(vec![0; MAX_RANGE_PROOF_SIZE + 1]);
+        tx.push_ring_ct_out(out);
+        assert_eq!(
+            check_standard(&tx, &cfg),
+            Err(RejectReason::RingCtOutRangeProofSize)
+        );
+    }
+}
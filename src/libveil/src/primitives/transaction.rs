@@ -1,7 +1,10 @@
 use crate::amount::{self, Amount};
-use crate::crypto;
+use crate::constants::WITNESS_SCALE_FACTOR;
+use crate::crypto::hash::{Txid, Wtxid};
+use crate::prelude::*;
+use crate::serialize;
 use crate::u256;
-use std::convert::TryFrom;
+use core::convert::TryFrom;
 
 pub trait ToOutpoint {
     fn to_outpoint(&self) -> OutPoint;
@@ -39,7 +42,7 @@ pub enum DataOutputKind {
 // TODO: Serialization to bytes.
 // TODO: Partially eq, not equal, greater
 // TODO: to_string() should be what the debug is
-#[derive(Debug, PartialOrd, PartialEq)]
+#[derive(Clone, Debug, PartialOrd, PartialEq)]
 /// An outpoint
 ///
 /// A combination of a transaction hash and an index sequence number into its out.
@@ -55,6 +58,14 @@ impl OutPoint {
         Self { hash, sequence }
     }
 
+    pub fn hash(&self) -> &u256 {
+        &self.hash
+    }
+
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
     // TODO: hash then n
     //    pub fn serialize
 
@@ -82,6 +93,11 @@ impl OutPoint {
     pub fn is_anon(&self) -> bool {
         self.sequence == Self::ANON_MARKER
     }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.hash.to_le_bytes());
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+    }
 }
 
 // TODO: Actual script
@@ -89,8 +105,8 @@ pub struct TransactionIn {
     prev_out: OutPoint,
     script: Vec<u8>,
     sequence: u32,
-    script_data: Vec<u8>,    // TODO: Actual script data struct
-    script_witness: Vec<u8>, // TODO: Actual script witness struct
+    script_data: Vec<Vec<u8>>,
+    script_witness: Vec<Vec<u8>>,
 }
 
 impl TransactionIn {
@@ -138,6 +154,52 @@ impl TransactionIn {
     pub fn anon(&self) -> (u32, u32) {
         self.prev_out.anon()
     }
+
+    pub fn prev_out(&self) -> &OutPoint {
+        &self.prev_out
+    }
+
+    pub fn script(&self) -> &[u8] {
+        &self.script
+    }
+
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Data stack of an anon input, holding the key images.
+    pub fn script_data(&self) -> &[Vec<u8>] {
+        &self.script_data
+    }
+
+    pub fn script_witness(&self) -> &[Vec<u8>] {
+        &self.script_witness
+    }
+
+    pub fn set_script(&mut self, script: Vec<u8>) {
+        self.script = script;
+    }
+
+    pub fn set_sequence(&mut self, sequence: u32) {
+        self.sequence = sequence;
+    }
+
+    pub fn set_script_data(&mut self, script_data: Vec<Vec<u8>>) {
+        self.script_data = script_data;
+    }
+
+    pub fn set_script_witness(&mut self, script_witness: Vec<Vec<u8>>) {
+        self.script_witness = script_witness;
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.prev_out.encode(buf);
+        serialize::write_var_bytes(buf, &self.script);
+        buf.extend_from_slice(&self.sequence.to_le_bytes());
+        if self.is_anon() {
+            serialize::write_stack(buf, &self.script_data);
+        }
+    }
 }
 
 pub struct StandardOut {
@@ -188,7 +250,13 @@ impl StandardOut {
     }
 
     pub fn is_clear(&self) -> bool {
-        self.amount == 0 && self.script == Vec::new()
+        self.amount == 0 && self.script.is_empty()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(OutputKind::Standard as u8);
+        buf.extend_from_slice(&self.amount.to_le_bytes());
+        serialize::write_var_bytes(buf, &self.script);
     }
 }
 
@@ -261,6 +329,14 @@ impl CtOut {
             && self.script.is_empty()
             && self.range_proof.is_empty()
     }
+
+    fn encode(&self, buf: &mut Vec<u8>, witness: bool) {
+        buf.push(OutputKind::Ct as u8);
+        buf.extend_from_slice(&self.commitment);
+        encode_data(buf, &self.ephemeral_public_key, &self.data);
+        serialize::write_var_bytes(buf, &self.script);
+        encode_range_proof(buf, &self.range_proof, witness);
+    }
 }
 
 #[derive(Default)]
@@ -332,6 +408,31 @@ impl RingCtOut {
             && self.data.is_empty()
             && self.range_proof.is_empty()
     }
+
+    fn encode(&self, buf: &mut Vec<u8>, witness: bool) {
+        buf.push(OutputKind::RingCt as u8);
+        buf.extend_from_slice(&self.public_key);
+        buf.extend_from_slice(&self.commitment);
+        encode_data(buf, &self.ephemeral_public_key, &self.data);
+        encode_range_proof(buf, &self.range_proof, witness);
+    }
+}
+
+/// Blinded outputs carry the ephemeral public key at the front of their data.
+fn encode_data(buf: &mut Vec<u8>, ephemeral_public_key: &[u8], data: &[u8]) {
+    let len = ephemeral_public_key.len() + data.len();
+    serialize::write_compact_size(buf, len as u64);
+    buf.extend_from_slice(ephemeral_public_key);
+    buf.extend_from_slice(data);
+}
+
+/// Range proofs are witness data, without the witness an empty proof is written in their place.
+fn encode_range_proof(buf: &mut Vec<u8>, range_proof: &[u8], witness: bool) {
+    if witness {
+        serialize::write_var_bytes(buf, range_proof);
+    } else {
+        serialize::write_compact_size(buf, 0);
+    }
 }
 
 pub struct DataOut(pub i64);
@@ -344,18 +445,63 @@ impl DataOut {
     pub fn fee(&self) -> &i64 {
         &self.0
     }
+
+    /// The data carried by the output, the fee tagged with its `DataOutputKind`.
+    pub fn data(&self) -> Vec<u8> {
+        let mut data = vec![DataOutputKind::Fee as u8];
+        serialize::write_var_int(&mut data, self.0 as u64);
+        data
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(OutputKind::Data as u8);
+        serialize::write_var_bytes(buf, &self.data());
+    }
+}
+
+/// An output of any kind. A transaction keeps them in one list, in the order veild's `vpout`
+/// does, since the order is part of what is hashed and what output indices refer to.
+pub enum TransactionOut {
+    Standard(StandardOut),
+    Ct(CtOut),
+    RingCt(RingCtOut),
+    Data(DataOut),
+}
+
+impl TransactionOut {
+    pub fn kind(&self) -> OutputKind {
+        match self {
+            TransactionOut::Standard(_) => OutputKind::Standard,
+            TransactionOut::Ct(_) => OutputKind::Ct,
+            TransactionOut::RingCt(_) => OutputKind::RingCt,
+            TransactionOut::Data(_) => OutputKind::Data,
+        }
+    }
+
+    /// Whether the output carries a range proof, which is witness data.
+    fn has_witness(&self) -> bool {
+        match self {
+            TransactionOut::Ct(out) => !out.range_proof.is_empty(),
+            TransactionOut::RingCt(out) => !out.range_proof.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>, witness: bool) {
+        match self {
+            TransactionOut::Standard(out) => out.encode(buf),
+            TransactionOut::Ct(out) => out.encode(buf, witness),
+            TransactionOut::RingCt(out) => out.encode(buf, witness),
+            TransactionOut::Data(out) => out.encode(buf),
+        }
+    }
 }
 
 pub struct Transaction {
     transaction_in: Vec<TransactionIn>,
-    standard_out: Option<Vec<StandardOut>>,
-    ct_out: Option<Vec<CtOut>>,
-    ring_ct_out: Option<Vec<RingCtOut>>,
-    data_out: Option<Vec<DataOut>>,
+    transaction_out: Vec<TransactionOut>,
     version: u32,
     lock_time: u32,
-    hash: u256,
-    witness_hash: u256,
 }
 
 impl Transaction {
@@ -372,14 +518,9 @@ impl Transaction {
     pub fn new() -> Self {
         Self {
             transaction_in: Vec::new(),
-            standard_out: None,
-            ct_out: None,
-            ring_ct_out: None,
-            data_out: None,
+            transaction_out: Vec::new(),
             version: Self::CURRENT_VERSION,
             lock_time: 0,
-            hash: u256([0u64; 4]),
-            witness_hash: u256([0u64; 4]),
         }
     }
 
@@ -391,40 +532,28 @@ impl Transaction {
         self.transaction_in = tx_ins;
     }
 
-    pub fn push_standard_out(&mut self, std_out: StandardOut) {
-        self.standard_out.get_or_insert(Vec::new()).push(std_out);
+    pub fn push_transaction_out(&mut self, tx_out: TransactionOut) {
+        self.transaction_out.push(tx_out);
     }
 
-    pub fn set_standard_outs(&mut self, std_outs: Vec<StandardOut>) {
-        self.standard_out = Some(std_outs);
+    pub fn set_transaction_outs(&mut self, tx_outs: Vec<TransactionOut>) {
+        self.transaction_out = tx_outs;
     }
 
-    pub fn push_ct_out(&mut self, ct_out: CtOut) {
-        self.ct_out.get_or_insert(Vec::new()).push(ct_out);
+    pub fn push_standard_out(&mut self, std_out: StandardOut) {
+        self.push_transaction_out(TransactionOut::Standard(std_out));
     }
 
-    pub fn set_ct_outs(&mut self, ct_outs: Vec<CtOut>) {
-        self.ct_out = Some(ct_outs);
+    pub fn push_ct_out(&mut self, ct_out: CtOut) {
+        self.push_transaction_out(TransactionOut::Ct(ct_out));
     }
 
     pub fn push_ring_ct_out(&mut self, ring_ct_out: RingCtOut) {
-        self.ring_ct_out.get_or_insert(Vec::new()).push(ring_ct_out);
-    }
-
-    pub fn set_ring_ct_outs(&mut self, ring_ct_outs: Vec<RingCtOut>) {
-        self.ring_ct_out = Some(ring_ct_outs);
+        self.push_transaction_out(TransactionOut::RingCt(ring_ct_out));
     }
 
     pub fn push_data_out(&mut self, data_out: DataOut) {
-        self.data_out.get_or_insert(Vec::new()).push(data_out);
-    }
-
-    pub fn set_data_outs(&mut self, data_outs: Vec<DataOut>) {
-        self.data_out = Some(data_outs);
-    }
-
-    pub fn data_out(&self) -> Option<&[DataOut]> {
-        self.data_out.as_ref().map(|x| x.as_ref())
+        self.push_transaction_out(TransactionOut::Data(data_out));
     }
 
     pub fn set_lock_time(&mut self, lock_time: u32) {
@@ -435,32 +564,76 @@ impl Transaction {
         &self.transaction_in
     }
 
-    pub fn standard_out(&self) -> Option<&[StandardOut]> {
-        self.standard_out.as_ref().map(|x| x.as_ref())
+    /// Every output, in order, so the position of an output is its index in an `OutPoint`.
+    pub fn transaction_out(&self) -> &[TransactionOut] {
+        &self.transaction_out
+    }
+
+    pub fn standard_out(&self) -> impl Iterator<Item = &StandardOut> {
+        self.transaction_out.iter().filter_map(|out| match out {
+            TransactionOut::Standard(out) => Some(out),
+            _ => None,
+        })
     }
 
-    pub fn ct_out(&self) -> Option<&[CtOut]> {
-        self.ct_out.as_ref().map(|x| x.as_ref())
+    pub fn ct_out(&self) -> impl Iterator<Item = &CtOut> {
+        self.transaction_out.iter().filter_map(|out| match out {
+            TransactionOut::Ct(out) => Some(out),
+            _ => None,
+        })
     }
 
-    pub fn ring_ct_out(&self) -> Option<&[RingCtOut]> {
-        self.ring_ct_out.as_ref().map(|x| x.as_ref())
+    pub fn ring_ct_out(&self) -> impl Iterator<Item = &RingCtOut> {
+        self.transaction_out.iter().filter_map(|out| match out {
+            TransactionOut::RingCt(out) => Some(out),
+            _ => None,
+        })
+    }
+
+    pub fn data_out(&self) -> impl Iterator<Item = &DataOut> {
+        self.transaction_out.iter().filter_map(|out| match out {
+            TransactionOut::Data(out) => Some(out),
+            _ => None,
+        })
     }
 
     pub fn version(&self) -> &u32 {
         &self.version
     }
 
+    pub fn set_version(&mut self, version: u32) {
+        self.version = version
+    }
+
     pub fn lock_time(&self) -> &u32 {
         &self.lock_time
     }
 
-    pub fn hash(&self) -> &u256 {
-        &self.hash
+    // The hashes are not public until they are checked against transactions from veild.
+
+    /// The hash of the transaction without witness data, which signatures cannot change.
+    #[allow(dead_code)]
+    fn txid(&self) -> Txid {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, false);
+        Txid::hash(&buf)
+    }
+
+    /// The hash of the transaction including witness data, the same bytes as the txid when
+    /// there is none.
+    #[allow(dead_code)]
+    fn witness_hash(&self) -> Wtxid {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, self.has_witness());
+        Wtxid::hash(&buf)
     }
 
-    pub fn witness_hash(&self) -> &u256 {
-        &self.witness_hash
+    /// Whether any input has a witness or any output a range proof.
+    pub fn has_witness(&self) -> bool {
+        self.transaction_in
+            .iter()
+            .any(|tx_in| !tx_in.script_witness.is_empty())
+            || self.transaction_out.iter().any(TransactionOut::has_witness)
     }
 
     pub fn input_len(&self) -> usize {
@@ -468,20 +641,7 @@ impl Transaction {
     }
 
     pub fn output_len(&self) -> usize {
-        let mut count = 0usize;
-        if let Some(v) = &self.standard_out {
-            count += v.len()
-        }
-        if let Some(v) = &self.ct_out {
-            count += v.len()
-        }
-        if let Some(v) = &self.ring_ct_out {
-            count += v.len()
-        }
-        if let Some(v) = &self.data_out {
-            count += v.len()
-        }
-        count
+        self.transaction_out.len()
     }
 
     pub fn is_txin_empty(&self) -> bool {
@@ -489,19 +649,14 @@ impl Transaction {
     }
 
     pub fn is_txout_empty(&self) -> bool {
-        self.standard_out.is_none()
-            && self.ct_out.is_none()
-            && self.ring_ct_out.is_none()
-            && self.data_out.is_none()
+        self.transaction_out.is_empty()
     }
 
     pub fn is_stake(&self) -> bool {
-        if self.is_txin_empty()
-            && self.transaction_in.len() != 1
-            && self.output_len() > 1
-            && self.standard_out.is_some()
-        {
-            self.standard_out.as_ref().unwrap()[0].is_clear()
+        if self.is_txin_empty() && self.transaction_in.len() != 1 && self.output_len() > 1 {
+            self.standard_out()
+                .next()
+                .is_some_and(StandardOut::is_clear)
         } else {
             false
         }
@@ -512,38 +667,82 @@ impl Transaction {
     }
 
     pub fn has_standard_out(&self) -> bool {
-        self.standard_out.is_some()
+        self.standard_out().next().is_some()
     }
 
     pub fn has_ct_out(&self) -> bool {
-        self.ct_out.is_some()
+        self.ct_out().next().is_some()
     }
 
     pub fn has_ring_ct_out(&self) -> bool {
-        self.ring_ct_out.is_some()
+        self.ring_ct_out().next().is_some()
     }
 
     pub fn has_data_out(&self) -> bool {
-        self.data_out.is_some()
+        self.data_out().next().is_some()
     }
 
-    pub fn standard_out_amount(&self) -> Amount {
+    /// Sum of the standard outputs, `None` if an output or the sum is outside the money range.
+    pub fn standard_out_amount(&self) -> Option<Amount> {
         let mut amount: Amount = 0;
-        if let Some(v) = &self.standard_out {
-            for out in v {
-                amount += out.amount();
-                if !amount::money_range(&out.amount()) || !amount::money_range(&amount) {
-                    println!("TODO: Throw value out of range error")
-                }
+        for out in self.standard_out() {
+            if !amount::money_range(out.amount()) {
+                return None;
+            }
+            amount += out.amount();
+            if !amount::money_range(&amount) {
+                return None;
             }
         }
-        amount
+        Some(amount)
+    }
+
+    /// Serialized size without the input witnesses and range proofs.
+    pub fn base_size(&self) -> usize {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, false);
+        buf.len()
+    }
+
+    /// Serialized size including the input witnesses and range proofs.
+    pub fn total_size(&self) -> usize {
+        let mut buf = Vec::new();
+        self.encode(&mut buf, true);
+        buf.len()
+    }
+
+    /// Transaction weight as defined by BIP 141.
+    pub fn weight(&self) -> usize {
+        self.base_size() * (WITNESS_SCALE_FACTOR - 1) + self.total_size()
+    }
+
+    /// Virtual size, the weight scaled down and rounded up to whole bytes.
+    pub fn vsize(&self) -> usize {
+        self.weight().div_ceil(WITNESS_SCALE_FACTOR)
     }
 
-    // TOOD: Need sha526
-    //    fn hash_data()
+    /// Encodes the transaction the way veild does.
+    fn encode(&self, buf: &mut Vec<u8>, witness: bool) {
+        buf.push(self.version as u8);
+        buf.push((self.version >> 8) as u8);
+        buf.extend_from_slice(&self.lock_time.to_le_bytes());
+
+        serialize::write_compact_size(buf, self.transaction_in.len() as u64);
+        for tx_in in &self.transaction_in {
+            tx_in.encode(buf);
+        }
+
+        serialize::write_compact_size(buf, self.output_len() as u64);
+        for out in &self.transaction_out {
+            out.encode(buf, witness);
+        }
 
-    //    fn hash_witness_data()
+        if witness {
+            for tx_in in &self.transaction_in {
+                serialize::write_stack(buf, &tx_in.script_witness);
+            }
+        }
+    }
 }
 
 impl Default for Transaction {
@@ -551,3 +750,79 @@ impl Default for Transaction {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.push_transaction_in(TransactionIn::new(
+            OutPoint::new(u256::from(1u32), 0),
+            Vec::new(),
+            TransactionIn::SEQUENCE_FINAL,
+        ));
+        tx.push_standard_out(StandardOut::new(amount::COIN, vec![0x51]));
+        tx
+    }
+
+    #[test]
+    fn txid() {
+        let mut tx = transaction();
+        let mut buf = Vec::new();
+        tx.encode(&mut buf, false);
+        let txid = tx.txid();
+        assert_eq!(txid, Txid::hash(&buf));
+        assert_eq!(tx.witness_hash().as_bytes(), txid.as_bytes());
+
+        let mut out = CtOut::new();
+        out.set_commitment(vec![8; 33]);
+        out.set_range_proof(vec![1; 100]);
+        tx.push_ct_out(out);
+        let txid = tx.txid();
+        let witness_hash = tx.witness_hash();
+        assert_ne!(witness_hash.as_bytes(), txid.as_bytes());
+
+        // Witness data only changes the witness hash.
+        tx.transaction_in[0].set_script_witness(vec![vec![2; 72]]);
+        assert_eq!(tx.txid(), txid);
+        assert_ne!(tx.witness_hash(), witness_hash);
+    }
+
+    #[test]
+    fn output_order() {
+        let mut tx = transaction();
+        tx.push_data_out(DataOut(1000));
+        let mut buf = Vec::new();
+        tx.encode(&mut buf, false);
+
+        // Outputs are written in the order they were added, whatever their kind.
+        let mut outputs = vec![2];
+        StandardOut::new(amount::COIN, vec![0x51]).encode(&mut outputs);
+        DataOut(1000).encode(&mut outputs);
+        assert!(buf.ends_with(&outputs));
+
+        let mut swapped = Transaction::new();
+        swapped.set_transaction_ins(tx.transaction_in);
+        swapped.push_data_out(DataOut(1000));
+        swapped.push_standard_out(StandardOut::new(amount::COIN, vec![0x51]));
+        assert_eq!(swapped.base_size(), buf.len());
+        let mut swapped_buf = Vec::new();
+        swapped.encode(&mut swapped_buf, false);
+        assert_ne!(swapped_buf, buf);
+    }
+
+    #[test]
+    fn standard_out_amount() {
+        let mut tx = transaction();
+        tx.push_standard_out(StandardOut::new(amount::COIN, vec![0x51]));
+        assert_eq!(tx.standard_out_amount(), Some(2 * amount::COIN));
+
+        tx.push_standard_out(StandardOut::new(amount::MAX_MONEY, vec![0x51]));
+        assert_eq!(tx.standard_out_amount(), None);
+
+        let mut tx = transaction();
+        tx.push_standard_out(StandardOut::new(-1, vec![0x51]));
+        assert_eq!(tx.standard_out_amount(), None);
+    }
+}
@@ -22,6 +22,8 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
+use crate::prelude::*;
+
 /// Maximum number of bytes pushable to the stack.
 pub const MAX_SCRIPT_ELEMENT_SIZE: u32 = 520;
 
@@ -172,3 +174,219 @@ pub enum OpCodeKind {
 }
 
 pub struct Script {}
+
+/// Standard script templates, as recognised by veild's solver.
+#[derive(Debug, PartialEq)]
+pub enum ScriptKind {
+    NonStandard,
+    PubKey,
+    PubKeyHash,
+    ScriptHash,
+    Multisig { required: u8, keys: u8 },
+    NullData,
+    WitnessV0KeyHash,
+    WitnessV0ScriptHash,
+    WitnessUnknown,
+}
+
+/// Matches a script against the standard templates.
+pub fn solve(script: &[u8]) -> ScriptKind {
+    use OpCodeKind::*;
+
+    match script {
+        [dup, hash160, 20, .., equal_verify, check_sig]
+            if script.len() == 25
+                && *dup == OpDup as u8
+                && *hash160 == OpHash160 as u8
+                && *equal_verify == OpEqualVerify as u8
+                && *check_sig == OpCheckSig as u8 =>
+        {
+            return ScriptKind::PubKeyHash;
+        }
+        [hash160, 20, .., equal]
+            if script.len() == 23 && *hash160 == OpHash160 as u8 && *equal == OpEqual as u8 =>
+        {
+            return ScriptKind::ScriptHash;
+        }
+        [op_return, rest @ ..] if *op_return == OpReturn as u8 => {
+            return if is_push_only(rest) {
+                ScriptKind::NullData
+            } else {
+                ScriptKind::NonStandard
+            };
+        }
+        _ => {}
+    }
+
+    if let Some((version, program)) = witness_program(script) {
+        return match (version, program.len()) {
+            (0, 20) => ScriptKind::WitnessV0KeyHash,
+            (0, 32) => ScriptKind::WitnessV0ScriptHash,
+            (0, _) => ScriptKind::NonStandard,
+            _ => ScriptKind::WitnessUnknown,
+        };
+    }
+
+    let ops: Option<Vec<(u8, &[u8])>> = Instructions::new(script).collect();
+    let ops = match ops {
+        Some(ops) => ops,
+        None => return ScriptKind::NonStandard,
+    };
+
+    match ops.as_slice() {
+        [(_, key), (check_sig, _)] if *check_sig == OpCheckSig as u8 && is_public_key(key) => {
+            ScriptKind::PubKey
+        }
+        [(m, _), keys @ .., (n, _), (check_multisig, _)]
+            if *check_multisig == OpCheckMultiSig as u8 =>
+        {
+            let (required, total) = match (small_int(*m), small_int(*n)) {
+                (Some(m), Some(n)) => (m, n),
+                _ => return ScriptKind::NonStandard,
+            };
+            if required < 1
+                || required > total
+                || keys.len() != total as usize
+                || !keys.iter().all(|(_, key)| is_public_key(key))
+            {
+                return ScriptKind::NonStandard;
+            }
+            ScriptKind::Multisig {
+                required,
+                keys: total,
+            }
+        }
+        _ => ScriptKind::NonStandard,
+    }
+}
+
+/// Whether the script only consists of push operations.
+pub fn is_push_only(script: &[u8]) -> bool {
+    Instructions::new(script).all(|op| match op {
+        Some((opcode, _)) => opcode <= OpCodeKind::Op16 as u8,
+        None => false,
+    })
+}
+
+/// Whether the script can never be spent, which is the case for null data outputs.
+pub fn is_unspendable(script: &[u8]) -> bool {
+    script.first() == Some(&(OpCodeKind::OpReturn as u8)) || script.len() > MAX_SCRIPT_SIZE as usize
+}
+
+/// Returns the version and program of a witness output script.
+pub fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    if script.len() < 4 || script.len() > 42 || script[1] as usize + 2 != script.len() {
+        return None;
+    }
+
+    let version = match script[0] {
+        0x00 => 0,
+        op => small_int(op).filter(|v| *v > 0)?,
+    };
+    Some((version, &script[2..]))
+}
+
+fn small_int(opcode: u8) -> Option<u8> {
+    if opcode == OpCodeKind::OpFalse as u8 {
+        Some(0)
+    } else if opcode >= OpCodeKind::OpTrue as u8 && opcode <= OpCodeKind::Op16 as u8 {
+        Some(opcode - OpCodeKind::OpTrue as u8 + 1)
+    } else {
+        None
+    }
+}
+
+fn is_public_key(key: &[u8]) -> bool {
+    match key.first() {
+        Some(0x02) | Some(0x03) => key.len() == 33,
+        Some(0x04) | Some(0x06) | Some(0x07) => key.len() == 65,
+        _ => false,
+    }
+}
+
+/// Iterates over the opcodes of a script along with the data they push.
+///
+/// Yields `None` once if a push runs past the end of the script.
+struct Instructions<'a> {
+    script: &'a [u8],
+}
+
+impl<'a> Instructions<'a> {
+    fn new(script: &'a [u8]) -> Self {
+        Self { script }
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Option<(u8, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&opcode, rest) = self.script.split_first()?;
+
+        let (len, rest) = match opcode {
+            0x01..=0x4b => (opcode as usize, rest),
+            op if op == OpCodeKind::OpPushData1 as u8 && !rest.is_empty() => {
+                (rest[0] as usize, &rest[1..])
+            }
+            op if op == OpCodeKind::OpPushData2 as u8 && rest.len() >= 2 => {
+                (u16::from_le_bytes([rest[0], rest[1]]) as usize, &rest[2..])
+            }
+            op if op == OpCodeKind::OpPushData4 as u8 && rest.len() >= 4 => (
+                u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize,
+                &rest[4..],
+            ),
+            op if op >= OpCodeKind::OpPushData1 as u8 && op <= OpCodeKind::OpPushData4 as u8 => {
+                self.script = &[];
+                return Some(None);
+            }
+            _ => (0, rest),
+        };
+
+        if len > rest.len() {
+            self.script = &[];
+            return Some(None);
+        }
+
+        let (data, rest) = rest.split_at(len);
+        self.script = rest;
+        Some(Some((opcode, data)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_templates() {
+        let mut p2pkh = vec![0x76, 0xa9, 20];
+        p2pkh.extend_from_slice(&[0; 20]);
+        p2pkh.extend_from_slice(&[0x88, 0xac]);
+        assert_eq!(solve(&p2pkh), ScriptKind::PubKeyHash);
+
+        let mut p2sh = vec![0xa9, 20];
+        p2sh.extend_from_slice(&[0; 20]);
+        p2sh.push(0x87);
+        assert_eq!(solve(&p2sh), ScriptKind::ScriptHash);
+
+        let mut p2wpkh = vec![0x00, 20];
+        p2wpkh.extend_from_slice(&[0; 20]);
+        assert_eq!(solve(&p2wpkh), ScriptKind::WitnessV0KeyHash);
+
+        let mut multisig = vec![0x51, 33, 0x02];
+        multisig.extend_from_slice(&[1; 32]);
+        multisig.extend_from_slice(&[0x51, 0xae]);
+        assert_eq!(
+            solve(&multisig),
+            ScriptKind::Multisig {
+                required: 1,
+                keys: 1
+            }
+        );
+
+        assert_eq!(solve(&[0x6a, 0x02, 0xca, 0xfe]), ScriptKind::NullData);
+        assert_eq!(solve(&[0x6a, 0x76]), ScriptKind::NonStandard);
+        assert_eq!(solve(&[0x4c]), ScriptKind::NonStandard);
+        assert!(!is_push_only(&[0x02, 0x00]));
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Finds the confidential outputs of a transaction that pay a stealth address.
+//!
+//! RingCT outputs carry their one-time public key, CT outputs pay it with a pay to public key
+//! hash script. Either way the output also carries the sender's ephemeral public key.
+
+use crate::crypto::hash;
+use crate::prelude::*;
+use crate::primitives::transaction::{Transaction, TransactionOut};
+use crate::script::{self, ScriptKind};
+use libsecp256k1_veil::{Error, PublicKey, StealthScanKey, Verification};
+
+/// An output found paying a stealth address, by its index among all the transaction's outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StealthOutput {
+    Ct(usize),
+    RingCt(usize),
+}
+
+/// The CT and RingCT outputs of `tx` paying the address of `scan_key`.
+///
+/// Outputs whose keys or scripts do not parse cannot pay any address and are skipped.
+pub fn scan_transaction(
+    ctx: &impl Verification,
+    scan_key: &StealthScanKey,
+    tx: &Transaction,
+) -> Result<Vec<StealthOutput>, Error> {
+    let mut found = Vec::new();
+    for (i, out) in tx.transaction_out().iter().enumerate() {
+        match out {
+            TransactionOut::Ct(out) => {
+                let ephemeral_public_key = match PublicKey::parse(ctx, out.ephemeral_public_key()) {
+                    Ok(key) => key,
+                    Err(_) => continue,
+                };
+                if script::solve(out.script()) != ScriptKind::PubKeyHash {
+                    continue;
+                }
+                let output_key = scan_key.output_key(ctx, &ephemeral_public_key)?;
+                if hash::hash160(&output_key.serialize_compressed(ctx)?) == out.script()[3..23] {
+                    found.push(StealthOutput::Ct(i));
+                }
+            }
+            TransactionOut::RingCt(out) => {
+                let keys = (
+                    PublicKey::parse(ctx, out.ephemeral_public_key()),
+                    PublicKey::parse(ctx, out.public_key()),
+                );
+                if let (Ok(ephemeral_public_key), Ok(public_key)) = keys {
+                    if scan_key.is_mine(ctx, &ephemeral_public_key, &public_key)? {
+                        found.push(StealthOutput::RingCt(i));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::{Network, PubkeyHashAddress};
+    use crate::amount::COIN;
+    use crate::primitives::transaction::{CtOut, DataOut, RingCtOut, StandardOut};
+    use libsecp256k1_veil::{Context, ContextAll, PrivateKey, StealthAddress};
+
+    #[test]
+    fn scan() {
+        let ctx = ContextAll::new();
+        let scan = PrivateKey::new(&ctx);
+        let spend = PrivateKey::new(&ctx);
+        let address = StealthAddress::new(&ctx, &scan, &spend).unwrap();
+        let scan_key = StealthScanKey::new(scan, address.spend_public_key);
+        let other = StealthAddress::new(&ctx, &PrivateKey::new(&ctx), &spend).unwrap();
+
+        // Outputs of every kind are mixed, as veild allows.
+        let mut tx = Transaction::new();
+        tx.push_data_out(DataOut(1000));
+        for address in &[&other, &address] {
+            let ephemeral = PrivateKey::new(&ctx);
+            let ephemeral_public_key = PublicKey::new(&ctx, &ephemeral)
+                .unwrap()
+                .serialize_compressed(&ctx)
+                .unwrap();
+            let output_key = address
+                .derive_output_key(&ctx, &ephemeral)
+                .unwrap()
+                .serialize_compressed(&ctx)
+                .unwrap();
+
+            let mut out = CtOut::new();
+            out.set_ephemeral_public_key(ephemeral_public_key.to_vec());
+            out.set_script(
+                PubkeyHashAddress::from_public_key(Network::Main, &output_key).script_pubkey(),
+            );
+            tx.push_ct_out(out);
+            tx.push_standard_out(StandardOut::new(COIN, vec![0x51]));
+
+            let mut out = RingCtOut::new();
+            out.set_ephemeral_public_key(ephemeral_public_key.to_vec());
+            out.set_public_key(output_key.to_vec());
+            tx.push_ring_ct_out(out);
+        }
+        // Garbage keys are skipped rather than failing the scan.
+        let mut out = RingCtOut::new();
+        out.set_ephemeral_public_key(vec![2; 33]);
+        out.set_public_key(vec![0; 33]);
+        tx.push_ring_ct_out(out);
+
+        assert_eq!(
+            scan_transaction(&ctx, &scan_key, &tx).unwrap(),
+            [StealthOutput::Ct(4), StealthOutput::RingCt(6)]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Block subsidy schedule.
+//!
+//! Every block pays the staker or miner a block reward. Founder, lab and budget payments accrue
+//! with every block and are paid out together in a superblock once every budget payment cycle.
+
+use crate::amount::{Amount, COIN};
+use crate::prelude::*;
+
+/// Rewards, either for a single block or accrued over a budget payment cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BlockRewards {
+    /// Paid to the staker or miner of the block.
+    pub block: Amount,
+    /// Paid to the founders.
+    pub founder: Amount,
+    /// Paid to the Veil Lab development fund.
+    pub lab: Amount,
+    /// Paid to the Veil Foundation budget.
+    pub budget: Amount,
+}
+
+impl BlockRewards {
+    const fn coins(block: Amount, founder: Amount, lab: Amount, budget: Amount) -> Self {
+        Self {
+            block: block * COIN,
+            founder: founder * COIN,
+            lab: lab * COIN,
+            budget: budget * COIN,
+        }
+    }
+
+    /// Everything paid out in a superblock.
+    pub fn budget_total(&self) -> Amount {
+        self.founder + self.lab + self.budget
+    }
+}
+
+/// Subsidy schedule parameters, the defaults are main net's.
+pub struct SubsidyParams {
+    /// Number of blocks before the rewards step down to the next period.
+    blocks_per_period: u32,
+    /// Number of blocks between superblocks.
+    budget_payment_cycle: u32,
+    /// Height after which no new coins are created.
+    supply_creation_stop: u32,
+    /// Rewards for each period, the last one applies to every later period.
+    periods: Vec<BlockRewards>,
+}
+
+impl Default for SubsidyParams {
+    fn default() -> Self {
+        Self {
+            blocks_per_period: 525_600,
+            budget_payment_cycle: 43_200,
+            supply_creation_stop: 9_816_000,
+            periods: vec![
+                BlockRewards::coins(50, 10, 10, 30),
+                BlockRewards::coins(40, 8, 8, 24),
+                BlockRewards::coins(30, 6, 6, 18),
+                BlockRewards::coins(20, 4, 4, 12),
+                BlockRewards::coins(10, 0, 2, 6),
+            ],
+        }
+    }
+}
+
+impl SubsidyParams {
+    /// Returns `None` if either block count is zero or there are no periods.
+    pub fn new(
+        blocks_per_period: u32,
+        budget_payment_cycle: u32,
+        supply_creation_stop: u32,
+        periods: Vec<BlockRewards>,
+    ) -> Option<Self> {
+        if blocks_per_period == 0 || budget_payment_cycle == 0 || periods.is_empty() {
+            return None;
+        }
+        Some(Self {
+            blocks_per_period,
+            budget_payment_cycle,
+            supply_creation_stop,
+            periods,
+        })
+    }
+
+    /// Rewards created by the block at `height`, before any superblock payout.
+    pub fn block_rewards(&self, height: u32) -> BlockRewards {
+        if height == 0 || height > self.supply_creation_stop {
+            return BlockRewards::default();
+        }
+
+        let period = ((height - 1) / self.blocks_per_period) as usize;
+        self.periods[period.min(self.periods.len() - 1)]
+    }
+
+    /// The reward paid to the staker or miner of the block at `height`.
+    pub fn block_subsidy(&self, height: u32) -> Amount {
+        self.block_rewards(height).block
+    }
+
+    pub fn is_superblock(&self, height: u32) -> bool {
+        height != 0 && height % self.budget_payment_cycle == 0
+    }
+
+    /// The first superblock after `height`.
+    pub fn next_superblock(&self, height: u32) -> u32 {
+        (height / self.budget_payment_cycle + 1) * self.budget_payment_cycle
+    }
+
+    /// Founder, lab and budget payments made by the superblock at `height`.
+    ///
+    /// Returns `None` if `height` is not a superblock. The payments are the per block rates at
+    /// the superblock's height for a whole cycle.
+    pub fn superblock_payments(&self, height: u32) -> Option<BlockRewards> {
+        if !self.is_superblock(height) {
+            return None;
+        }
+
+        let rates = self.block_rewards(height);
+        let cycle = Amount::from(self.budget_payment_cycle);
+        Some(BlockRewards {
+            block: 0,
+            founder: rates.founder * cycle,
+            lab: rates.lab * cycle,
+            budget: rates.budget * cycle,
+        })
+    }
+
+    /// Total amount of coins created up to and including the block at `height`.
+    ///
+    /// Fees are not subtracted, so veild's money supply can be slightly lower than this.
+    pub fn money_supply_at(&self, height: u32) -> Amount {
+        let height = height.min(self.supply_creation_stop);
+        let cycle = self.budget_payment_cycle;
+        let mut supply: Amount = 0;
+
+        let mut start = 1;
+        for (i, rewards) in self.periods.iter().enumerate() {
+            if start > height {
+                break;
+            }
+            let end = if i + 1 == self.periods.len() {
+                height
+            } else {
+                height.min(start + self.blocks_per_period - 1)
+            };
+
+            let blocks = Amount::from(end - start + 1);
+            let superblocks = Amount::from(end / cycle - (start - 1) / cycle);
+            supply += rewards.block * blocks;
+            supply += rewards.budget_total() * Amount::from(cycle) * superblocks;
+
+            start = end + 1;
+        }
+
+        supply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::MAX_MONEY;
+
+    #[test]
+    fn periods() {
+        let params = SubsidyParams::default();
+        assert_eq!(params.block_subsidy(0), 0);
+        assert_eq!(params.block_subsidy(1), 50 * COIN);
+        assert_eq!(params.block_subsidy(525_600), 50 * COIN);
+        assert_eq!(params.block_subsidy(525_601), 40 * COIN);
+        assert_eq!(params.block_subsidy(5_000_000), 10 * COIN);
+        assert_eq!(params.block_subsidy(9_816_001), 0);
+    }
+
+    #[test]
+    fn superblocks() {
+        let params = SubsidyParams::default();
+        assert!(!params.is_superblock(0));
+        assert!(params.is_superblock(43_200));
+        assert_eq!(params.next_superblock(43_200), 86_400);
+        assert_eq!(params.superblock_payments(43_201), None);
+        assert_eq!(
+            params.superblock_payments(43_200).unwrap().budget_total(),
+            50 * COIN * 43_200
+        );
+    }
+
+    #[test]
+    fn money_supply_matches_blocks() {
+        let params = SubsidyParams::new(7, 3, 40, SubsidyParams::default().periods).unwrap();
+
+        let mut supply = 0;
+        for height in 0..50 {
+            supply += params.block_subsidy(height);
+            if let Some(payments) = params.superblock_payments(height) {
+                supply += payments.budget_total();
+            }
+            assert_eq!(params.money_supply_at(height), supply, "height {}", height);
+        }
+    }
+
+    #[test]
+    fn invalid_params() {
+        let periods = || SubsidyParams::default().periods;
+        assert!(SubsidyParams::new(0, 3, 40, periods()).is_none());
+        assert!(SubsidyParams::new(7, 0, 40, periods()).is_none());
+        assert!(SubsidyParams::new(7, 3, 40, Vec::new()).is_none());
+    }
+
+    #[test]
+    fn money_supply_below_max_money() {
+        let params = SubsidyParams::default();
+        assert!(params.money_supply_at(u32::MAX) <= MAX_MONEY);
+    }
+}
@@ -22,8 +22,6 @@
 // ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
 // POSSIBILITY OF SUCH DAMAGE.
 
-use std::u64;
-
 pub trait MulOverflow {
     fn mul_with_overflow(&self, rhs: Self) -> (Self, Self, bool)
     where
@@ -67,3 +65,10 @@ impl MulOverflow for u64 {
         (result_hi, result_lo, overflowed)
     }
 }
+
+/// Overwrites `data` with zeros in a way the compiler will not optimize out, for secrets.
+pub(crate) fn zero(data: &mut [u8]) {
+    for byte in data.iter_mut() {
+        unsafe { core::ptr::write_volatile(byte, 0) };
+    }
+}
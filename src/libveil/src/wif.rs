@@ -0,0 +1,194 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+//! Wallet import format, the base58 private keys `dumpprivkey` and `importprivkey` use.
+
+use crate::address::Network;
+use crate::prelude::*;
+use crate::util::zero;
+use libsecp256k1_veil::{Context, PrivateKey};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+/// Flag byte following the key when its public key is used compressed.
+const COMPRESSED_FLAG: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum WifError {
+    /// Not base58, or the checksum does not match.
+    InvalidBase58,
+    InvalidLength(usize),
+    UnknownPrefix(u8),
+    InvalidCompressionFlag(u8),
+    /// Zero or not below the curve order.
+    InvalidPrivateKey,
+}
+
+impl fmt::Display for WifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WifError::InvalidBase58 => f.write_str("invalid base58 or checksum"),
+            WifError::InvalidLength(len) => write!(f, "invalid private key length {}", len),
+            WifError::UnknownPrefix(prefix) => write!(f, "unknown private key prefix {}", prefix),
+            WifError::InvalidCompressionFlag(flag) => {
+                write!(f, "invalid compression flag {}", flag)
+            }
+            WifError::InvalidPrivateKey => f.write_str("private key out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for WifError {}
+
+/// A private key along with the network it is for and whether its addresses use the compressed
+/// public key.
+#[derive(Debug, PartialEq)]
+pub struct Wif {
+    pub network: Network,
+    pub compressed: bool,
+    pub private_key: PrivateKey,
+}
+
+impl Wif {
+    pub fn decode(ctx: &impl Context, s: &str) -> Result<Self, WifError> {
+        let mut data = bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(|_| WifError::InvalidBase58)?;
+        let wif = Self::from_data(ctx, &data);
+        zero(&mut data);
+        wif
+    }
+
+    fn from_data(ctx: &impl Context, data: &[u8]) -> Result<Self, WifError> {
+        let compressed = match data.len() {
+            33 => false,
+            34 if data[33] == COMPRESSED_FLAG => true,
+            34 => return Err(WifError::InvalidCompressionFlag(data[33])),
+            len => return Err(WifError::InvalidLength(len)),
+        };
+        let network =
+            Network::from_secret_key_prefix(data[0]).ok_or(WifError::UnknownPrefix(data[0]))?;
+        let private_key =
+            PrivateKey::from_slice(ctx, &data[1..33]).map_err(|_| WifError::InvalidPrivateKey)?;
+
+        Ok(Self {
+            network,
+            compressed,
+            private_key,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let mut data = [0u8; 34];
+        data[0] = self.network.secret_key_prefix();
+        data[1..33].copy_from_slice(&self.private_key.to_bytes());
+        data[33] = COMPRESSED_FLAG;
+        let len = if self.compressed { 34 } else { 33 };
+
+        let encoded = bs58::encode(&data[..len]).with_check().into_string();
+        zero(&mut data);
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libsecp256k1_veil::ContextNone;
+
+    // Veil shares its secret key prefixes with Bitcoin, these are the vectors from the Bitcoin
+    // wiki.
+    const KEY: [u8; 32] = [
+        0x0c, 0x28, 0xfc, 0xa3, 0x86, 0xc7, 0xa2, 0x27, 0x60, 0x0b, 0x2f, 0xe5, 0x0b, 0x7c, 0xae,
+        0x11, 0xec, 0x86, 0xd3, 0xbf, 0x1f, 0xbe, 0x47, 0x1b, 0xe8, 0x98, 0x27, 0xe1, 0x9d, 0x72,
+        0xaa, 0x1d,
+    ];
+    const UNCOMPRESSED: &str = "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ";
+    const COMPRESSED: &str = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+
+    #[test]
+    fn vectors() {
+        let ctx = ContextNone::new();
+        let wif = Wif::decode(&ctx, UNCOMPRESSED).unwrap();
+        assert_eq!(wif.network, Network::Main);
+        assert!(!wif.compressed);
+        assert_eq!(wif.private_key.to_bytes(), KEY);
+        assert_eq!(wif.encode(), UNCOMPRESSED);
+
+        let wif = Wif::decode(&ctx, COMPRESSED).unwrap();
+        assert_eq!(wif.network, Network::Main);
+        assert!(wif.compressed);
+        assert_eq!(wif.private_key.to_bytes(), KEY);
+        assert_eq!(wif.encode(), COMPRESSED);
+    }
+
+    #[test]
+    fn round_trip() {
+        let ctx = ContextNone::new();
+        let wif = Wif {
+            network: Network::Test,
+            compressed: true,
+            private_key: PrivateKey::new(&ctx),
+        };
+        let encoded = wif.encode();
+        assert!(encoded.starts_with('c'));
+        assert_eq!(Wif::decode(&ctx, &encoded), Ok(wif));
+    }
+
+    #[test]
+    fn invalid() {
+        let ctx = ContextNone::new();
+        let encode = |data: &[u8]| bs58::encode(data).with_check().into_string();
+        let mut data = [1u8; 34];
+        data[0] = 128;
+
+        assert_eq!(
+            Wif::decode(&ctx, &encode(&data[..32])),
+            Err(WifError::InvalidLength(32))
+        );
+        data[33] = 2;
+        assert_eq!(
+            Wif::decode(&ctx, &encode(&data)),
+            Err(WifError::InvalidCompressionFlag(2))
+        );
+        data[0] = 0;
+        assert_eq!(
+            Wif::decode(&ctx, &encode(&data[..33])),
+            Err(WifError::UnknownPrefix(0))
+        );
+        data[0] = 128;
+        data[1..33].copy_from_slice(&[0; 32]);
+        assert_eq!(
+            Wif::decode(&ctx, &encode(&data[..33])),
+            Err(WifError::InvalidPrivateKey)
+        );
+
+        let mut corrupted = COMPRESSED.to_string();
+        corrupted.pop();
+        corrupted.push('1');
+        assert_eq!(Wif::decode(&ctx, &corrupted), Err(WifError::InvalidBase58));
+    }
+}
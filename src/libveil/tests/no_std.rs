@@ -0,0 +1,96 @@
+// Copyright 2020 Veil Rust Developers
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice,
+// this list of conditions and the following disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+// ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+// LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+// CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+// SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+// INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+// CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+// ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+// POSSIBILITY OF SUCH DAMAGE.
+
+//! Builds the crate without `std`, the way hardware signer firmware uses it.
+//!
+//! The host check always runs, `#![no_std]` rejects any use of `std` outside the gated modules
+//! there too. The check against `thumbv7em-none-eabihf` needs that target and an ARM C toolchain
+//! for libsecp256k1, so it is ignored by default and run in CI with `cargo test -p veil --test
+//! no_std -- --ignored`.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+fn succeeds(command: &mut Command) -> bool {
+    command
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn embedded_target_available() -> bool {
+    let sysroot = match Command::new("rustc").args(["--print", "sysroot"]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+        Err(_) => return false,
+    };
+    Path::new(&sysroot)
+        .join("lib/rustlib")
+        .join(TARGET)
+        .exists()
+        && succeeds(Command::new("arm-none-eabi-gcc").arg("--version"))
+}
+
+fn check(target: Option<&str>) {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+
+    let mut command = Command::new(cargo);
+    command
+        .arg("check")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .args(["--no-default-features", "--features", "alloc"])
+        .env("CARGO_TARGET_DIR", manifest_dir.join("../../target/no_std"));
+    if let Some(target) = target {
+        command.args(["--target", target]);
+    }
+
+    let output = command.output().expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "no_std build failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn no_std_host() {
+    check(None);
+}
+
+#[test]
+#[ignore]
+fn no_std_embedded() {
+    assert!(
+        embedded_target_available(),
+        "{} or arm-none-eabi-gcc is missing, install them with `rustup target add {}` and the \
+         gcc-arm-none-eabi package",
+        TARGET,
+        TARGET
+    );
+    check(Some(TARGET));
+}